@@ -0,0 +1,37 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS "public"."client" (
+    "id" serial PRIMARY KEY,
+    "name" text UNIQUE NOT NULL,
+    "api_key_hash" text UNIQUE NOT NULL,
+    "description" text NOT NULL DEFAULT '',
+    "is_active" boolean NOT NULL DEFAULT FALSE,
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON COLUMN "public"."client"."id" IS '主键自增id';
+
+COMMENT ON COLUMN "public"."client"."name" IS '客户端名称';
+
+COMMENT ON COLUMN "public"."client"."api_key_hash" IS '客户端密钥(sha256)';
+
+COMMENT ON COLUMN "public"."client"."description" IS '客户端说明';
+
+COMMENT ON COLUMN "public"."client"."is_active" IS '是否启用';
+
+COMMENT ON COLUMN "public"."client"."created_at" IS '创建时间';
+
+COMMENT ON COLUMN "public"."client"."updated_at" IS '更新时间';
+
+COMMENT ON TABLE "public"."client" IS '客户端表';
+
+CREATE TRIGGER update_client_timestamp
+    BEFORE UPDATE ON "public"."client"
+    FOR EACH ROW
+    WHEN (NEW IS DISTINCT FROM OLD)
+    EXECUTE FUNCTION update_timestamp ();
+
+CREATE TRIGGER track_client_change
+    AFTER INSERT OR UPDATE OR DELETE ON "public"."client"
+    FOR EACH ROW
+    EXECUTE FUNCTION track_change ();
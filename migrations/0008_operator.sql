@@ -0,0 +1,41 @@
+-- Add migration script here
+ALTER TABLE "public"."change_log"
+    ADD COLUMN IF NOT EXISTS "client_id" int NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS "operator" text NOT NULL DEFAULT '';
+
+COMMENT ON COLUMN "public"."change_log"."client_id" IS '客户端id(0表示直接操作数据库)';
+
+COMMENT ON COLUMN "public"."change_log"."operator" IS '操作人';
+
+ALTER TABLE "public"."account_log"
+    ADD COLUMN IF NOT EXISTS "client_id" int NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS "operator" text NOT NULL DEFAULT '';
+
+COMMENT ON COLUMN "public"."account_log"."client_id" IS '客户端id';
+
+COMMENT ON COLUMN "public"."account_log"."operator" IS '操作人';
+
+-- 操作人取自会话设置 `amazing.client_id` 和 `amazing.operator`
+-- 未设置时(例如直接通过 psql 修改配置表)，操作人记录为当前数据库用户
+CREATE OR REPLACE FUNCTION track_change ()
+    RETURNS TRIGGER
+    AS $$
+DECLARE
+    v_client_id int := COALESCE(NULLIF(current_setting('amazing.client_id', TRUE), ''), '0')::int;
+    v_operator text := COALESCE(NULLIF(current_setting('amazing.operator', TRUE), ''), SESSION_USER);
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO change_log (TABLE_NAME, operation_type, new_data, client_id, operator)
+            VALUES (TG_TABLE_NAME, 'INSERT', ROW_TO_JSON(NEW)::jsonb - 'created_at' - 'updated_at', v_client_id, v_operator);
+    ELSIF TG_OP = 'UPDATE'
+            AND NEW IS DISTINCT FROM OLD THEN
+            INSERT INTO change_log (TABLE_NAME, operation_type, old_data, new_data, client_id, operator)
+                VALUES (TG_TABLE_NAME, 'UPDATE', ROW_TO_JSON(OLD)::jsonb - 'created_at' - 'updated_at', ROW_TO_JSON(NEW)::jsonb - 'created_at' - 'updated_at', v_client_id, v_operator);
+    ELSIF TG_OP = 'DELETE' THEN
+        INSERT INTO change_log (TABLE_NAME, operation_type, old_data, client_id, operator)
+            VALUES (TG_TABLE_NAME, 'DELETE', ROW_TO_JSON(OLD)::jsonb - 'created_at' - 'updated_at', v_client_id, v_operator);
+    END IF;
+    RETURN NEW;
+END;
+$$
+LANGUAGE plpgsql;
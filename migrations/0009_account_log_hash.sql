@@ -0,0 +1,7 @@
+-- Add migration script here
+ALTER TABLE "public"."account_log"
+    ADD COLUMN IF NOT EXISTS "hash" text NOT NULL DEFAULT '';
+
+CREATE INDEX IF NOT EXISTS account_log_account_id_id_idx ON "public"."account_log" ("account_id", "id");
+
+COMMENT ON COLUMN "public"."account_log"."hash" IS '日志哈希(本条日志内容与同账户上一条日志哈希共同计算)';
@@ -0,0 +1,113 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS "public"."tenant" (
+    "id" serial PRIMARY KEY,
+    "name" text UNIQUE NOT NULL,
+    "description" text NOT NULL DEFAULT '',
+    "is_active" boolean NOT NULL DEFAULT FALSE,
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON COLUMN "public"."tenant"."id" IS '主键自增id';
+
+COMMENT ON COLUMN "public"."tenant"."name" IS '租户名称';
+
+COMMENT ON COLUMN "public"."tenant"."description" IS '租户说明';
+
+COMMENT ON COLUMN "public"."tenant"."is_active" IS '是否启用';
+
+COMMENT ON COLUMN "public"."tenant"."created_at" IS '创建时间';
+
+COMMENT ON COLUMN "public"."tenant"."updated_at" IS '更新时间';
+
+COMMENT ON TABLE "public"."tenant" IS '租户表';
+
+-- 已有数据均归属默认租户
+INSERT INTO "public"."tenant" ("id", "name", "description", "is_active")
+    VALUES (1, 'default', '默认租户', 't');
+
+SELECT setval(pg_get_serial_sequence('"public"."tenant"', 'id'), 1);
+
+ALTER TABLE "public"."change_log"
+    ADD COLUMN IF NOT EXISTS "tenant_id" int NOT NULL DEFAULT 0;
+
+COMMENT ON COLUMN "public"."change_log"."tenant_id" IS '租户id(0表示不属于任何租户)';
+
+UPDATE "public"."change_log" SET "tenant_id" = 1
+WHERE "table_name" IN ('asset_type', 'action_type', 'client');
+
+CREATE INDEX IF NOT EXISTS change_log_tenant_id_log_id_idx ON "public"."change_log" ("tenant_id", "log_id");
+
+-- 租户id取自变更行的 `tenant_id` 字段，没有该字段的表记为 0
+CREATE OR REPLACE FUNCTION track_change ()
+    RETURNS TRIGGER
+    AS $$
+DECLARE
+    v_client_id int := COALESCE(NULLIF(current_setting('amazing.client_id', TRUE), ''), '0')::int;
+    v_operator text := COALESCE(NULLIF(current_setting('amazing.operator', TRUE), ''), SESSION_USER);
+    v_tenant_id int;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        v_tenant_id := COALESCE((ROW_TO_JSON(OLD)::jsonb ->> 'tenant_id')::int, 0);
+    ELSE
+        v_tenant_id := COALESCE((ROW_TO_JSON(NEW)::jsonb ->> 'tenant_id')::int, 0);
+    END IF;
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO change_log (TABLE_NAME, operation_type, new_data, client_id, operator, tenant_id)
+            VALUES (TG_TABLE_NAME, 'INSERT', ROW_TO_JSON(NEW)::jsonb - 'created_at' - 'updated_at', v_client_id, v_operator, v_tenant_id);
+    ELSIF TG_OP = 'UPDATE'
+            AND NEW IS DISTINCT FROM OLD THEN
+            INSERT INTO change_log (TABLE_NAME, operation_type, old_data, new_data, client_id, operator, tenant_id)
+                VALUES (TG_TABLE_NAME, 'UPDATE', ROW_TO_JSON(OLD)::jsonb - 'created_at' - 'updated_at', ROW_TO_JSON(NEW)::jsonb - 'created_at' - 'updated_at', v_client_id, v_operator, v_tenant_id);
+    ELSIF TG_OP = 'DELETE' THEN
+        INSERT INTO change_log (TABLE_NAME, operation_type, old_data, client_id, operator, tenant_id)
+            VALUES (TG_TABLE_NAME, 'DELETE', ROW_TO_JSON(OLD)::jsonb - 'created_at' - 'updated_at', v_client_id, v_operator, v_tenant_id);
+    END IF;
+    RETURN NEW;
+END;
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER update_tenant_timestamp
+    BEFORE UPDATE ON "public"."tenant"
+    FOR EACH ROW
+    WHEN (NEW IS DISTINCT FROM OLD)
+    EXECUTE FUNCTION update_timestamp ();
+
+CREATE TRIGGER track_tenant_change
+    AFTER INSERT OR UPDATE OR DELETE ON "public"."tenant"
+    FOR EACH ROW
+    EXECUTE FUNCTION track_change ();
+
+-- 资产类型、账户操作类型名称在租户内唯一
+ALTER TABLE "public"."asset_type"
+    ADD COLUMN IF NOT EXISTS "tenant_id" int NOT NULL DEFAULT 1,
+    DROP CONSTRAINT IF EXISTS "asset_type_name_key",
+    ADD CONSTRAINT "asset_type_tenant_id_name_key" UNIQUE ("tenant_id", "name");
+
+COMMENT ON COLUMN "public"."asset_type"."tenant_id" IS '租户id';
+
+ALTER TABLE "public"."action_type"
+    ADD COLUMN IF NOT EXISTS "tenant_id" int NOT NULL DEFAULT 1,
+    DROP CONSTRAINT IF EXISTS "action_type_name_key",
+    ADD CONSTRAINT "action_type_tenant_id_name_key" UNIQUE ("tenant_id", "name");
+
+COMMENT ON COLUMN "public"."action_type"."tenant_id" IS '租户id';
+
+ALTER TABLE "public"."client"
+    ADD COLUMN IF NOT EXISTS "tenant_id" int NOT NULL DEFAULT 1;
+
+COMMENT ON COLUMN "public"."client"."tenant_id" IS '租户id';
+
+-- 不同租户的`user_id`相互独立
+ALTER TABLE "public"."account"
+    ADD COLUMN IF NOT EXISTS "tenant_id" int NOT NULL DEFAULT 1,
+    DROP CONSTRAINT IF EXISTS "account_user_id_asset_type_id_key",
+    ADD CONSTRAINT "account_tenant_id_user_id_asset_type_id_key" UNIQUE ("tenant_id", "user_id", "asset_type_id");
+
+COMMENT ON COLUMN "public"."account"."tenant_id" IS '租户id';
+
+ALTER TABLE "public"."account_log"
+    ADD COLUMN IF NOT EXISTS "tenant_id" int NOT NULL DEFAULT 1;
+
+COMMENT ON COLUMN "public"."account_log"."tenant_id" IS '租户id';
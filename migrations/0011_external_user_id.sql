@@ -0,0 +1,7 @@
+-- Add migration script here
+-- 外部用户标识兼容整数(如 64 位雪花 id)和字符串(如 UUID)，统一按字符串存储，已有整数 id 原样转换
+ALTER TABLE "public"."account"
+    ALTER COLUMN "user_id" TYPE text USING "user_id"::text,
+    ADD CONSTRAINT "account_user_id_check" CHECK ("user_id" <> '');
+
+COMMENT ON COLUMN "public"."account"."user_id" IS '外部用户标识';
@@ -0,0 +1,104 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS "public"."exchange_rate" (
+    "id" serial PRIMARY KEY,
+    "tenant_id" int NOT NULL DEFAULT 1,
+    "from_asset_type_id" int NOT NULL,
+    "to_asset_type_id" int NOT NULL,
+    "rate" DECIMAL(18, 8) NOT NULL CHECK ("rate" > 0),
+    "spread" DECIMAL(8, 6) NOT NULL DEFAULT 0 CHECK ("spread" >= 0 AND "spread" < 1),
+    "effective_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "is_active" boolean NOT NULL DEFAULT FALSE,
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ("from_asset_type_id" <> "to_asset_type_id"),
+    UNIQUE ("tenant_id", "from_asset_type_id", "to_asset_type_id", "effective_at")
+);
+
+COMMENT ON COLUMN "public"."exchange_rate"."id" IS '主键自增id';
+
+COMMENT ON COLUMN "public"."exchange_rate"."tenant_id" IS '租户id';
+
+COMMENT ON COLUMN "public"."exchange_rate"."from_asset_type_id" IS '源资产类型id';
+
+COMMENT ON COLUMN "public"."exchange_rate"."to_asset_type_id" IS '目标资产类型id';
+
+COMMENT ON COLUMN "public"."exchange_rate"."rate" IS '汇率(1 单位源资产可兑换的目标资产数量)';
+
+COMMENT ON COLUMN "public"."exchange_rate"."spread" IS '点差(按比例从兑换所得中扣除)';
+
+COMMENT ON COLUMN "public"."exchange_rate"."effective_at" IS '生效时间';
+
+COMMENT ON COLUMN "public"."exchange_rate"."is_active" IS '是否启用';
+
+COMMENT ON COLUMN "public"."exchange_rate"."created_at" IS '创建时间';
+
+COMMENT ON COLUMN "public"."exchange_rate"."updated_at" IS '更新时间';
+
+COMMENT ON TABLE "public"."exchange_rate" IS '汇率表';
+
+CREATE TRIGGER update_exchange_rate_timestamp
+    BEFORE UPDATE ON "public"."exchange_rate"
+    FOR EACH ROW
+    WHEN (NEW IS DISTINCT FROM OLD)
+    EXECUTE FUNCTION update_timestamp ();
+
+CREATE TRIGGER track_exchange_rate_change
+    AFTER INSERT OR UPDATE OR DELETE ON "public"."exchange_rate"
+    FOR EACH ROW
+    EXECUTE FUNCTION track_change ();
+
+CREATE TABLE IF NOT EXISTS "public"."exchange_log" (
+    "id" bigserial PRIMARY KEY,
+    "tenant_id" int NOT NULL,
+    "user_id" text NOT NULL,
+    "exchange_rate_id" int NOT NULL,
+    "from_asset_type_id" int NOT NULL,
+    "to_asset_type_id" int NOT NULL,
+    "rate" DECIMAL(18, 8) NOT NULL,
+    "spread" DECIMAL(8, 6) NOT NULL,
+    "from_amount" DECIMAL(18, 6) NOT NULL,
+    "to_amount" DECIMAL(18, 6) NOT NULL,
+    "order_number" text NOT NULL,
+    "description" text NOT NULL DEFAULT '',
+    "client_id" int NOT NULL DEFAULT 0,
+    "operator" text NOT NULL DEFAULT '',
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE ("tenant_id", "order_number")
+);
+
+COMMENT ON COLUMN "public"."exchange_log"."id" IS '主键自增id';
+
+COMMENT ON COLUMN "public"."exchange_log"."tenant_id" IS '租户id';
+
+COMMENT ON COLUMN "public"."exchange_log"."user_id" IS '外部用户标识';
+
+COMMENT ON COLUMN "public"."exchange_log"."exchange_rate_id" IS '使用的汇率id';
+
+COMMENT ON COLUMN "public"."exchange_log"."from_asset_type_id" IS '源资产类型id';
+
+COMMENT ON COLUMN "public"."exchange_log"."to_asset_type_id" IS '目标资产类型id';
+
+COMMENT ON COLUMN "public"."exchange_log"."rate" IS '兑换时的汇率';
+
+COMMENT ON COLUMN "public"."exchange_log"."spread" IS '兑换时的点差';
+
+COMMENT ON COLUMN "public"."exchange_log"."from_amount" IS '扣减的源资产数量';
+
+COMMENT ON COLUMN "public"."exchange_log"."to_amount" IS '增加的目标资产数量';
+
+COMMENT ON COLUMN "public"."exchange_log"."order_number" IS '订单号(与两条账户操作日志相同)';
+
+COMMENT ON COLUMN "public"."exchange_log"."description" IS '兑换说明';
+
+COMMENT ON COLUMN "public"."exchange_log"."client_id" IS '客户端id';
+
+COMMENT ON COLUMN "public"."exchange_log"."operator" IS '操作人';
+
+COMMENT ON COLUMN "public"."exchange_log"."created_at" IS '创建时间';
+
+COMMENT ON TABLE "public"."exchange_log" IS '资产兑换日志表';
+
+-- 兑换专用的账户操作类型，不直接开放给`/accounts/actions`
+INSERT INTO "public"."action_type" ("tenant_id", "name", "description", "available_balance_change", "frozen_balance_change", "total_income_change", "total_expense_change", "is_active")
+    VALUES (1, 'EXC_OUT', '兑换支出(可用余额减少 累计支出增加)', 'DEC', 'NONE', 'NONE', 'INC', 'f'),
+    (1, 'EXC_IN', '兑换收入(可用余额增加 累计收入增加)', 'INC', 'NONE', 'INC', 'NONE', 'f');
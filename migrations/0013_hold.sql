@@ -0,0 +1,73 @@
+-- Add migration script here
+CREATE TYPE hold_status_enum AS ENUM (
+    'HELD',
+    'CAPTURED',
+    'RELEASED',
+    'EXPIRED'
+);
+
+CREATE TABLE IF NOT EXISTS "public"."hold" (
+    "id" bigserial PRIMARY KEY,
+    "tenant_id" int NOT NULL DEFAULT 1,
+    "account_id" int NOT NULL,
+    "user_id" text NOT NULL,
+    "asset_type_id" int NOT NULL,
+    "reference" text NOT NULL,
+    "amount" DECIMAL(18, 6) NOT NULL CHECK ("amount" > 0),
+    "captured_amount" DECIMAL(18, 6) NOT NULL DEFAULT 0,
+    "released_amount" DECIMAL(18, 6) NOT NULL DEFAULT 0,
+    "status" hold_status_enum NOT NULL DEFAULT 'HELD',
+    "expires_at" timestamp NOT NULL,
+    "description" text NOT NULL DEFAULT '',
+    "client_id" int NOT NULL DEFAULT 0,
+    "operator" text NOT NULL DEFAULT '',
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ("captured_amount" >= 0 AND "released_amount" >= 0 AND "captured_amount" + "released_amount" <= "amount"),
+    UNIQUE ("tenant_id", "reference")
+);
+
+COMMENT ON COLUMN "public"."hold"."id" IS '主键自增id';
+
+COMMENT ON COLUMN "public"."hold"."tenant_id" IS '租户id';
+
+COMMENT ON COLUMN "public"."hold"."account_id" IS '账户id';
+
+COMMENT ON COLUMN "public"."hold"."user_id" IS '外部用户标识';
+
+COMMENT ON COLUMN "public"."hold"."asset_type_id" IS '资产类型id';
+
+COMMENT ON COLUMN "public"."hold"."reference" IS '外部引用(如支付授权号)，同一租户下唯一';
+
+COMMENT ON COLUMN "public"."hold"."amount" IS '冻结金额';
+
+COMMENT ON COLUMN "public"."hold"."captured_amount" IS '已扣款金额';
+
+COMMENT ON COLUMN "public"."hold"."released_amount" IS '已释放金额';
+
+COMMENT ON COLUMN "public"."hold"."status" IS '状态';
+
+COMMENT ON COLUMN "public"."hold"."expires_at" IS '过期时间';
+
+COMMENT ON COLUMN "public"."hold"."description" IS '预授权说明';
+
+COMMENT ON COLUMN "public"."hold"."client_id" IS '客户端id';
+
+COMMENT ON COLUMN "public"."hold"."operator" IS '操作人';
+
+COMMENT ON COLUMN "public"."hold"."created_at" IS '创建时间';
+
+COMMENT ON COLUMN "public"."hold"."updated_at" IS '更新时间';
+
+COMMENT ON TABLE "public"."hold" IS '预授权(冻结)表';
+
+-- 供过期释放任务查询
+CREATE INDEX IF NOT EXISTS hold_expires_at_idx ON "public"."hold" ("expires_at")
+WHERE
+    "status" = 'HELD';
+
+CREATE TRIGGER update_hold_timestamp
+    BEFORE UPDATE ON "public"."hold"
+    FOR EACH ROW
+    WHEN (NEW IS DISTINCT FROM OLD)
+    EXECUTE FUNCTION update_timestamp ();
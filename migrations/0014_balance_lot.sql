@@ -0,0 +1,57 @@
+-- Add migration script here
+ALTER TABLE "public"."asset_type"
+    ADD COLUMN IF NOT EXISTS "lot_ttl_days" int CHECK ("lot_ttl_days" > 0);
+
+COMMENT ON COLUMN "public"."asset_type"."lot_ttl_days" IS '余额批次有效天数(为空表示不按批次跟踪余额)';
+
+CREATE TABLE IF NOT EXISTS "public"."balance_lot" (
+    "id" bigserial PRIMARY KEY,
+    "tenant_id" int NOT NULL DEFAULT 1,
+    "account_id" int NOT NULL,
+    "amount" DECIMAL(18, 6) NOT NULL CHECK ("amount" > 0),
+    "remaining_amount" DECIMAL(18, 6) NOT NULL CHECK ("remaining_amount" >= 0 AND "remaining_amount" <= "amount"),
+    "expires_at" timestamp NOT NULL,
+    "order_number" text NOT NULL,
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON COLUMN "public"."balance_lot"."id" IS '主键自增id';
+
+COMMENT ON COLUMN "public"."balance_lot"."tenant_id" IS '租户id';
+
+COMMENT ON COLUMN "public"."balance_lot"."account_id" IS '账户id';
+
+COMMENT ON COLUMN "public"."balance_lot"."amount" IS '批次金额';
+
+COMMENT ON COLUMN "public"."balance_lot"."remaining_amount" IS '批次剩余金额';
+
+COMMENT ON COLUMN "public"."balance_lot"."expires_at" IS '过期时间';
+
+COMMENT ON COLUMN "public"."balance_lot"."order_number" IS '产生该批次的订单号';
+
+COMMENT ON COLUMN "public"."balance_lot"."created_at" IS '创建时间';
+
+COMMENT ON COLUMN "public"."balance_lot"."updated_at" IS '更新时间';
+
+COMMENT ON TABLE "public"."balance_lot" IS '余额批次表';
+
+-- 按先进先出扣减时查询
+CREATE INDEX IF NOT EXISTS balance_lot_account_id_expires_at_idx ON "public"."balance_lot" ("account_id", "expires_at", "id")
+WHERE
+    "remaining_amount" > 0;
+
+-- 供过期任务查询
+CREATE INDEX IF NOT EXISTS balance_lot_expires_at_idx ON "public"."balance_lot" ("expires_at")
+WHERE
+    "remaining_amount" > 0;
+
+CREATE TRIGGER update_balance_lot_timestamp
+    BEFORE UPDATE ON "public"."balance_lot"
+    FOR EACH ROW
+    WHEN (NEW IS DISTINCT FROM OLD)
+    EXECUTE FUNCTION update_timestamp ();
+
+-- 余额批次过期专用的账户操作类型，不直接开放给`/accounts/actions`
+INSERT INTO "public"."action_type" ("tenant_id", "name", "description", "available_balance_change", "frozen_balance_change", "total_income_change", "total_expense_change", "is_active")
+    VALUES (1, 'LOT_EXP', '过期(可用余额减少 累计支出增加)', 'DEC', 'NONE', 'NONE', 'INC', 'f');
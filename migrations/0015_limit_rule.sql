@@ -0,0 +1,53 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS "public"."limit_rule" (
+    "id" serial PRIMARY KEY,
+    "tenant_id" int NOT NULL DEFAULT 1,
+    "asset_type_id" int,
+    "action_type_id" int,
+    "window_seconds" int NOT NULL CHECK ("window_seconds" > 0),
+    "max_amount" DECIMAL(18, 6) CHECK ("max_amount" > 0),
+    "max_count" int CHECK ("max_count" > 0),
+    "description" text NOT NULL DEFAULT '',
+    "is_active" boolean NOT NULL DEFAULT FALSE,
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ("max_amount" IS NOT NULL OR "max_count" IS NOT NULL)
+);
+
+COMMENT ON COLUMN "public"."limit_rule"."id" IS '主键自增id';
+
+COMMENT ON COLUMN "public"."limit_rule"."tenant_id" IS '租户id';
+
+COMMENT ON COLUMN "public"."limit_rule"."asset_type_id" IS '资产类型id(为空表示所有资产类型)';
+
+COMMENT ON COLUMN "public"."limit_rule"."action_type_id" IS '账户操作类型id(为空表示所有账户操作类型)';
+
+COMMENT ON COLUMN "public"."limit_rule"."window_seconds" IS '统计窗口(秒)，按 UTC 时间对齐';
+
+COMMENT ON COLUMN "public"."limit_rule"."max_amount" IS '窗口内同一用户累计操作金额上限(为空表示不限)';
+
+COMMENT ON COLUMN "public"."limit_rule"."max_count" IS '窗口内同一用户累计操作次数上限(为空表示不限)';
+
+COMMENT ON COLUMN "public"."limit_rule"."description" IS '规则说明，超出限额时返回给调用方';
+
+COMMENT ON COLUMN "public"."limit_rule"."is_active" IS '是否启用';
+
+COMMENT ON COLUMN "public"."limit_rule"."created_at" IS '创建时间';
+
+COMMENT ON COLUMN "public"."limit_rule"."updated_at" IS '更新时间';
+
+COMMENT ON TABLE "public"."limit_rule" IS '限额规则表';
+
+CREATE TRIGGER update_limit_rule_timestamp
+    BEFORE UPDATE ON "public"."limit_rule"
+    FOR EACH ROW
+    WHEN (NEW IS DISTINCT FROM OLD)
+    EXECUTE FUNCTION update_timestamp ();
+
+CREATE TRIGGER track_limit_rule_change
+    AFTER INSERT OR UPDATE OR DELETE ON "public"."limit_rule"
+    FOR EACH ROW
+    EXECUTE FUNCTION track_change ();
+
+-- 按窗口统计用户已使用的限额
+CREATE INDEX IF NOT EXISTS account_log_account_id_created_at_idx ON "public"."account_log" ("account_id", "created_at");
@@ -0,0 +1,62 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS "public"."fee_rule" (
+    "id" serial PRIMARY KEY,
+    "tenant_id" int NOT NULL DEFAULT 1,
+    "asset_type_id" int NOT NULL,
+    "action_type_id" int NOT NULL,
+    "fixed_amount" DECIMAL(18, 6) NOT NULL DEFAULT 0 CHECK ("fixed_amount" >= 0),
+    "rate" DECIMAL(8, 6) NOT NULL DEFAULT 0 CHECK ("rate" >= 0 AND "rate" < 1),
+    "min_amount" DECIMAL(18, 6) CHECK ("min_amount" >= 0),
+    "max_amount" DECIMAL(18, 6) CHECK ("max_amount" >= 0),
+    "house_user_id" text NOT NULL,
+    "description" text NOT NULL DEFAULT '手续费',
+    "is_active" boolean NOT NULL DEFAULT FALSE,
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ("min_amount" IS NULL OR "max_amount" IS NULL OR "min_amount" <= "max_amount"),
+    UNIQUE ("tenant_id", "asset_type_id", "action_type_id")
+);
+
+COMMENT ON COLUMN "public"."fee_rule"."id" IS '主键自增id';
+
+COMMENT ON COLUMN "public"."fee_rule"."tenant_id" IS '租户id';
+
+COMMENT ON COLUMN "public"."fee_rule"."asset_type_id" IS '资产类型id';
+
+COMMENT ON COLUMN "public"."fee_rule"."action_type_id" IS '账户操作类型id';
+
+COMMENT ON COLUMN "public"."fee_rule"."fixed_amount" IS '固定手续费';
+
+COMMENT ON COLUMN "public"."fee_rule"."rate" IS '按操作金额比例收取的手续费率';
+
+COMMENT ON COLUMN "public"."fee_rule"."min_amount" IS '最低手续费(为空表示不限)';
+
+COMMENT ON COLUMN "public"."fee_rule"."max_amount" IS '最高手续费(为空表示不限)';
+
+COMMENT ON COLUMN "public"."fee_rule"."house_user_id" IS '收取手续费的平台账户`user_id`，资产类型与操作相同';
+
+COMMENT ON COLUMN "public"."fee_rule"."description" IS '手续费说明，记录到手续费账户操作日志';
+
+COMMENT ON COLUMN "public"."fee_rule"."is_active" IS '是否启用';
+
+COMMENT ON COLUMN "public"."fee_rule"."created_at" IS '创建时间';
+
+COMMENT ON COLUMN "public"."fee_rule"."updated_at" IS '更新时间';
+
+COMMENT ON TABLE "public"."fee_rule" IS '手续费规则表';
+
+CREATE TRIGGER update_fee_rule_timestamp
+    BEFORE UPDATE ON "public"."fee_rule"
+    FOR EACH ROW
+    WHEN (NEW IS DISTINCT FROM OLD)
+    EXECUTE FUNCTION update_timestamp ();
+
+CREATE TRIGGER track_fee_rule_change
+    AFTER INSERT OR UPDATE OR DELETE ON "public"."fee_rule"
+    FOR EACH ROW
+    EXECUTE FUNCTION track_change ();
+
+-- 手续费专用的账户操作类型，不直接开放给`/accounts/actions`
+INSERT INTO "public"."action_type" ("tenant_id", "name", "description", "available_balance_change", "frozen_balance_change", "total_income_change", "total_expense_change", "is_active")
+    VALUES (1, 'FEE_EXP', '手续费支出(可用余额减少 累计支出增加)', 'DEC', 'NONE', 'NONE', 'INC', 'f'),
+    (1, 'FEE_INC', '手续费收入(可用余额增加 累计收入增加)', 'INC', 'NONE', 'INC', 'NONE', 'f');
@@ -0,0 +1,26 @@
+-- Add migration script here
+ALTER TABLE "public"."asset_type"
+    ADD COLUMN IF NOT EXISTS "issuance_user_id" text,
+    ADD COLUMN IF NOT EXISTS "burn_user_id" text,
+    ADD COLUMN IF NOT EXISTS "fee_user_id" text,
+    ADD COLUMN IF NOT EXISTS "is_double_entry" boolean NOT NULL DEFAULT FALSE,
+    ADD CONSTRAINT "asset_type_double_entry_check" CHECK (NOT "is_double_entry" OR ("issuance_user_id" IS NOT NULL AND "burn_user_id" IS NOT NULL));
+
+COMMENT ON COLUMN "public"."asset_type"."issuance_user_id" IS '发行系统账户`user_id`，余额为负数，其绝对值为累计净发行量';
+
+COMMENT ON COLUMN "public"."asset_type"."burn_user_id" IS '销毁系统账户`user_id`，余额为累计净销毁量';
+
+COMMENT ON COLUMN "public"."asset_type"."fee_user_id" IS '手续费系统账户`user_id`，手续费规则未指定收取账户时使用';
+
+COMMENT ON COLUMN "public"."asset_type"."is_double_entry" IS '是否启用复式记账，启用后单边操作自动生成发行或销毁系统账户的对应分录';
+
+-- 手续费规则未指定收取账户时计入资产类型的手续费系统账户
+ALTER TABLE "public"."fee_rule"
+    ALTER COLUMN "house_user_id" DROP NOT NULL;
+
+COMMENT ON COLUMN "public"."fee_rule"."house_user_id" IS '收取手续费的平台账户`user_id`，资产类型与操作相同(为空表示使用资产类型的手续费系统账户)';
+
+-- 系统账户对应分录专用的账户操作类型，不直接开放给`/accounts/actions`
+INSERT INTO "public"."action_type" ("tenant_id", "name", "description", "available_balance_change", "frozen_balance_change", "total_income_change", "total_expense_change", "is_active")
+    VALUES (1, 'SYS_ISS', '系统发行(可用余额减少 累计支出增加)', 'DEC', 'NONE', 'NONE', 'INC', 'f'),
+    (1, 'SYS_BRN', '系统销毁(可用余额增加 累计收入增加)', 'INC', 'NONE', 'INC', 'NONE', 'f');
@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE "public"."asset_type"
+    ADD COLUMN IF NOT EXISTS "max_supply" DECIMAL(18, 6) CHECK ("max_supply" > 0);
+
+COMMENT ON COLUMN "public"."asset_type"."max_supply" IS '发行上限，流通量(除发行、销毁系统账户外所有账户的可用余额 + 冻结余额)达到上限后不能再增加(为空表示不限)';
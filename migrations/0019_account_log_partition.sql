@@ -0,0 +1,232 @@
+-- Add migration script here
+-- `account_log`按`created_at`按月分区，未覆盖的时间(如导入的历史数据)写入默认分区
+ALTER TABLE "public"."account_log" RENAME TO "account_log_unpartitioned";
+
+ALTER SEQUENCE "public"."account_log_id_seq" OWNED BY NONE;
+
+CREATE TABLE "public"."account_log" (
+    "id" bigint NOT NULL DEFAULT nextval('account_log_id_seq'),
+    "account_id" int NOT NULL,
+    "action_type_id" int NOT NULL,
+    "amount_available_balance" DECIMAL(18, 6) NOT NULL,
+    "amount_frozen_balance" DECIMAL(18, 6) NOT NULL,
+    "amount_total_income" DECIMAL(18, 6) NOT NULL,
+    "amount_total_expense" DECIMAL(18, 6) NOT NULL,
+    "available_balance_after" DECIMAL(18, 6) NOT NULL,
+    "frozen_balance_after" DECIMAL(18, 6) NOT NULL,
+    "total_income_after" DECIMAL(18, 6) NOT NULL,
+    "total_expense_after" DECIMAL(18, 6) NOT NULL,
+    "order_number" text NOT NULL DEFAULT '',
+    "description" text NOT NULL DEFAULT '',
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "client_id" int NOT NULL DEFAULT 0,
+    "operator" text NOT NULL DEFAULT '',
+    "hash" text NOT NULL DEFAULT '',
+    "tenant_id" int NOT NULL DEFAULT 1
+)
+PARTITION BY RANGE ("created_at");
+
+ALTER SEQUENCE "public"."account_log_id_seq" OWNED BY "public"."account_log"."id";
+
+CREATE TABLE "public"."account_log_default" PARTITION OF "public"."account_log" DEFAULT;
+
+CREATE TYPE account_log_partition_status_enum AS ENUM (
+    'ATTACHED',
+    'DETACHED',
+    'DROPPED'
+);
+
+CREATE TABLE IF NOT EXISTS "public"."account_log_partition" (
+    "name" text PRIMARY KEY,
+    "range_start" timestamp NOT NULL UNIQUE,
+    "range_end" timestamp NOT NULL,
+    "status" account_log_partition_status_enum NOT NULL DEFAULT 'ATTACHED',
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON COLUMN "public"."account_log_partition"."name" IS '分区表名';
+
+COMMENT ON COLUMN "public"."account_log_partition"."range_start" IS '分区开始时间(包含)';
+
+COMMENT ON COLUMN "public"."account_log_partition"."range_end" IS '分区结束时间(不包含)';
+
+COMMENT ON COLUMN "public"."account_log_partition"."status" IS '分区状态';
+
+COMMENT ON COLUMN "public"."account_log_partition"."created_at" IS '创建时间';
+
+COMMENT ON COLUMN "public"."account_log_partition"."updated_at" IS '更新时间';
+
+COMMENT ON TABLE "public"."account_log_partition" IS '账户操作日志月分区表';
+
+CREATE TRIGGER update_account_log_partition_timestamp
+    BEFORE UPDATE ON "public"."account_log_partition"
+    FOR EACH ROW
+    WHEN (NEW IS DISTINCT FROM OLD)
+    EXECUTE FUNCTION update_timestamp ();
+
+-- 创建`p_month`所在月的分区，已创建(包括已归档)时不做任何操作
+CREATE OR REPLACE FUNCTION create_account_log_partition (p_month timestamp)
+    RETURNS void
+    AS $$
+DECLARE
+    v_start timestamp := date_trunc('month', p_month);
+    v_end timestamp := date_trunc('month', p_month) + INTERVAL '1 month';
+    v_name text := 'account_log_' || to_char(date_trunc('month', p_month), 'YYYYMM');
+BEGIN
+    INSERT INTO account_log_partition (name, range_start, range_end)
+        VALUES (v_name, v_start, v_end)
+    ON CONFLICT (name)
+        DO NOTHING;
+    IF NOT FOUND THEN
+        RETURN;
+    END IF;
+    EXECUTE format('CREATE TABLE %I PARTITION OF account_log FOR VALUES FROM (%L) TO (%L)', v_name, v_start, v_end);
+END;
+$$
+LANGUAGE plpgsql;
+
+-- 为已有日志及之后两个月创建分区
+DO $$
+DECLARE
+    v_month timestamp;
+BEGIN
+    SELECT
+        date_trunc('month', COALESCE(MIN(created_at), LOCALTIMESTAMP)) INTO v_month
+    FROM
+        account_log_unpartitioned;
+    WHILE v_month < date_trunc('month', LOCALTIMESTAMP) + INTERVAL '3 months' LOOP
+        PERFORM
+            create_account_log_partition (v_month);
+        v_month := v_month + INTERVAL '1 month';
+    END LOOP;
+END;
+$$;
+
+INSERT INTO "public"."account_log"
+SELECT
+    *
+FROM
+    "public"."account_log_unpartitioned";
+
+-- 分区表的唯一约束需包含分区键，订单号唯一性改由单独的表保证，归档后仍然有效
+CREATE TABLE IF NOT EXISTS "public"."account_log_order" (
+    "account_id" int NOT NULL,
+    "action_type_id" int NOT NULL,
+    "order_number" text NOT NULL,
+    "account_log_id" bigint NOT NULL,
+    PRIMARY KEY ("account_id", "action_type_id", "order_number")
+);
+
+COMMENT ON COLUMN "public"."account_log_order"."account_id" IS '账户id';
+
+COMMENT ON COLUMN "public"."account_log_order"."action_type_id" IS '操作类型id';
+
+COMMENT ON COLUMN "public"."account_log_order"."order_number" IS '订单号';
+
+COMMENT ON COLUMN "public"."account_log_order"."account_log_id" IS '账户操作日志id';
+
+COMMENT ON TABLE "public"."account_log_order" IS '账户操作日志订单号表';
+
+INSERT INTO "public"."account_log_order"
+SELECT
+    account_id,
+    action_type_id,
+    order_number,
+    id
+FROM
+    "public"."account_log_unpartitioned";
+
+DROP TABLE "public"."account_log_unpartitioned";
+
+ALTER TABLE "public"."account_log"
+    ADD PRIMARY KEY ("id", "created_at");
+
+CREATE INDEX IF NOT EXISTS account_log_account_id_id_idx ON "public"."account_log" ("account_id", "id");
+
+CREATE INDEX IF NOT EXISTS account_log_account_id_created_at_idx ON "public"."account_log" ("account_id", "created_at");
+
+-- 同一账户、操作类型及订单号的日志只能写入一次，重复时违反`account_log_order`主键
+CREATE OR REPLACE FUNCTION track_account_log_order ()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    INSERT INTO account_log_order (account_id, action_type_id, order_number, account_log_id)
+        VALUES (NEW.account_id, NEW.action_type_id, NEW.order_number, NEW.id);
+    RETURN NEW;
+END;
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER track_account_log_order
+    AFTER INSERT ON "public"."account_log"
+    FOR EACH ROW
+    EXECUTE FUNCTION track_account_log_order ();
+
+-- 每个账户已归档的最后一条日志，归档后用于延续哈希链及计算余额
+CREATE TABLE IF NOT EXISTS "public"."account_log_checkpoint" (
+    LIKE "public"."account_log",
+    PRIMARY KEY ("account_id")
+);
+
+COMMENT ON TABLE "public"."account_log_checkpoint" IS '账户操作日志归档检查点表';
+
+-- 记录各账户在分区内的最后一条日志后分离分区，分离后的分区表仍保留，导出后再删除
+CREATE OR REPLACE FUNCTION detach_account_log_partition (p_name text)
+    RETURNS void
+    AS $$
+BEGIN
+    UPDATE
+        account_log_partition
+    SET
+        status = 'DETACHED'
+    WHERE
+        name = p_name
+        AND status = 'ATTACHED';
+    IF NOT FOUND THEN
+        RETURN;
+    END IF;
+    EXECUTE format('INSERT INTO account_log_checkpoint SELECT DISTINCT ON (account_id) * FROM %I ORDER BY account_id, id DESC
+        ON CONFLICT (account_id) DO UPDATE SET
+            id = EXCLUDED.id,
+            action_type_id = EXCLUDED.action_type_id,
+            amount_available_balance = EXCLUDED.amount_available_balance,
+            amount_frozen_balance = EXCLUDED.amount_frozen_balance,
+            amount_total_income = EXCLUDED.amount_total_income,
+            amount_total_expense = EXCLUDED.amount_total_expense,
+            available_balance_after = EXCLUDED.available_balance_after,
+            frozen_balance_after = EXCLUDED.frozen_balance_after,
+            total_income_after = EXCLUDED.total_income_after,
+            total_expense_after = EXCLUDED.total_expense_after,
+            order_number = EXCLUDED.order_number,
+            description = EXCLUDED.description,
+            created_at = EXCLUDED.created_at,
+            client_id = EXCLUDED.client_id,
+            operator = EXCLUDED.operator,
+            hash = EXCLUDED.hash,
+            tenant_id = EXCLUDED.tenant_id
+        WHERE account_log_checkpoint.id < EXCLUDED.id', p_name);
+    EXECUTE format('ALTER TABLE account_log DETACH PARTITION %I', p_name);
+END;
+$$
+LANGUAGE plpgsql;
+
+-- 删除已分离的分区表
+CREATE OR REPLACE FUNCTION drop_account_log_partition (p_name text)
+    RETURNS void
+    AS $$
+BEGIN
+    UPDATE
+        account_log_partition
+    SET
+        status = 'DROPPED'
+    WHERE
+        name = p_name
+        AND status = 'DETACHED';
+    IF NOT FOUND THEN
+        RETURN;
+    END IF;
+    EXECUTE format('DROP TABLE %I', p_name);
+END;
+$$
+LANGUAGE plpgsql;
@@ -0,0 +1,93 @@
+-- Add migration script here
+CREATE TYPE action_job_status_enum AS ENUM (
+    'PENDING',
+    'RUNNING',
+    'COMPLETED'
+);
+
+CREATE TYPE action_job_item_status_enum AS ENUM (
+    'PENDING',
+    'SUCCEEDED',
+    'FAILED'
+);
+
+CREATE TABLE IF NOT EXISTS "public"."action_job" (
+    "id" bigserial PRIMARY KEY,
+    "tenant_id" int NOT NULL,
+    "status" action_job_status_enum NOT NULL DEFAULT 'PENDING',
+    "chunk_size" int NOT NULL CHECK ("chunk_size" > 0),
+    "total_count" int NOT NULL,
+    "succeeded_count" int NOT NULL DEFAULT 0,
+    "failed_count" int NOT NULL DEFAULT 0,
+    "client_id" int NOT NULL DEFAULT 0,
+    "operator" text NOT NULL DEFAULT '',
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "finished_at" timestamp
+);
+
+CREATE INDEX IF NOT EXISTS action_job_status_idx ON "public"."action_job" ("status")
+WHERE
+    "status" <> 'COMPLETED';
+
+COMMENT ON COLUMN "public"."action_job"."id" IS '主键自增id';
+
+COMMENT ON COLUMN "public"."action_job"."tenant_id" IS '租户id';
+
+COMMENT ON COLUMN "public"."action_job"."status" IS '任务状态';
+
+COMMENT ON COLUMN "public"."action_job"."chunk_size" IS '每个事务处理的账户操作数量';
+
+COMMENT ON COLUMN "public"."action_job"."total_count" IS '账户操作总数';
+
+COMMENT ON COLUMN "public"."action_job"."succeeded_count" IS '已成功的账户操作数量';
+
+COMMENT ON COLUMN "public"."action_job"."failed_count" IS '已失败的账户操作数量';
+
+COMMENT ON COLUMN "public"."action_job"."client_id" IS '提交任务的客户端id';
+
+COMMENT ON COLUMN "public"."action_job"."operator" IS '操作人';
+
+COMMENT ON COLUMN "public"."action_job"."created_at" IS '创建时间';
+
+COMMENT ON COLUMN "public"."action_job"."updated_at" IS '更新时间，处理中的任务每处理一块更新一次';
+
+COMMENT ON COLUMN "public"."action_job"."finished_at" IS '完成时间';
+
+COMMENT ON TABLE "public"."action_job" IS '批量账户操作任务表';
+
+CREATE TABLE IF NOT EXISTS "public"."action_job_item" (
+    "job_id" bigint NOT NULL,
+    "seq" int NOT NULL,
+    "user_id" text NOT NULL,
+    "asset_type_id" int NOT NULL,
+    "action_type_id" int NOT NULL,
+    "amount" double precision NOT NULL,
+    "order_number" text NOT NULL,
+    "description" text NOT NULL,
+    "status" action_job_item_status_enum NOT NULL DEFAULT 'PENDING',
+    "message" text NOT NULL DEFAULT '',
+    PRIMARY KEY ("job_id", "seq")
+);
+
+COMMENT ON COLUMN "public"."action_job_item"."job_id" IS '任务id';
+
+COMMENT ON COLUMN "public"."action_job_item"."seq" IS '账户操作在提交的批次中的序号(从 0 开始)';
+
+COMMENT ON COLUMN "public"."action_job_item"."user_id" IS '外部用户标识';
+
+COMMENT ON COLUMN "public"."action_job_item"."asset_type_id" IS '资产类型id';
+
+COMMENT ON COLUMN "public"."action_job_item"."action_type_id" IS '账户操作类型id';
+
+COMMENT ON COLUMN "public"."action_job_item"."amount" IS '操作金额';
+
+COMMENT ON COLUMN "public"."action_job_item"."order_number" IS '订单号';
+
+COMMENT ON COLUMN "public"."action_job_item"."description" IS '操作描述';
+
+COMMENT ON COLUMN "public"."action_job_item"."status" IS '处理结果';
+
+COMMENT ON COLUMN "public"."action_job_item"."message" IS '失败原因';
+
+COMMENT ON TABLE "public"."action_job_item" IS '批量账户操作任务明细表';
@@ -0,0 +1,55 @@
+-- Add migration script here
+-- 同一事务内写入的账户操作日志使用同一交易id，如一次`/accounts/actions`请求的所有操作、手续费及系统账户对应分录
+CREATE SEQUENCE IF NOT EXISTS "public"."account_transaction_id_seq";
+
+ALTER TABLE "public"."account_log"
+    ADD COLUMN IF NOT EXISTS "transaction_id" bigint;
+
+CREATE INDEX IF NOT EXISTS account_log_transaction_id_idx ON "public"."account_log" ("transaction_id")
+WHERE
+    "transaction_id" IS NOT NULL;
+
+COMMENT ON COLUMN "public"."account_log"."transaction_id" IS '交易id，同一事务内写入的日志相同(历史日志为空)';
+
+ALTER TABLE "public"."account_log_checkpoint"
+    ADD COLUMN IF NOT EXISTS "transaction_id" bigint;
+
+CREATE OR REPLACE FUNCTION detach_account_log_partition (p_name text)
+    RETURNS void
+    AS $$
+BEGIN
+    UPDATE
+        account_log_partition
+    SET
+        status = 'DETACHED'
+    WHERE
+        name = p_name
+        AND status = 'ATTACHED';
+    IF NOT FOUND THEN
+        RETURN;
+    END IF;
+    EXECUTE format('INSERT INTO account_log_checkpoint SELECT DISTINCT ON (account_id) * FROM %I ORDER BY account_id, id DESC
+        ON CONFLICT (account_id) DO UPDATE SET
+            id = EXCLUDED.id,
+            action_type_id = EXCLUDED.action_type_id,
+            amount_available_balance = EXCLUDED.amount_available_balance,
+            amount_frozen_balance = EXCLUDED.amount_frozen_balance,
+            amount_total_income = EXCLUDED.amount_total_income,
+            amount_total_expense = EXCLUDED.amount_total_expense,
+            available_balance_after = EXCLUDED.available_balance_after,
+            frozen_balance_after = EXCLUDED.frozen_balance_after,
+            total_income_after = EXCLUDED.total_income_after,
+            total_expense_after = EXCLUDED.total_expense_after,
+            order_number = EXCLUDED.order_number,
+            description = EXCLUDED.description,
+            created_at = EXCLUDED.created_at,
+            client_id = EXCLUDED.client_id,
+            operator = EXCLUDED.operator,
+            hash = EXCLUDED.hash,
+            tenant_id = EXCLUDED.tenant_id,
+            transaction_id = EXCLUDED.transaction_id
+        WHERE account_log_checkpoint.id < EXCLUDED.id', p_name);
+    EXECUTE format('ALTER TABLE account_log DETACH PARTITION %I', p_name);
+END;
+$$
+LANGUAGE plpgsql;
@@ -0,0 +1,40 @@
+-- Add migration script here
+-- 流通量随余额变更累计，检查发行上限时不再按账户汇总
+ALTER TABLE "public"."asset_type"
+    ADD COLUMN IF NOT EXISTS "supply" DECIMAL(18, 6) NOT NULL DEFAULT 0;
+
+COMMENT ON COLUMN "public"."asset_type"."supply" IS '流通量(除发行、销毁系统账户外所有账户的可用余额 + 冻结余额)，随余额变更累计';
+
+UPDATE
+    "public"."asset_type"
+SET
+    "supply" = coalesce((
+        SELECT
+            sum(account.available_balance + account.frozen_balance)
+        FROM account
+        WHERE
+            account.asset_type_id = asset_type.id
+            AND account.user_id IS DISTINCT FROM asset_type.issuance_user_id
+            AND account.user_id IS DISTINCT FROM asset_type.burn_user_id), 0);
+
+-- 流通量变更频繁，不更新时间戳，也不记录数据变更日志
+DROP TRIGGER IF EXISTS update_asset_type_timestamp ON "public"."asset_type";
+
+CREATE TRIGGER update_asset_type_timestamp
+    BEFORE UPDATE ON "public"."asset_type"
+    FOR EACH ROW
+    WHEN (to_jsonb(NEW) - 'supply' IS DISTINCT FROM to_jsonb(OLD) - 'supply')
+    EXECUTE FUNCTION update_timestamp ();
+
+DROP TRIGGER IF EXISTS track_asset_type_change ON "public"."asset_type";
+
+CREATE TRIGGER track_asset_type_change
+    AFTER INSERT OR DELETE ON "public"."asset_type"
+    FOR EACH ROW
+    EXECUTE FUNCTION track_change ();
+
+CREATE TRIGGER track_asset_type_update
+    AFTER UPDATE ON "public"."asset_type"
+    FOR EACH ROW
+    WHEN (to_jsonb(NEW) - 'supply' - 'updated_at' IS DISTINCT FROM to_jsonb(OLD) - 'supply' - 'updated_at')
+    EXECUTE FUNCTION track_change ();
@@ -0,0 +1,6 @@
+-- Add migration script here
+-- 复式记账资产类型按批次合计分录：单边操作的净额生成系统账户对应分录，双边操作需合计为零
+ALTER TABLE "public"."action_type"
+    ADD COLUMN IF NOT EXISTS "is_single_sided" boolean NOT NULL DEFAULT TRUE;
+
+COMMENT ON COLUMN "public"."action_type"."is_single_sided" IS '是否单边操作，复式记账资产类型的单边操作(如发放、消费)由系统账户生成对应分录，双边操作(如转账的转出、转入)需在同一批次内合计为零';
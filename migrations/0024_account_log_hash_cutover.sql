@@ -0,0 +1,13 @@
+-- Add migration script here
+-- 记录本迁移执行时最大的日志 id：此前的历史日志哈希为空或为最初的 v1 格式，之后写入的日志哈希均需为当前格式
+CREATE TABLE IF NOT EXISTS "public"."account_log_hash_cutover" (
+    "id" boolean PRIMARY KEY DEFAULT TRUE CHECK ("id"),
+    "last_legacy_id" bigint NOT NULL
+);
+
+COMMENT ON TABLE "public"."account_log_hash_cutover" IS '账户操作日志哈希格式切换点';
+COMMENT ON COLUMN "public"."account_log_hash_cutover"."last_legacy_id" IS '切换前最后一条日志 id，之后的日志哈希为空或不是当前格式时校验失败';
+
+INSERT INTO "public"."account_log_hash_cutover" ("last_legacy_id")
+    SELECT coalesce(max("id"), 0) FROM "public"."account_log"
+    ON CONFLICT DO NOTHING;
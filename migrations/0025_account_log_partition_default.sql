@@ -0,0 +1,38 @@
+-- Add migration script here
+-- 默认分区中已有某月的日志(如导入的早期历史数据)时，直接创建该月分区会失败
+-- 改为先创建独立的表，将默认分区中该月的日志移入后再挂载为分区
+CREATE OR REPLACE FUNCTION create_account_log_partition (p_month timestamp)
+    RETURNS void
+    AS $$
+DECLARE
+    v_start timestamp := date_trunc('month', p_month);
+    v_end timestamp := date_trunc('month', p_month) + INTERVAL '1 month';
+    v_name text := 'account_log_' || to_char(date_trunc('month', p_month), 'YYYYMM');
+BEGIN
+    INSERT INTO account_log_partition (name, range_start, range_end)
+        VALUES (v_name, v_start, v_end)
+    ON CONFLICT (name)
+        DO NOTHING;
+    IF NOT FOUND THEN
+        RETURN;
+    END IF;
+    -- 移动期间阻止写入默认分区，订单号已记录在`account_log_order`中，移动不影响其唯一性
+    LOCK TABLE account_log_default IN EXCLUSIVE MODE;
+    EXECUTE format('CREATE TABLE %I (LIKE account_log INCLUDING DEFAULTS INCLUDING CONSTRAINTS)', v_name);
+    EXECUTE format('WITH moved AS (DELETE FROM account_log_default WHERE created_at >= %L AND created_at < %L RETURNING *) INSERT INTO %I SELECT * FROM moved', v_start, v_end, v_name);
+    EXECUTE format('ALTER TABLE account_log ATTACH PARTITION %I FOR VALUES FROM (%L) TO (%L)', v_name, v_start, v_end);
+END;
+$$
+LANGUAGE plpgsql;
+
+-- 为默认分区中已有日志的月份创建分区
+DO $$
+DECLARE
+    v_month timestamp;
+BEGIN
+    FOR v_month IN SELECT DISTINCT date_trunc('month', created_at) FROM account_log_default LOOP
+        PERFORM
+            create_account_log_partition (v_month);
+    END LOOP;
+END;
+$$;
@@ -0,0 +1,517 @@
+use amazing::{
+    model::{
+        account::AccountModel,
+        account_log::AccountLogModel,
+        account_log_partition::AccountLogPartitionModel,
+        action_type::{ActionTypeModel, Change},
+        asset_type::AssetTypeModel,
+        change_log::ChangeLogModel,
+    },
+    repository::postgres::PgRepository,
+    service::{
+        account_log_partition::AccountLogPartitionService,
+        action_type::ActionTypeService,
+        asset_type::AssetTypeService,
+        client::Operator,
+        fee_rule::FeeRuleService,
+        import::ImportService,
+        limit_rule::LimitRuleService,
+        reconciliation::{Correction, ReconciliationService},
+    },
+};
+use anyhow::Context;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    postgres::PgPoolOptions,
+    types::chrono::{NaiveDate, NaiveDateTime, Utc},
+    PgPool,
+};
+use std::{collections::HashMap, fs, io, path::PathBuf};
+
+// 导出账户流水时每次读取的日志条数
+const EXPORT_CHUNK_SIZE: i64 = 1000;
+// 导入历史账户操作时每个事务的行数
+const IMPORT_CHUNK_SIZE: usize = 500;
+
+/// amazing 管理工具
+#[derive(Parser)]
+#[command(name = "amazing-admin", version)]
+struct Cli {
+    /// 配置文件，与服务端共用
+    #[arg(long, default_value = "config.toml")]
+    config: PathBuf,
+    /// 操作人，记录到`change_log`和`account_log`
+    #[arg(long, env = "USER", default_value = "")]
+    operator: String,
+    /// 租户id，对账及执行修复操作时不限租户
+    #[arg(long, env = "AMAZING_TENANT_ID", default_value_t = 1)]
+    tenant_id: i32,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 资产类型
+    #[command(subcommand)]
+    AssetTypes(AssetTypeCommand),
+    /// 账户操作类型
+    #[command(subcommand)]
+    ActionTypes(ActionTypeCommand),
+    /// 查看某`user_id`的资产账户
+    Accounts {
+        #[arg(long)]
+        user_id: String,
+    },
+    /// 查看资产账户操作日志
+    Logs {
+        #[arg(long)]
+        user_id: String,
+        #[arg(long)]
+        asset_type_id: i32,
+        /// 上一页最后一条日志的`id`
+        #[arg(long)]
+        before_id: Option<i64>,
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+    },
+    /// 对账：账户余额与最新账户操作日志不一致时输出待审批的修复操作
+    Reconcile {
+        /// 修复操作输出文件，审批后通过`apply-corrections`执行
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// 执行已审批的修复操作
+    ApplyCorrections {
+        #[arg(long)]
+        file: PathBuf,
+    },
+    /// 导出资产账户流水(CSV)
+    Export {
+        #[arg(long)]
+        user_id: String,
+        #[arg(long)]
+        asset_type_id: i32,
+        /// 开始日期(包含)，格式`YYYY-MM-DD`
+        #[arg(long)]
+        from: NaiveDate,
+        /// 结束日期(不包含)，格式`YYYY-MM-DD`
+        #[arg(long)]
+        to: NaiveDate,
+        /// 输出文件，为空时输出到标准输出
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// 导入历史账户操作(CSV)，账户不存在时自动创建，已导入的订单跳过，中断后可重新执行
+    Import {
+        /// 导入文件，列为`user_id,asset_type,action_type,amount,order_number,created_at[,description]`
+        #[arg(long)]
+        file: PathBuf,
+        /// 逐行导入结果输出文件(CSV)，为空时输出到标准输出
+        #[arg(long)]
+        report: Option<PathBuf>,
+        #[arg(long, default_value_t = IMPORT_CHUNK_SIZE)]
+        chunk_size: usize,
+    },
+    /// 账户操作日志月分区
+    #[command(subcommand)]
+    Partitions(PartitionCommand),
+}
+
+#[derive(Subcommand)]
+enum PartitionCommand {
+    /// 列出所有分区(包括已归档)
+    List,
+    /// 归档结束时间不晚于`--before`的分区：分离后导出为`<分区表名>.jsonl`
+    Archive {
+        /// 归档截止日期，格式`YYYY-MM-DD`，不能晚于当前月第一天
+        #[arg(long)]
+        before: NaiveDate,
+        #[arg(long)]
+        output_dir: PathBuf,
+        /// 导出后删除分区表
+        #[arg(long)]
+        drop: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AssetTypeCommand {
+    /// 列出所有资产类型(包括未启用)
+    List,
+    /// 添加资产类型，需重启服务生效
+    Add {
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value = "")]
+        description: String,
+        /// 余额批次有效天数，为空时不按批次跟踪余额
+        #[arg(long)]
+        lot_ttl_days: Option<i32>,
+        #[arg(long)]
+        inactive: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ActionTypeCommand {
+    /// 列出所有账户操作类型(包括未启用)
+    List,
+    /// 添加账户操作类型，需重启服务生效
+    Add {
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value = "")]
+        description: String,
+        #[arg(long)]
+        available_balance_change: ChangeArg,
+        #[arg(long)]
+        frozen_balance_change: ChangeArg,
+        #[arg(long)]
+        total_income_change: ChangeArg,
+        #[arg(long)]
+        total_expense_change: ChangeArg,
+        #[arg(long)]
+        inactive: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ChangeArg {
+    Inc,
+    Dec,
+    None,
+}
+
+impl From<ChangeArg> for Change {
+    fn from(change: ChangeArg) -> Self {
+        match change {
+            ChangeArg::Inc => Change::Inc,
+            ChangeArg::Dec => Change::Dec,
+            ChangeArg::None => Change::None,
+        }
+    }
+}
+
+// 只读取`config.toml`中管理工具需要的配置项
+#[derive(Deserialize)]
+struct Config {
+    postgres: PostgresConfig,
+}
+
+#[derive(Deserialize)]
+struct PostgresConfig {
+    url: String,
+}
+
+#[derive(Serialize)]
+struct StatementRow<'a> {
+    id: i64,
+    created_at: NaiveDateTime,
+    action_type: &'a str,
+    amount_available_balance: String,
+    amount_frozen_balance: String,
+    amount_total_income: String,
+    amount_total_expense: String,
+    available_balance_after: String,
+    frozen_balance_after: String,
+    total_income_after: String,
+    total_expense_after: String,
+    order_number: &'a str,
+    description: &'a str,
+    operator: &'a str,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let config: Config = toml::from_str(
+        &fs::read_to_string(&cli.config)
+            .with_context(|| format!("Failed to read {}", cli.config.display()))?,
+    )?;
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&config.postgres.url)
+        .await?;
+    // 管理工具不经过客户端认证，`client_id`记为 0
+    let operator = Operator {
+        client_id: 0,
+        tenant_id: cli.tenant_id,
+        name: cli.operator,
+    };
+    match cli.command {
+        Command::AssetTypes(AssetTypeCommand::List) => {
+            println!("id\tname\tis_active\tdescription");
+            for asset_type in
+                AssetTypeModel::fetch_all_including_inactive(&pool, operator.tenant_id).await?
+            {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    asset_type.id, asset_type.name, asset_type.is_active, asset_type.description
+                );
+            }
+        }
+        Command::AssetTypes(AssetTypeCommand::Add {
+            name,
+            description,
+            lot_ttl_days,
+            inactive,
+        }) => {
+            let mut tx = pool.begin().await?;
+            ChangeLogModel::set_operator(&mut *tx, operator.client_id, &operator.name).await?;
+            let asset_type = AssetTypeModel::create(
+                &mut *tx,
+                operator.tenant_id,
+                &name,
+                &description,
+                lot_ttl_days,
+                !inactive,
+            )
+            .await?;
+            tx.commit().await?;
+            print_json(&asset_type)?;
+        }
+        Command::ActionTypes(ActionTypeCommand::List) => {
+            println!("id\tname\tis_active\tchanges\tdescription");
+            for action_type in
+                ActionTypeModel::fetch_all_including_inactive(&pool, operator.tenant_id).await?
+            {
+                println!(
+                    "{}\t{}\t{}\t{:?}/{:?}/{:?}/{:?}\t{}",
+                    action_type.id,
+                    action_type.name,
+                    action_type.is_active,
+                    action_type.available_balance_change,
+                    action_type.frozen_balance_change,
+                    action_type.total_income_change,
+                    action_type.total_expense_change,
+                    action_type.description
+                );
+            }
+        }
+        Command::ActionTypes(ActionTypeCommand::Add {
+            name,
+            description,
+            available_balance_change,
+            frozen_balance_change,
+            total_income_change,
+            total_expense_change,
+            inactive,
+        }) => {
+            let mut tx = pool.begin().await?;
+            ChangeLogModel::set_operator(&mut *tx, operator.client_id, &operator.name).await?;
+            let action_type = ActionTypeModel::create(
+                &mut *tx,
+                operator.tenant_id,
+                &name,
+                &description,
+                available_balance_change.into(),
+                frozen_balance_change.into(),
+                total_income_change.into(),
+                total_expense_change.into(),
+                !inactive,
+            )
+            .await?;
+            tx.commit().await?;
+            print_json(&action_type)?;
+        }
+        Command::Accounts { user_id } => {
+            let asset_type_ids =
+                AssetTypeModel::fetch_all_including_inactive(&pool, operator.tenant_id)
+                    .await?
+                    .iter()
+                    .map(|asset_type| asset_type.id)
+                    .collect();
+            print_json(
+                &AccountModel::find_multiple(&pool, operator.tenant_id, &user_id, asset_type_ids)
+                    .await?,
+            )?;
+        }
+        Command::Logs {
+            user_id,
+            asset_type_id,
+            before_id,
+            limit,
+        } => {
+            let account =
+                AccountModel::find(&pool, operator.tenant_id, &user_id, asset_type_id).await?;
+            print_json(&AccountLogModel::fetch_page(&pool, account.id, before_id, limit).await?)?;
+        }
+        Command::Reconcile { output } => reconcile(&pool, output).await?,
+        Command::ApplyCorrections { file } => {
+            let corrections: Vec<Correction> = serde_json::from_str(
+                &fs::read_to_string(&file)
+                    .with_context(|| format!("Failed to read {}", file.display()))?,
+            )?;
+            let repository = load_repository(&pool).await?;
+            ReconciliationService::apply_corrections(&repository, &corrections, &operator).await?;
+            eprintln!("Applied {} corrections", corrections.len());
+        }
+        Command::Export {
+            user_id,
+            asset_type_id,
+            from,
+            to,
+            output,
+        } => {
+            let writer: Box<dyn io::Write> = match output {
+                Some(path) => Box::new(fs::File::create(path)?),
+                None => Box::new(io::stdout()),
+            };
+            export(
+                &pool,
+                operator.tenant_id,
+                user_id,
+                asset_type_id,
+                from,
+                to,
+                writer,
+            )
+            .await?;
+        }
+        Command::Import {
+            file,
+            report,
+            chunk_size,
+        } => {
+            let reader = fs::File::open(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let writer: Box<dyn io::Write> = match report {
+                Some(path) => Box::new(fs::File::create(path)?),
+                None => Box::new(io::stdout()),
+            };
+            let repository = load_repository(&pool).await?;
+            let summary =
+                ImportService::import(&repository, reader, writer, chunk_size.max(1), &operator)
+                    .await?;
+            eprintln!(
+                "{} imported, {} skipped, {} failed",
+                summary.imported, summary.skipped, summary.failed
+            );
+        }
+        Command::Partitions(PartitionCommand::List) => {
+            println!("name\trange_start\trange_end\tstatus");
+            for partition in AccountLogPartitionModel::fetch_all(&pool).await? {
+                println!(
+                    "{}\t{}\t{}\t{:?}",
+                    partition.name, partition.range_start, partition.range_end, partition.status
+                );
+            }
+        }
+        Command::Partitions(PartitionCommand::Archive {
+            before,
+            output_dir,
+            drop,
+        }) => {
+            fs::create_dir_all(&output_dir)?;
+            for partition in AccountLogPartitionService::fetch_archivable(&pool, before).await? {
+                let path = output_dir.join(format!("{}.jsonl", partition.name));
+                let writer = io::BufWriter::new(fs::File::create(&path)?);
+                let exported =
+                    AccountLogPartitionService::archive(&pool, &partition, writer, drop).await?;
+                eprintln!(
+                    "{}: {exported} logs archived to {}",
+                    partition.name,
+                    path.display()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_json<T: Serialize>(value: &T) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+// 加载账户操作所需的配置缓存，管理工具不连接 Redis，限额每次从数据库统计
+async fn load_repository(pool: &PgPool) -> anyhow::Result<PgRepository> {
+    let repository = PgRepository::new(pool.clone());
+    AssetTypeService::init(&repository).await?;
+    ActionTypeService::init(&repository).await?;
+    FeeRuleService::init(&repository).await?;
+    LimitRuleService::init(&repository, false).await?;
+    Ok(repository)
+}
+
+async fn reconcile(pool: &PgPool, output: Option<PathBuf>) -> anyhow::Result<()> {
+    let reconciliations = ReconciliationService::reconcile(pool).await?;
+    print_json(&reconciliations)?;
+    let proposed_at = Utc::now().naive_utc();
+    let corrections: Vec<Correction> = reconciliations
+        .iter()
+        .flat_map(|reconciliation| {
+            ReconciliationService::propose_corrections(reconciliation, proposed_at)
+        })
+        .collect();
+    eprintln!(
+        "{} mismatched accounts, {} proposed corrections",
+        reconciliations.len(),
+        corrections.len()
+    );
+    if let Some(path) = output {
+        fs::write(&path, serde_json::to_string_pretty(&corrections)?)?;
+        eprintln!("Corrections written to {}", path.display());
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn export(
+    pool: &PgPool,
+    tenant_id: i32,
+    user_id: String,
+    asset_type_id: i32,
+    from: NaiveDate,
+    to: NaiveDate,
+    writer: impl io::Write,
+) -> anyhow::Result<()> {
+    let account = AccountModel::find(pool, tenant_id, &user_id, asset_type_id).await?;
+    let action_types: HashMap<i32, String> =
+        ActionTypeModel::fetch_all_including_inactive(pool, tenant_id)
+            .await?
+            .into_iter()
+            .map(|action_type| (action_type.id, action_type.name))
+            .collect();
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    let mut after_id = 0;
+    loop {
+        let account_logs = AccountLogModel::fetch_range(
+            pool,
+            account.id,
+            from.and_time(Default::default()),
+            to.and_time(Default::default()),
+            after_id,
+            EXPORT_CHUNK_SIZE,
+        )
+        .await?;
+        let Some(last) = account_logs.last() else {
+            break;
+        };
+        after_id = last.id;
+        for account_log in &account_logs {
+            csv_writer.serialize(StatementRow {
+                id: account_log.id,
+                created_at: account_log.created_at,
+                action_type: action_types
+                    .get(&account_log.action_type_id)
+                    .map_or("", String::as_str),
+                amount_available_balance: account_log.amount_available_balance.to_string(),
+                amount_frozen_balance: account_log.amount_frozen_balance.to_string(),
+                amount_total_income: account_log.amount_total_income.to_string(),
+                amount_total_expense: account_log.amount_total_expense.to_string(),
+                available_balance_after: account_log.available_balance_after.to_string(),
+                frozen_balance_after: account_log.frozen_balance_after.to_string(),
+                total_income_after: account_log.total_income_after.to_string(),
+                total_expense_after: account_log.total_expense_after.to_string(),
+                order_number: &account_log.order_number,
+                description: &account_log.description,
+                operator: &account_log.operator,
+            })?;
+        }
+    }
+    csv_writer.flush()?;
+    Ok(())
+}
@@ -1,95 +1,426 @@
 use crate::{
-    model::account::AccountModel,
-    service::{
-        account::AccountService, action_type::ActionTypeService, asset_type::AssetTypeService,
+    model::{
+        account::{AccountModel, UserId},
+        account_log::{AccountLogModel, AccountTransactionLegModel},
     },
+    repository::postgres::PgRepository,
+    service::{account::AccountService, client::Operator, statement::StatementService},
+};
+use axum::{
+    body::Body,
+    extract::Query,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
-use axum::{http::StatusCode, Json};
 use axum_kit::{validation::ValidatedJson, AppResult};
 use num_traits::cast::FromPrimitive;
-use serde::Deserialize;
-use sqlx::types::Decimal;
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::types::{chrono::NaiveDate, Decimal};
+use utoipa::{IntoParams, ToSchema};
 use validator::{Validate, ValidationError};
 
-#[derive(Deserialize, Validate, Debug)]
+#[derive(Deserialize, Validate, ToSchema, Debug)]
 pub struct AccountRequest {
-    #[validate(range(min = 1))]
-    pub user_id: i32,
-    #[validate(custom(function = "validate_asset_type_id"))]
+    #[serde(deserialize_with = "deserialize_user_id")]
+    #[validate(length(min = 1, max = 64), custom(function = "validate_user_id"))]
+    #[schema(value_type = String, min_length = 1, max_length = 64, example = "1234567890123456789")]
+    pub user_id: String,
+    /// 当前租户已启用的资产类型id，见`GET /assets`
     pub asset_type_id: i32,
 }
 
-#[derive(Deserialize, Validate, Debug)]
+#[derive(Deserialize, Validate, ToSchema, Debug)]
 pub struct AccountsRequest {
+    #[serde(deserialize_with = "deserialize_user_id")]
+    #[validate(length(min = 1, max = 64), custom(function = "validate_user_id"))]
+    #[schema(value_type = String, min_length = 1, max_length = 64, example = "1234567890123456789")]
+    pub user_id: String,
+}
+
+#[derive(Deserialize, Validate, ToSchema, Debug)]
+pub struct AccountLogsRequest {
+    #[serde(deserialize_with = "deserialize_user_id")]
+    #[validate(length(min = 1, max = 64), custom(function = "validate_user_id"))]
+    #[schema(value_type = String, min_length = 1, max_length = 64, example = "1234567890123456789")]
+    pub user_id: String,
+    /// 当前租户已启用的资产类型id，见`GET /assets`
+    pub asset_type_id: i32,
+    /// 上一页最后一条日志的`id`，为空时从最新日志开始查询
     #[validate(range(min = 1))]
-    pub user_id: i32,
+    #[schema(minimum = 1)]
+    pub before_id: Option<i64>,
+    #[serde(default = "default_limit")]
+    #[validate(range(min = 1, max = 100))]
+    #[schema(minimum = 1, maximum = 100, default = 20)]
+    pub limit: i64,
 }
 
-#[derive(Deserialize, Validate, Debug)]
+#[derive(Deserialize, ToSchema, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    #[default]
+    Csv,
+    /// JSON Lines，每行一条记录
+    Ndjson,
+}
+
+#[derive(Deserialize, Validate, ToSchema, Debug)]
+pub struct AccountStatementRequest {
+    #[serde(deserialize_with = "deserialize_user_id")]
+    #[validate(length(min = 1, max = 64), custom(function = "validate_user_id"))]
+    #[schema(value_type = String, min_length = 1, max_length = 64, example = "1234567890123456789")]
+    pub user_id: String,
+    /// 当前租户已启用的资产类型id，为空时导出所有资产类型
+    pub asset_type_id: Option<i32>,
+    /// 开始日期(包含，UTC)，格式`YYYY-MM-DD`
+    pub start_date: NaiveDate,
+    /// 结束日期(不包含，UTC)，格式`YYYY-MM-DD`
+    pub end_date: NaiveDate,
+    #[serde(default)]
+    pub format: StatementFormat,
+}
+
+#[derive(Deserialize, Serialize, Validate, ToSchema, Debug, Clone)]
 pub struct AccountActionRequest {
-    #[validate(range(min = 1))]
-    pub user_id: i32,
-    #[validate(custom(function = "validate_asset_type_id"))]
+    #[serde(deserialize_with = "deserialize_user_id")]
+    #[validate(length(min = 1, max = 64), custom(function = "validate_user_id"))]
+    #[schema(value_type = String, min_length = 1, max_length = 64, example = "1234567890123456789")]
+    pub user_id: String,
+    /// 当前租户已启用的资产类型id，见`GET /assets`
     pub asset_type_id: i32,
-    #[validate(custom(function = "validate_action_type_id"))]
+    /// 当前租户已启用的账户操作类型id，见`GET /actions`
     pub action_type_id: i32,
+    /// 操作金额，最多6位小数
     #[validate(range(min = 0.000001), custom(function = "validate_amount"))]
+    #[schema(minimum = 0.000001, multiple_of = 0.000001)]
     pub amount: f64,
+    /// 订单号，同一账户同一操作类型下唯一
     #[validate(length(min = 32))]
+    #[schema(min_length = 32)]
     pub order_number: String,
     #[validate(length(min = 1))]
+    #[schema(min_length = 1)]
     pub description: String,
 }
 
-fn validate_asset_type_id(id: i32) -> Result<(), ValidationError> {
-    if !AssetTypeService::is_active(id) {
-        return Err(ValidationError::new("无效值"));
-    }
-    Ok(())
+// 交易id响应头，见`/accounts/transactions/info`
+const X_TRANSACTION_ID: &str = "x-transaction-id";
+
+#[derive(Deserialize, Validate, ToSchema, Debug)]
+pub struct AccountTransactionRequest {
+    /// 交易id，见`/accounts/actions`响应头`x-transaction-id`及账户操作日志的`transaction_id`
+    pub transaction_id: i64,
+}
+
+#[derive(Deserialize, ToSchema, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountActionMode {
+    /// 同一批次的操作全部成功或全部失败
+    #[default]
+    Atomic,
+    /// 每个操作单独成功或失败，返回每个操作的处理结果
+    Partial,
 }
 
-fn validate_action_type_id(id: i32) -> Result<(), ValidationError> {
-    if !ActionTypeService::is_active(id) {
+#[derive(Deserialize, IntoParams, Debug)]
+#[into_params(parameter_in = Query)]
+pub struct AccountActionsQuery {
+    #[serde(default)]
+    pub mode: AccountActionMode,
+    /// 为真时只预览，处理完成后回滚，返回操作后的账户及每个操作的处理结果
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, ToSchema, PartialEq, Clone, Copy, Debug)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum AccountActionStatus {
+    Succeeded,
+    Failed,
+}
+
+#[derive(Serialize, ToSchema, Debug)]
+pub struct AccountActionResult {
+    pub status: AccountActionStatus,
+    /// 失败时的错误码，同整批处理时该操作失败返回的 HTTP 状态码
+    pub code: Option<u16>,
+    /// 失败原因
+    pub message: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AccountActionsPreview {
+    /// 操作后的账户，包括收取手续费的平台账户及系统账户
+    pub accounts: Vec<AccountModel>,
+    pub results: Vec<AccountActionResult>,
+}
+
+#[derive(Serialize, ToSchema, Debug)]
+pub struct AccountLogsVerifyResponse {
+    pub account_id: i32,
+    /// 已通过校验的日志条数
+    pub verified: i64,
+    /// 哈希链上线前写入、没有哈希的历史日志条数
+    pub unhashed: i64,
+    /// 第一条校验失败的日志`id`，为空表示哈希链完整
+    pub broken_log_id: Option<i64>,
+}
+
+pub(crate) fn deserialize_user_id<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<String, D::Error> {
+    Ok(match UserId::deserialize(deserializer)? {
+        UserId::Integer(user_id) => user_id.to_string(),
+        UserId::String(user_id) => user_id,
+    })
+}
+
+pub(crate) fn validate_user_id(user_id: &str) -> Result<(), ValidationError> {
+    if user_id.trim() != user_id || user_id.chars().any(char::is_control) {
         return Err(ValidationError::new("无效值"));
     }
     Ok(())
 }
 
-fn validate_amount(amount: f64) -> Result<(), ValidationError> {
+fn default_limit() -> i64 {
+    20
+}
+
+pub(crate) fn validate_amount(amount: f64) -> Result<(), ValidationError> {
     if Decimal::from_f64(amount).unwrap().scale() > 6 {
         return Err(ValidationError::new("无效值(最多6位小数)"));
     }
     Ok(())
 }
 
-// 添加账户
+/// 添加账户
+#[utoipa::path(
+    post,
+    path = "/accounts/new",
+    tag = "account",
+    request_body = AccountRequest,
+    responses(
+        (status = 201, description = "创建成功", body = AccountModel),
+        (status = 400, description = "参数错误"),
+        (status = 404, description = "资产类型不存在"),
+    ),
+    security(("api_key" = []))
+)]
 pub async fn create(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
     ValidatedJson(payload): ValidatedJson<AccountRequest>,
 ) -> AppResult<(StatusCode, Json<AccountModel>)> {
-    let account = AccountService::create(&payload).await?;
+    let account = AccountService::create(&repository, operator.tenant_id, &payload).await?;
     Ok((StatusCode::CREATED, Json(account)))
 }
 
-// 账户信息
+/// 账户信息
+#[utoipa::path(
+    post,
+    path = "/accounts/info",
+    tag = "account",
+    request_body = AccountRequest,
+    responses(
+        (status = 200, description = "账户信息", body = AccountModel),
+        (status = 400, description = "参数错误"),
+        (status = 404, description = "资产类型不存在"),
+    ),
+    security(("api_key" = []))
+)]
 pub async fn info(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
     ValidatedJson(payload): ValidatedJson<AccountRequest>,
 ) -> AppResult<Json<AccountModel>> {
-    let account = AccountService::info(&payload).await?;
+    let account = AccountService::info(&repository, operator.tenant_id, &payload).await?;
     Ok(Json(account))
 }
 
-// 某`user_id`所有账户信息
+/// 某`user_id`所有账户信息
+#[utoipa::path(
+    post,
+    path = "/accounts/infos",
+    tag = "account",
+    request_body = AccountsRequest,
+    responses(
+        (status = 200, description = "账户信息列表", body = Vec<AccountModel>),
+        (status = 400, description = "参数错误"),
+    ),
+    security(("api_key" = []))
+)]
 pub async fn infos(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
     ValidatedJson(payload): ValidatedJson<AccountsRequest>,
 ) -> AppResult<Json<Vec<AccountModel>>> {
-    let account = AccountService::infos(&payload).await?;
+    let account = AccountService::infos(&repository, operator.tenant_id, &payload).await?;
     Ok(Json(account))
 }
 
-// 账户操作
-// 仅涉及可用余额、冻结余额、累计收入、累计支出的变更
+/// 导出账单
+///
+/// 按账户输出期初余额、日期范围内的账户操作日志及期末余额，以流式响应返回
+#[utoipa::path(
+    post,
+    path = "/accounts/statement",
+    tag = "account",
+    request_body = AccountStatementRequest,
+    responses(
+        (status = 200, description = "CSV 格式的账单，`format`为`ndjson`时为`application/x-ndjson`格式", content_type = "text/csv"),
+        (status = 400, description = "参数错误"),
+        (status = 404, description = "资产类型或资产账户不存在"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn statement(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<AccountStatementRequest>,
+) -> AppResult<Response> {
+    let statement = StatementService::export(repository, operator.tenant_id, &payload).await?;
+    let (content_type, file_name) = match payload.format {
+        StatementFormat::Csv => ("text/csv; charset=utf-8", "statement.csv"),
+        StatementFormat::Ndjson => ("application/x-ndjson", "statement.ndjson"),
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+        ],
+        Body::from_stream(statement),
+    )
+        .into_response())
+}
+
+/// 账户操作日志
+#[utoipa::path(
+    post,
+    path = "/accounts/logs",
+    tag = "account",
+    request_body = AccountLogsRequest,
+    responses(
+        (status = 200, description = "按`id`倒序的账户操作日志", body = Vec<AccountLogModel>),
+        (status = 400, description = "参数错误"),
+        (status = 404, description = "资产类型不存在"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn logs(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<AccountLogsRequest>,
+) -> AppResult<Json<Vec<AccountLogModel>>> {
+    let account_logs = AccountService::logs(&repository, operator.tenant_id, &payload).await?;
+    Ok(Json(account_logs))
+}
+
+/// 校验账户操作日志哈希链
+#[utoipa::path(
+    post,
+    path = "/accounts/logs/verify",
+    tag = "account",
+    request_body = AccountRequest,
+    responses(
+        (status = 200, description = "校验结果", body = AccountLogsVerifyResponse),
+        (status = 400, description = "参数错误"),
+        (status = 404, description = "资产类型不存在"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn verify_logs(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<AccountRequest>,
+) -> AppResult<Json<AccountLogsVerifyResponse>> {
+    let response = AccountService::verify_logs(&repository, operator.tenant_id, &payload).await?;
+    Ok(Json(response))
+}
+
+/// 账户操作
+///
+/// 仅涉及可用余额、冻结余额、累计收入、累计支出的变更，同一批次的操作在同一事务内完成
+///
+/// `mode=partial`时每个操作单独成功或失败，按提交顺序返回每个操作的处理结果，手续费及系统账户对应分录随各自的操作成功或失败；参数错误时仍整批失败
+///
+/// `dry_run=true`时只预览，返回操作后的账户而不提交，整批处理时任一操作失败同样返回错误
+#[utoipa::path(
+    post,
+    path = "/accounts/actions",
+    tag = "account",
+    params(
+        ("x-operator" = Option<String>, Header, description = "实际操作人"),
+        AccountActionsQuery
+    ),
+    request_body = Vec<AccountActionRequest>,
+    responses(
+        (
+            status = 200,
+            description = "操作成功，`mode=partial`时返回每个操作的处理结果，`dry_run=true`时返回预览结果",
+            body = Vec<AccountActionResult>,
+            headers(("x-transaction-id" = i64, description = "交易id，预览时没有"))
+        ),
+        (status = 400, description = "参数错误"),
+        (status = 402, description = "存在余额不足的账户"),
+        (status = 403, description = "存在未启用账户"),
+        (status = 404, description = "资产类型或账户操作类型不存在"),
+        (status = 409, description = "存在已处理的订单"),
+        (status = 422, description = "超出资产发行上限"),
+        (status = 429, description = "超出限额"),
+    ),
+    security(("api_key" = []))
+)]
 pub async fn actions(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    Query(query): Query<AccountActionsQuery>,
     ValidatedJson(payload): ValidatedJson<Vec<AccountActionRequest>>,
-) -> AppResult<()> {
-    AccountService::actions(&payload).await
+) -> AppResult<Response> {
+    if query.dry_run {
+        let preview = AccountService::preview(&repository, &payload, &operator, query.mode).await?;
+        return Ok(Json(preview).into_response());
+    }
+    match query.mode {
+        AccountActionMode::Atomic => {
+            let transaction_id = AccountService::actions(&repository, &payload, &operator).await?;
+            Ok([(X_TRANSACTION_ID, transaction_id.to_string())].into_response())
+        }
+        AccountActionMode::Partial => {
+            let (transaction_id, results) =
+                AccountService::actions_partial(&repository, &payload, &operator).await?;
+            Ok((
+                [(X_TRANSACTION_ID, transaction_id.to_string())],
+                Json(results),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// 获取交易的所有账户操作日志
+///
+/// 同一交易的日志在同一事务内写入，如一次`/accounts/actions`请求的所有账户操作、手续费及系统账户对应分录，按`id`正序排列
+#[utoipa::path(
+    post,
+    path = "/accounts/transactions/info",
+    tag = "account",
+    request_body = AccountTransactionRequest,
+    responses(
+        (status = 200, description = "交易的账户操作日志", body = Vec<AccountTransactionLegModel>),
+        (status = 404, description = "交易不存在或日志已归档"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn transaction(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<AccountTransactionRequest>,
+) -> AppResult<Json<Vec<AccountTransactionLegModel>>> {
+    let legs = AccountService::transaction(&repository, operator.tenant_id, payload.transaction_id)
+        .await?;
+    Ok(Json(legs))
 }
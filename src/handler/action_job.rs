@@ -0,0 +1,170 @@
+use super::account::AccountActionRequest;
+use crate::{
+    model::action_job::{ActionJobItemStatus, ActionJobModel},
+    repository::postgres::PgRepository,
+    service::{action_job::ActionJobService, client::Operator},
+};
+use axum::{
+    body::Body,
+    http::{header, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Extension, Json,
+};
+use axum_kit::{validation::ValidatedJson, AppResult};
+use futures::{Stream, StreamExt};
+use serde::Deserialize;
+use std::convert::Infallible;
+use utoipa::ToSchema;
+use validator::Validate;
+
+// 提交任务的请求体上限，默认上限不足以提交数十万条账户操作
+pub const ACTION_JOB_BODY_LIMIT: usize = 128 * 1024 * 1024;
+
+#[derive(Deserialize, Validate, ToSchema, Debug)]
+pub struct ActionJobRequest {
+    /// 每个事务处理的账户操作数量
+    #[serde(default = "default_chunk_size")]
+    #[validate(range(min = 1, max = 5000))]
+    #[schema(minimum = 1, maximum = 5000, default = 500)]
+    pub chunk_size: i32,
+    /// 账户操作，提交时只检查数量，处理时逐条校验，结果见任务明细
+    #[validate(length(min = 1, max = 500000))]
+    #[schema(min_items = 1, max_items = 500000)]
+    pub actions: Vec<AccountActionRequest>,
+}
+
+fn default_chunk_size() -> i32 {
+    500
+}
+
+#[derive(Deserialize, Validate, ToSchema, Debug)]
+pub struct ActionJobIdRequest {
+    pub id: i64,
+}
+
+#[derive(Deserialize, Validate, ToSchema, Debug)]
+pub struct ActionJobReportRequest {
+    pub id: i64,
+    /// 只输出该状态的明细，为空时输出所有明细
+    pub status: Option<ActionJobItemStatus>,
+}
+
+/// 提交批量账户操作任务
+///
+/// 任务在后台按块处理，每块在同一事务内完成，每个账户操作单独成功或失败，处理结果与余额变更一同提交
+#[utoipa::path(
+    post,
+    path = "/jobs/actions/new",
+    operation_id = "create_action_job",
+    tag = "action_job",
+    params(("x-operator" = Option<String>, Header, description = "实际操作人")),
+    request_body = ActionJobRequest,
+    responses(
+        (status = 202, description = "已提交", body = ActionJobModel),
+        (status = 400, description = "参数错误"),
+        (status = 413, description = "请求体过大"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn create(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<ActionJobRequest>,
+) -> AppResult<(StatusCode, Json<ActionJobModel>)> {
+    let action_job = ActionJobService::create(&repository, &payload, &operator).await?;
+    Ok((StatusCode::ACCEPTED, Json(action_job)))
+}
+
+/// 批量账户操作任务进度
+#[utoipa::path(
+    post,
+    path = "/jobs/actions/info",
+    operation_id = "action_job_info",
+    tag = "action_job",
+    request_body = ActionJobIdRequest,
+    responses(
+        (status = 200, description = "任务进度", body = ActionJobModel),
+        (status = 404, description = "任务不存在"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn info(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<ActionJobIdRequest>,
+) -> AppResult<Json<ActionJobModel>> {
+    let action_job = ActionJobService::info(&repository, operator.tenant_id, payload.id).await?;
+    Ok(Json(action_job))
+}
+
+/// 订阅批量账户操作任务进度
+///
+/// 以 Server-Sent Events 推送`progress`事件，数据同任务进度，任务完成后推送最后一次进度并结束
+#[utoipa::path(
+    post,
+    path = "/jobs/actions/events",
+    operation_id = "action_job_events",
+    tag = "action_job",
+    request_body = ActionJobIdRequest,
+    responses(
+        (status = 200, description = "任务进度事件流", content_type = "text/event-stream"),
+        (status = 404, description = "任务不存在"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn events(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<ActionJobIdRequest>,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let progress = ActionJobService::progress(repository, operator.tenant_id, payload.id).await?;
+    let events = progress.map(|action_job| {
+        let event = action_job
+            .map_err(|err| err.to_string())
+            .and_then(|action_job| {
+                Event::default()
+                    .event("progress")
+                    .json_data(action_job)
+                    .map_err(|err| err.to_string())
+            });
+        Ok(event.unwrap_or_else(|err| Event::default().event("error").data(err)))
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// 导出批量账户操作任务明细
+///
+/// 按提交顺序输出每条账户操作的处理结果，`seq`为其在提交的`actions`中的序号(从 0 开始)
+#[utoipa::path(
+    post,
+    path = "/jobs/actions/report",
+    operation_id = "action_job_report",
+    tag = "action_job",
+    request_body = ActionJobReportRequest,
+    responses(
+        (status = 200, description = "CSV 格式的任务明细", content_type = "text/csv"),
+        (status = 404, description = "任务不存在"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn report(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<ActionJobReportRequest>,
+) -> AppResult<Response> {
+    let report = ActionJobService::report(repository, operator.tenant_id, &payload).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"action-job-{}.csv\"", payload.id),
+            ),
+        ],
+        Body::from_stream(report),
+    )
+        .into_response())
+}
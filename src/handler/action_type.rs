@@ -1,9 +1,24 @@
-use crate::{model::action_type::ActionTypeModel, service::action_type::ActionTypeService};
-use axum::Json;
+use crate::{
+    model::action_type::ActionTypeModel,
+    repository::{cache::CacheRepository, postgres::PgRepository},
+    service::{action_type::ActionTypeService, client::Operator},
+};
+use axum::{Extension, Json};
 use axum_kit::AppResult;
 
-// 账户操作类型列表
-pub async fn list() -> AppResult<Json<&'static Vec<ActionTypeModel>>> {
-    let action_type = ActionTypeService::list();
+/// 账户操作类型列表
+#[utoipa::path(
+    get,
+    path = "/actions",
+    tag = "action_type",
+    operation_id = "list_action_types",
+    responses((status = 200, description = "当前租户已启用的账户操作类型", body = Vec<ActionTypeModel>)),
+    security(("api_key" = []))
+)]
+pub async fn list(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+) -> AppResult<Json<Vec<ActionTypeModel>>> {
+    let action_type = ActionTypeService::list(repository.cache(), operator.tenant_id)?;
     Ok(Json(action_type))
 }
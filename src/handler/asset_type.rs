@@ -1,9 +1,43 @@
-use crate::{model::asset_type::AssetTypeModel, service::asset_type::AssetTypeService};
-use axum::Json;
+use crate::{
+    model::{asset_supply::AssetSupplyModel, asset_type::AssetTypeModel},
+    repository::{cache::CacheRepository, postgres::PgRepository},
+    service::{asset_type::AssetTypeService, client::Operator},
+};
+use axum::{Extension, Json};
 use axum_kit::AppResult;
 
-// 资产类型列表
-pub async fn list() -> AppResult<Json<&'static Vec<AssetTypeModel>>> {
-    let asset_type = AssetTypeService::list();
+/// 资产类型列表
+#[utoipa::path(
+    get,
+    path = "/assets",
+    tag = "asset_type",
+    operation_id = "list_asset_types",
+    responses((status = 200, description = "当前租户已启用的资产类型", body = Vec<AssetTypeModel>)),
+    security(("api_key" = []))
+)]
+pub async fn list(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+) -> AppResult<Json<Vec<AssetTypeModel>>> {
+    let asset_type = AssetTypeService::list(repository.cache(), operator.tenant_id)?;
     Ok(Json(asset_type))
 }
+
+/// 资产流通量统计
+///
+/// 按资产类型汇总所有账户的余额，不包括发行、销毁系统账户
+#[utoipa::path(
+    get,
+    path = "/assets/supply",
+    tag = "asset_type",
+    operation_id = "asset_supply",
+    responses((status = 200, description = "当前租户已启用资产类型的流通量", body = Vec<AssetSupplyModel>)),
+    security(("api_key" = []))
+)]
+pub async fn supply(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+) -> AppResult<Json<Vec<AssetSupplyModel>>> {
+    let asset_supplies = AssetTypeService::supplies(&repository, operator.tenant_id).await?;
+    Ok(Json(asset_supplies))
+}
@@ -0,0 +1,50 @@
+use crate::{
+    model::change_log::ChangeLogModel,
+    service::{change_log::ChangeLogService, client::Operator},
+};
+use axum::{Extension, Json};
+use axum_kit::{validation::ValidatedJson, AppResult};
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Deserialize, Validate, ToSchema, Debug)]
+pub struct ChangeLogsRequest {
+    /// 表名，为空时查询所有表
+    #[validate(length(min = 1))]
+    #[schema(min_length = 1)]
+    pub table_name: Option<String>,
+    /// 上一页最后一条日志的`log_id`，为空时从最新日志开始查询
+    #[validate(range(min = 1))]
+    #[schema(minimum = 1)]
+    pub before_log_id: Option<i32>,
+    #[serde(default = "default_limit")]
+    #[validate(range(min = 1, max = 100))]
+    #[schema(minimum = 1, maximum = 100, default = 20)]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    20
+}
+
+/// 数据变更日志
+#[utoipa::path(
+    post,
+    path = "/change_logs",
+    tag = "change_log",
+    operation_id = "list_change_logs",
+    request_body = ChangeLogsRequest,
+    responses(
+        (status = 200, description = "按`log_id`倒序的当前租户数据变更日志", body = Vec<ChangeLogModel>),
+        (status = 400, description = "参数错误"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn list(
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<ChangeLogsRequest>,
+) -> AppResult<Json<Vec<ChangeLogModel>>> {
+    let change_logs = ChangeLogService::list(operator.tenant_id, &payload).await?;
+    Ok(Json(change_logs))
+}
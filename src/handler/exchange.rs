@@ -0,0 +1,62 @@
+use super::account::{deserialize_user_id, validate_amount, validate_user_id};
+use crate::{
+    model::exchange_log::ExchangeLogModel,
+    repository::postgres::PgRepository,
+    service::{client::Operator, exchange::ExchangeService},
+};
+use axum::{Extension, Json};
+use axum_kit::{validation::ValidatedJson, AppResult};
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Deserialize, Validate, ToSchema, Debug)]
+pub struct ExchangeRequest {
+    #[serde(deserialize_with = "deserialize_user_id")]
+    #[validate(length(min = 1, max = 64), custom(function = "validate_user_id"))]
+    #[schema(value_type = String, min_length = 1, max_length = 64, example = "1234567890123456789")]
+    pub user_id: String,
+    /// 源资产类型id，扣减该资产
+    pub from_asset_type_id: i32,
+    /// 目标资产类型id，增加该资产
+    pub to_asset_type_id: i32,
+    /// 扣减的源资产数量，最多6位小数
+    #[validate(range(min = 0.000001), custom(function = "validate_amount"))]
+    #[schema(minimum = 0.000001, multiple_of = 0.000001)]
+    pub amount: f64,
+    /// 订单号，同一租户下唯一，两条账户操作日志使用该订单号
+    #[validate(length(min = 32))]
+    #[schema(min_length = 32)]
+    pub order_number: String,
+    #[validate(length(min = 1))]
+    #[schema(min_length = 1)]
+    pub description: String,
+}
+
+/// 资产兑换
+///
+/// 按当前生效的汇率扣除点差后，将同一用户的源资产兑换为目标资产，在同一事务内完成
+#[utoipa::path(
+    post,
+    path = "/accounts/exchange",
+    tag = "account",
+    params(("x-operator" = Option<String>, Header, description = "实际操作人")),
+    request_body = ExchangeRequest,
+    responses(
+        (status = 200, description = "兑换成功", body = ExchangeLogModel),
+        (status = 400, description = "参数错误"),
+        (status = 402, description = "源资产账户余额不足"),
+        (status = 403, description = "存在未启用账户"),
+        (status = 404, description = "资产类型、账户或汇率不存在"),
+        (status = 409, description = "存在已处理的订单"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn exchange(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<ExchangeRequest>,
+) -> AppResult<Json<ExchangeLogModel>> {
+    let exchange_log = ExchangeService::exchange(&repository, &payload, &operator).await?;
+    Ok(Json(exchange_log))
+}
@@ -0,0 +1,65 @@
+use crate::{
+    repository::{cache::CacheRepository, postgres::PgRepository},
+    service::health::HealthService,
+};
+use axum::{http::StatusCode, Extension, Json};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema, Debug)]
+pub struct ReadinessResponse {
+    pub postgres: bool,
+    pub redis: bool,
+    pub asset_type: bool,
+    pub action_type: bool,
+    pub client: bool,
+    pub limit_rule: bool,
+    pub fee_rule: bool,
+}
+
+impl ReadinessResponse {
+    pub fn is_ready(&self) -> bool {
+        self.postgres
+            && self.redis
+            && self.asset_type
+            && self.action_type
+            && self.client
+            && self.limit_rule
+            && self.fee_rule
+    }
+}
+
+/// 存活检查
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "health",
+    responses((status = 200, description = "服务存活"))
+)]
+pub async fn healthz() -> StatusCode {
+    StatusCode::OK
+}
+
+/// 就绪检查
+///
+/// 数据库、缓存连接正常且资产类型、账户操作类型、客户端、限额规则配置加载完成后才返回 200
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    responses(
+        (status = 200, description = "服务就绪", body = ReadinessResponse),
+        (status = 503, description = "服务未就绪", body = ReadinessResponse),
+    )
+)]
+pub async fn readyz(
+    Extension(repository): Extension<PgRepository>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let readiness = HealthService::readiness(repository.cache()).await;
+    let status = if readiness.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness))
+}
@@ -0,0 +1,179 @@
+use super::account::{deserialize_user_id, validate_amount, validate_user_id};
+use crate::{
+    model::hold::HoldModel,
+    repository::postgres::PgRepository,
+    service::{client::Operator, hold::HoldService},
+};
+use axum::{http::StatusCode, Extension, Json};
+use axum_kit::{validation::ValidatedJson, AppResult};
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Deserialize, Validate, ToSchema, Debug)]
+pub struct HoldRequest {
+    #[serde(deserialize_with = "deserialize_user_id")]
+    #[validate(length(min = 1, max = 64), custom(function = "validate_user_id"))]
+    #[schema(value_type = String, min_length = 1, max_length = 64, example = "1234567890123456789")]
+    pub user_id: String,
+    /// 当前租户已启用的资产类型id，见`GET /assets`
+    pub asset_type_id: i32,
+    /// 外部引用(如支付授权号)，同一租户下唯一，同时作为冻结操作的订单号
+    #[validate(length(min = 1, max = 128))]
+    #[schema(min_length = 1, max_length = 128)]
+    pub reference: String,
+    /// 冻结金额，最多6位小数
+    #[validate(range(min = 0.000001), custom(function = "validate_amount"))]
+    #[schema(minimum = 0.000001, multiple_of = 0.000001)]
+    pub amount: f64,
+    /// 有效期(秒)，过期后剩余金额自动释放
+    #[validate(range(min = 1, max = 2592000))]
+    #[schema(minimum = 1, maximum = 2592000)]
+    pub expires_in: i64,
+    #[validate(length(min = 1))]
+    #[schema(min_length = 1)]
+    pub description: String,
+}
+
+#[derive(Deserialize, Validate, ToSchema, Debug)]
+pub struct HoldReferenceRequest {
+    #[validate(length(min = 1, max = 128))]
+    #[schema(min_length = 1, max_length = 128)]
+    pub reference: String,
+}
+
+#[derive(Deserialize, Validate, ToSchema, Debug)]
+pub struct HoldCaptureRequest {
+    #[validate(length(min = 1, max = 128))]
+    #[schema(min_length = 1, max_length = 128)]
+    pub reference: String,
+    /// 扣款金额，最多6位小数，为空时扣除全部剩余金额
+    #[validate(range(min = 0.000001), custom(function = "validate_amount"))]
+    #[schema(minimum = 0.000001, multiple_of = 0.000001)]
+    pub amount: Option<f64>,
+    /// 订单号，同一账户下唯一，部分扣款时每次使用不同的订单号
+    #[validate(length(min = 32))]
+    #[schema(min_length = 32)]
+    pub order_number: String,
+    #[validate(length(min = 1))]
+    #[schema(min_length = 1)]
+    pub description: String,
+}
+
+#[derive(Deserialize, Validate, ToSchema, Debug)]
+pub struct HoldReleaseRequest {
+    #[validate(length(min = 1, max = 128))]
+    #[schema(min_length = 1, max_length = 128)]
+    pub reference: String,
+    #[validate(length(min = 1))]
+    #[schema(min_length = 1)]
+    pub description: String,
+}
+
+/// 创建预授权
+///
+/// 按`FRZ`将可用余额转入冻结余额
+#[utoipa::path(
+    post,
+    path = "/holds/new",
+    operation_id = "create_hold",
+    tag = "hold",
+    params(("x-operator" = Option<String>, Header, description = "实际操作人")),
+    request_body = HoldRequest,
+    responses(
+        (status = 201, description = "创建成功", body = HoldModel),
+        (status = 400, description = "参数错误"),
+        (status = 402, description = "可用余额不足"),
+        (status = 403, description = "账户未启用"),
+        (status = 404, description = "资产类型或账户不存在"),
+        (status = 409, description = "预授权已存在"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn create(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<HoldRequest>,
+) -> AppResult<(StatusCode, Json<HoldModel>)> {
+    let hold = HoldService::create(&repository, &payload, &operator).await?;
+    Ok((StatusCode::CREATED, Json(hold)))
+}
+
+/// 预授权信息
+#[utoipa::path(
+    post,
+    path = "/holds/info",
+    operation_id = "hold_info",
+    tag = "hold",
+    request_body = HoldReferenceRequest,
+    responses(
+        (status = 200, description = "预授权信息", body = HoldModel),
+        (status = 400, description = "参数错误"),
+        (status = 404, description = "预授权不存在"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn info(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<HoldReferenceRequest>,
+) -> AppResult<Json<HoldModel>> {
+    let hold = HoldService::info(&repository, operator.tenant_id, &payload).await?;
+    Ok(Json(hold))
+}
+
+/// 预授权扣款
+///
+/// 按`FB_EXP`从冻结余额扣款，支持多次部分扣款，全部扣除后预授权结束
+#[utoipa::path(
+    post,
+    path = "/holds/capture",
+    operation_id = "capture_hold",
+    tag = "hold",
+    params(("x-operator" = Option<String>, Header, description = "实际操作人")),
+    request_body = HoldCaptureRequest,
+    responses(
+        (status = 200, description = "扣款成功", body = HoldModel),
+        (status = 400, description = "参数错误或超出剩余金额"),
+        (status = 403, description = "账户未启用"),
+        (status = 404, description = "预授权不存在"),
+        (status = 409, description = "预授权已结束、已过期或存在已处理的订单"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn capture(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<HoldCaptureRequest>,
+) -> AppResult<Json<HoldModel>> {
+    let hold = HoldService::capture(&repository, &payload, &operator).await?;
+    Ok(Json(hold))
+}
+
+/// 释放预授权
+///
+/// 按`UFZ`将剩余金额转回可用余额，预授权结束
+#[utoipa::path(
+    post,
+    path = "/holds/release",
+    operation_id = "release_hold",
+    tag = "hold",
+    params(("x-operator" = Option<String>, Header, description = "实际操作人")),
+    request_body = HoldReleaseRequest,
+    responses(
+        (status = 200, description = "释放成功", body = HoldModel),
+        (status = 400, description = "参数错误"),
+        (status = 403, description = "账户未启用"),
+        (status = 404, description = "预授权不存在"),
+        (status = 409, description = "预授权已结束"),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn release(
+    Extension(repository): Extension<PgRepository>,
+    Extension(operator): Extension<Operator>,
+    ValidatedJson(payload): ValidatedJson<HoldReleaseRequest>,
+) -> AppResult<Json<HoldModel>> {
+    let hold = HoldService::release(&repository, &payload, &operator).await?;
+    Ok(Json(hold))
+}
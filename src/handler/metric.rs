@@ -0,0 +1,16 @@
+use crate::service::metric::MetricService;
+use axum_kit::AppResult;
+
+/// Prometheus 指标
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    responses(
+        (status = 200, description = "Prometheus 文本格式指标", body = String, content_type = "text/plain"),
+        (status = 503, description = "监控指标未初始化"),
+    )
+)]
+pub async fn render() -> AppResult<String> {
+    MetricService::render()
+}
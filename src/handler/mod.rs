@@ -1,3 +1,9 @@
 pub mod account;
+pub mod action_job;
 pub mod action_type;
 pub mod asset_type;
+pub mod change_log;
+pub mod exchange;
+pub mod health;
+pub mod hold;
+pub mod metric;
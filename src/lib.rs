@@ -0,0 +1,6 @@
+pub mod handler;
+pub mod middleware;
+pub mod model;
+pub mod repository;
+pub mod route;
+pub mod service;
@@ -1,20 +1,104 @@
-mod handler;
-mod model;
-mod route;
-mod service;
+use amazing::{
+    repository::{cache::CacheRepository, postgres::PgRepository},
+    route,
+    service::{
+        self, account_log_partition::AccountLogPartitionService, action_job::ActionJobService,
+        action_type::ActionTypeService, asset_type::AssetTypeService,
+        balance_lot::BalanceLotService, client::ClientService, fee_rule::FeeRuleService,
+        hold::HoldService, limit_rule::LimitRuleService,
+    },
+};
+use axum_kit::AppResult;
+use std::time::Duration;
+
+const CONFIG_PATH: &str = "config.toml";
+// 配置缓存加载最大尝试次数
+const INIT_MAX_ATTEMPTS: u32 = 5;
+// 过期预授权释放间隔
+const HOLD_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+// 过期余额批次扣减间隔
+const LOT_EXPIRE_INTERVAL: Duration = Duration::from_secs(3600);
+// 批量账户操作任务领取间隔
+const ACTION_JOB_POLL_INTERVAL: Duration = Duration::from_secs(5);
+// 账户操作日志分区创建间隔
+const PARTITION_CREATE_INTERVAL: Duration = Duration::from_secs(86400);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let _worker_guard =
-        axum_kit::bootstrap::Application::default("config.toml", route::api::init())?
-            .before_run(|| {
-                tokio::spawn(async move {
-                    service::asset_type::AssetTypeService::init().await?;
-                    service::action_type::ActionTypeService::init().await?;
-                    Ok(())
-                })
+    service::metric::MetricService::init()?;
+    // 路由及后台任务使用同一存储，共享其配置缓存
+    let repository = PgRepository::default();
+    let router = route::api::router(repository.clone());
+    let _worker_guard = axum_kit::bootstrap::Application::default(CONFIG_PATH, router)?
+        .before_run(|| {
+            tokio::spawn(async move {
+                // 配置缓存加载完成后才启动服务，加载失败时直接退出，不会带着未初始化的缓存提供服务
+                init_with_retry(&repository)
+                    .await
+                    .map_err(|err| anyhow::anyhow!("Failed to initialize caches: {err}"))?;
+                tokio::spawn(HoldService::sweep(repository.clone(), HOLD_SWEEP_INTERVAL));
+                tokio::spawn(BalanceLotService::sweep(
+                    repository.clone(),
+                    LOT_EXPIRE_INTERVAL,
+                ));
+                tokio::spawn(ActionJobService::work(
+                    repository.clone(),
+                    ACTION_JOB_POLL_INTERVAL,
+                ));
+                tokio::spawn(AccountLogPartitionService::sweep(
+                    repository,
+                    PARTITION_CREATE_INTERVAL,
+                ));
+                Ok(())
             })
-            .run()
-            .await?;
+        })
+        .run()
+        .await?;
+    Ok(())
+}
+
+// 加载资产类型、账户操作类型、客户端、限额规则及手续费规则配置缓存，失败时按指数退避重试
+async fn init_with_retry(repository: &PgRepository) -> AppResult<()> {
+    let mut attempt = 1;
+    loop {
+        match init(repository).await {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt < INIT_MAX_ATTEMPTS => {
+                let delay = Duration::from_secs(1 << attempt);
+                tracing::warn!(
+                    "Failed to initialize caches (attempt {attempt}/{INIT_MAX_ATTEMPTS}), \
+                     retrying in {delay:?}: {err}"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(err) => {
+                tracing::error!(
+                    "Failed to initialize caches after {INIT_MAX_ATTEMPTS} attempts: {err}"
+                );
+                return Err(err);
+            }
+        }
+    }
+}
+
+// 已加载成功的缓存不重复加载
+async fn init(repository: &PgRepository) -> AppResult<()> {
+    let cache = repository.cache();
+    if !AssetTypeService::is_initialized(cache) {
+        AssetTypeService::init(repository).await?;
+    }
+    if !ActionTypeService::is_initialized(cache) {
+        ActionTypeService::init(repository).await?;
+    }
+    if !ClientService::is_initialized(cache) {
+        ClientService::init(repository).await?;
+    }
+    if !LimitRuleService::is_initialized(cache) {
+        LimitRuleService::init(repository, true).await?;
+    }
+    if !FeeRuleService::is_initialized(cache) {
+        FeeRuleService::init(repository).await?;
+    }
     Ok(())
 }
@@ -0,0 +1,45 @@
+use crate::{
+    repository::{cache::CacheRepository, postgres::PgRepository},
+    service::client::{ClientService, Operator},
+};
+use axum::{
+    extract::Request,
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use axum_kit::{error::Error, AppResult};
+
+// 操作人请求头，由客户端透传实际发起操作的管理员或业务方标识
+const X_OPERATOR: &str = "x-operator";
+
+// 客户端认证
+// 请求需携带`Authorization: Bearer <api_key>`，认证通过后将`Operator`写入请求扩展
+// 租户由客户端决定，后续所有查询均限定在该租户内
+pub async fn auth(mut request: Request, next: Next) -> AppResult<Response> {
+    let api_key = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| Error::Custom(StatusCode::UNAUTHORIZED, "认证失败".to_string()))?;
+    let repository = request.extensions().get::<PgRepository>().ok_or_else(|| {
+        Error::Custom(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "存储未初始化".to_string(),
+        )
+    })?;
+    let client = ClientService::by_api_key(repository.cache(), api_key)?;
+    let name = request
+        .headers()
+        .get(X_OPERATOR)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .unwrap_or_default();
+    request.extensions_mut().insert(Operator {
+        client_id: client.id,
+        tenant_id: client.tenant_id,
+        name,
+    });
+    Ok(next.run(request).await)
+}
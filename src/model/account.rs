@@ -1,14 +1,36 @@
 use axum_kit::AppResult;
-use serde::Serialize;
+use serde::{Deserialize, Serialize, Serializer};
 use sqlx::{
     types::{chrono::NaiveDateTime, Decimal},
     PgExecutor,
 };
+use utoipa::ToSchema;
 
-#[derive(Serialize)]
+// 外部用户标识，兼容整数(如 64 位雪花 id)和字符串(如 UUID)两种形式，统一按字符串处理
+// 整数`42`与字符串`"42"`为同一用户
+#[derive(Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum UserId {
+    Integer(u64),
+    String(String),
+}
+
+// 纯数字(不超过 64 位且没有前导零)的`user_id`返回整数，与按字符串存储前的返回格式一致，其他返回字符串
+pub fn serialize_user_id<S: Serializer>(user_id: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    match user_id.parse::<u64>() {
+        Ok(integer) if integer.to_string() == user_id => serializer.serialize_u64(integer),
+        _ => serializer.serialize_str(user_id),
+    }
+}
+
+#[derive(Serialize, ToSchema, Clone)]
 pub struct AccountModel {
     pub id: i32,
-    pub user_id: i32,
+    #[serde(skip_serializing)]
+    pub tenant_id: i32,
+    #[serde(serialize_with = "serialize_user_id")]
+    #[schema(value_type = UserId)]
+    pub user_id: String,
     pub asset_type_id: i32,
     pub available_balance: Decimal,
     pub frozen_balance: Decimal,
@@ -26,15 +48,17 @@ pub struct AccountModel {
 impl AccountModel {
     pub async fn create(
         executor: impl PgExecutor<'_>,
-        user_id: i32,
+        tenant_id: i32,
+        user_id: &str,
         asset_type_id: i32,
     ) -> AppResult<Self> {
         let account = sqlx::query_as!(
             Self,
-            r#"insert into account (user_id, asset_type_id, is_active)
-                values ($1, $2, true)
+            r#"insert into account (tenant_id, user_id, asset_type_id, is_active)
+                values ($1, $2, $3, true)
             returning
                 id,
+                tenant_id,
                 user_id,
                 asset_type_id,
                 available_balance,
@@ -44,6 +68,7 @@ impl AccountModel {
                 is_active,
                 created_at,
                 updated_at"#,
+            tenant_id,
             user_id,
             asset_type_id
         )
@@ -54,13 +79,15 @@ impl AccountModel {
 
     pub async fn find(
         executor: impl PgExecutor<'_>,
-        user_id: i32,
+        tenant_id: i32,
+        user_id: &str,
         asset_type_id: i32,
     ) -> AppResult<Self> {
         let account = sqlx::query_as!(
             Self,
             r#"select
                 id,
+                tenant_id,
                 user_id,
                 asset_type_id,
                 available_balance,
@@ -73,8 +100,10 @@ impl AccountModel {
             from
                 account
             where
-                user_id = $1
-                and asset_type_id = $2"#,
+                tenant_id = $1
+                and user_id = $2
+                and asset_type_id = $3"#,
+            tenant_id,
             user_id,
             asset_type_id
         )
@@ -85,13 +114,15 @@ impl AccountModel {
 
     pub async fn find_multiple(
         executor: impl PgExecutor<'_>,
-        user_id: i32,
+        tenant_id: i32,
+        user_id: &str,
         asset_type_ids: Vec<i32>,
     ) -> AppResult<Vec<Self>> {
         let accounts = sqlx::query_as!(
             Self,
             r#"select
                 id,
+                tenant_id,
                 user_id,
                 asset_type_id,
                 available_balance,
@@ -104,8 +135,10 @@ impl AccountModel {
             from
                 account
             where
-                user_id = $1
-                and asset_type_id = any($2)"#,
+                tenant_id = $1
+                and user_id = $2
+                and asset_type_id = any($3)"#,
+            tenant_id,
             user_id,
             &asset_type_ids
         )
@@ -114,10 +147,49 @@ impl AccountModel {
         Ok(accounts)
     }
 
+    // 锁定账户行，需在事务内调用
+    pub async fn find_for_update(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> AppResult<Self> {
+        let account = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                user_id,
+                asset_type_id,
+                available_balance,
+                frozen_balance,
+                total_income,
+                total_expense,
+                is_active,
+                created_at,
+                updated_at
+            from
+                account
+            where
+                tenant_id = $1
+                and user_id = $2
+                and asset_type_id = $3
+            for update"#,
+            tenant_id,
+            user_id,
+            asset_type_id
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(account)
+    }
+
     #[allow(dead_code)]
+    #[allow(clippy::too_many_arguments)]
     pub async fn update_balance(
         executor: impl PgExecutor<'_>,
-        user_id: i32,
+        tenant_id: i32,
+        user_id: &str,
         asset_type_id: i32,
         amount_available_balance: Decimal,
         amount_frozen_balance: Decimal,
@@ -127,16 +199,18 @@ impl AccountModel {
         let account = sqlx::query_as!(
             Self,
             r#"update account
-                set available_balance = available_balance + $3,
-                frozen_balance = frozen_balance + $4,
-                total_income = total_income + $5,
-                total_expense = total_expense + $6,
+                set available_balance = available_balance + $4,
+                frozen_balance = frozen_balance + $5,
+                total_income = total_income + $6,
+                total_expense = total_expense + $7,
                 updated_at = now()
             where
-                user_id = $1
-                and asset_type_id = $2
+                tenant_id = $1
+                and user_id = $2
+                and asset_type_id = $3
             returning
                 id,
+                tenant_id,
                 user_id,
                 asset_type_id,
                 available_balance,
@@ -146,6 +220,7 @@ impl AccountModel {
                 is_active,
                 created_at,
                 updated_at"#,
+            tenant_id,
             user_id,
             asset_type_id,
             amount_available_balance,
@@ -162,11 +237,13 @@ impl AccountModel {
     #[allow(dead_code)]
     pub async fn is_exists(
         executor: impl PgExecutor<'_>,
-        user_id: i32,
+        tenant_id: i32,
+        user_id: &str,
         asset_type_id: i32,
     ) -> bool {
         if let Ok(Some(exists)) = sqlx::query_scalar!(
-            r#"select exists(select 1 from account where user_id = $1 and asset_type_id = $2)"#,
+            r#"select exists(select 1 from account where tenant_id = $1 and user_id = $2 and asset_type_id = $3)"#,
+            tenant_id,
             user_id,
             asset_type_id
         )
@@ -182,11 +259,13 @@ impl AccountModel {
     #[allow(dead_code)]
     pub async fn is_active(
         executor: impl PgExecutor<'_>,
-        user_id: i32,
+        tenant_id: i32,
+        user_id: &str,
         asset_type_id: i32,
     ) -> bool {
         if let Ok(Some(exists)) = sqlx::query_scalar!(
-            r#"select exists(select 1 from account where user_id = $1 and asset_type_id = $2 and is_active = true)"#,
+            r#"select exists(select 1 from account where tenant_id = $1 and user_id = $2 and asset_type_id = $3 and is_active = true)"#,
+            tenant_id,
             user_id,
             asset_type_id
         )
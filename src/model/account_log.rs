@@ -1,13 +1,22 @@
+use super::account::{serialize_user_id, UserId};
 use axum_kit::AppResult;
+use chrono::SubsecRound;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
 use sqlx::{
     types::{chrono::NaiveDateTime, Decimal},
-    PgExecutor,
+    PgConnection, PgExecutor,
 };
+use utoipa::ToSchema;
 
-#[derive(Serialize)]
+// 新写入日志的哈希格式版本，见`AccountLogModel::versioned_hash`
+const HASH_VERSION: u32 = 2;
+
+#[derive(Serialize, ToSchema, Clone, sqlx::FromRow)]
 pub struct AccountLogModel {
     pub id: i64,
+    #[serde(skip_serializing)]
+    pub tenant_id: i32,
     pub account_id: i32,
     pub action_type_id: i32,
     pub amount_available_balance: Decimal,
@@ -20,13 +29,35 @@ pub struct AccountLogModel {
     pub total_expense_after: Decimal,
     pub order_number: String,
     pub description: String,
+    pub client_id: i32,
+    pub operator: String,
+    pub hash: String,
     pub created_at: NaiveDateTime,
+    /// 交易id，同一事务内写入的日志相同，历史日志为空
+    // 本字段之前分离的分区表中没有该列
+    #[sqlx(default)]
+    pub transaction_id: Option<i64>,
+}
+
+// 交易中的一条账户操作日志及其账户
+#[derive(Serialize, ToSchema, Clone, sqlx::FromRow)]
+pub struct AccountTransactionLegModel {
+    #[serde(serialize_with = "serialize_user_id")]
+    #[schema(value_type = UserId)]
+    pub user_id: String,
+    pub asset_type_id: i32,
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub account_log: AccountLogModel,
 }
 
 impl AccountLogModel {
+    // 需在锁定账户行的事务内调用，保证同一账户的日志哈希链串行写入
+    // `created_at`为空时为当前时间，导入历史数据时使用原始时间
     #[allow(clippy::too_many_arguments)]
     pub async fn create(
-        executor: impl PgExecutor<'_>,
+        conn: &mut PgConnection,
+        tenant_id: i32,
         account_id: i32,
         action_type_id: i32,
         amount_available_balance: Decimal,
@@ -39,9 +70,39 @@ impl AccountLogModel {
         total_expense_after: Decimal,
         order_number: &str,
         description: &str,
+        client_id: i32,
+        operator: &str,
+        created_at: Option<NaiveDateTime>,
+        transaction_id: i64,
     ) -> AppResult<()> {
+        let (prev_hash, now) = Self::chain_head(&mut *conn, account_id).await?;
+        // 数据库只保存到微秒，哈希按保存后的时间计算
+        let created_at = created_at.map_or(now, |created_at| created_at.trunc_subsecs(6));
+        let mut account_log = AccountLogModel {
+            id: 0,
+            tenant_id,
+            account_id,
+            action_type_id,
+            amount_available_balance,
+            amount_frozen_balance,
+            amount_total_income,
+            amount_total_expense,
+            available_balance_after,
+            frozen_balance_after,
+            total_income_after,
+            total_expense_after,
+            order_number: order_number.to_string(),
+            description: description.to_string(),
+            client_id,
+            operator: operator.to_string(),
+            hash: String::new(),
+            created_at,
+            transaction_id: Some(transaction_id),
+        };
+        account_log.hash = account_log.chain_hash(&prev_hash);
         sqlx::query!(
             r#"insert into account_log (
+                tenant_id,
                 account_id,
                 action_type_id,
                 amount_available_balance,
@@ -53,9 +114,15 @@ impl AccountLogModel {
                 total_income_after,
                 total_expense_after,
                 order_number,
-                description
+                description,
+                client_id,
+                operator,
+                hash,
+                created_at,
+                transaction_id
             )
-            values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)"#,
+            values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)"#,
+            tenant_id,
             account_id,
             action_type_id,
             amount_available_balance,
@@ -67,13 +134,247 @@ impl AccountLogModel {
             total_income_after,
             total_expense_after,
             order_number,
-            description
+            description,
+            client_id,
+            operator,
+            account_log.hash,
+            created_at,
+            transaction_id
         )
-        .execute(executor)
+        .execute(conn)
         .await?;
         Ok(())
     }
 
+    pub async fn next_transaction_id(executor: impl PgExecutor<'_>) -> AppResult<i64> {
+        let transaction_id = sqlx::query_scalar!(
+            r#"select nextval('account_transaction_id_seq') as "transaction_id!""#
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(transaction_id)
+    }
+
+    // 交易的所有日志按`id`正序排列，已归档的日志不包括在内
+    pub async fn fetch_transaction(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        transaction_id: i64,
+    ) -> AppResult<Vec<AccountTransactionLegModel>> {
+        let legs = sqlx::query_as(
+            r#"select
+                account.user_id,
+                account.asset_type_id,
+                account_log.*
+            from
+                account_log
+                join account on account.id = account_log.account_id
+            where
+                account_log.tenant_id = $1
+                and account_log.transaction_id = $2
+            order by
+                account_log.id"#,
+        )
+        .bind(tenant_id)
+        .bind(transaction_id)
+        .fetch_all(executor)
+        .await?;
+        Ok(legs)
+    }
+
+    // 账户最新一条日志的哈希及当前事务时间，日志均已归档时哈希取自归档检查点，不存在时为空字符串
+    pub async fn chain_head(
+        executor: impl PgExecutor<'_>,
+        account_id: i32,
+    ) -> AppResult<(String, NaiveDateTime)> {
+        let head = sqlx::query!(
+            r#"select
+                coalesce((
+                    select
+                        hash
+                    from
+                        (
+                            (select id, hash from account_log where account_id = $1 order by id desc limit 1)
+                            union all
+                            (select id, hash from account_log_checkpoint where account_id = $1)
+                        ) l
+                    order by
+                        id desc
+                    limit 1
+                ), '') as "hash!",
+                localtimestamp as "now!""#,
+            account_id
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok((head.hash, head.now))
+    }
+
+    // 哈希格式切换前最后一条日志 id，之后的日志哈希均需为当前格式
+    pub async fn fetch_hash_cutover(executor: impl PgExecutor<'_>) -> AppResult<i64> {
+        let last_legacy_id =
+            sqlx::query_scalar!(r#"select last_legacy_id from account_log_hash_cutover"#)
+                .fetch_optional(executor)
+                .await?;
+        Ok(last_legacy_id.unwrap_or_default())
+    }
+
+    // 按最新的哈希格式计算本条日志的哈希 = 版本前缀 + sha256(上一条日志哈希 + 本条日志内容)
+    pub fn chain_hash(&self, prev_hash: &str) -> String {
+        self.versioned_hash(HASH_VERSION, prev_hash)
+    }
+
+    // 校验本条日志哈希是否与上一条日志哈希及本条日志内容一致
+    // 只有 id 不超过`hash_cutover`的历史日志可以是 v1 格式，之后的日志必须为当前格式
+    pub fn verify_hash(&self, prev_hash: &str, hash_cutover: i64) -> bool {
+        let version = match self.hash.split_once(':') {
+            Some((version, _)) => match version.strip_prefix('v').map(str::parse) {
+                Some(Ok(version)) => version,
+                _ => return false,
+            },
+            None => 1,
+        };
+        if version != HASH_VERSION && (version != 1 || self.id > hash_cutover) {
+            return false;
+        }
+        self.hash == self.versioned_hash(version, prev_hash)
+    }
+
+    // v1(无版本前缀)：账户、账户操作类型、变动金额、变动后余额、订单号、说明、客户端及操作人
+    // v2：在 v1 的基础上增加日志时间、租户及交易id
+    // 金额统一去除末尾零，避免同一数值因精度表示不同而得到不同哈希
+    fn versioned_hash(&self, version: u32, prev_hash: &str) -> String {
+        let mut content = vec![
+            serde_json::json!(prev_hash),
+            serde_json::json!(self.account_id),
+            serde_json::json!(self.action_type_id),
+            serde_json::json!([
+                self.amount_available_balance,
+                self.amount_frozen_balance,
+                self.amount_total_income,
+                self.amount_total_expense,
+            ]
+            .map(|amount| amount.normalize().to_string())),
+            serde_json::json!([
+                self.available_balance_after,
+                self.frozen_balance_after,
+                self.total_income_after,
+                self.total_expense_after,
+            ]
+            .map(|balance| balance.normalize().to_string())),
+            serde_json::json!(self.order_number),
+            serde_json::json!(self.description),
+            serde_json::json!(self.client_id),
+            serde_json::json!(self.operator),
+        ];
+        if version == 1 {
+            return hex::encode(Sha256::digest(
+                serde_json::Value::from(content).to_string().as_bytes(),
+            ));
+        }
+        content.insert(0, serde_json::json!(version));
+        content.push(serde_json::json!(self
+            .created_at
+            .format("%Y-%m-%dT%H:%M:%S%.6f")
+            .to_string()));
+        content.push(serde_json::json!(self.tenant_id));
+        content.push(serde_json::json!(self.transaction_id));
+        let digest = Sha256::digest(serde_json::Value::from(content).to_string().as_bytes());
+        format!("v{version}:{}", hex::encode(digest))
+    }
+
+    // 按`id`正序分页查询，`after_id`为上一页最后一条记录的`id`
+    pub async fn fetch_chain(
+        executor: impl PgExecutor<'_>,
+        account_id: i32,
+        after_id: i64,
+        limit: i64,
+    ) -> AppResult<Vec<Self>> {
+        let account_logs = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                account_id,
+                action_type_id,
+                amount_available_balance,
+                amount_frozen_balance,
+                amount_total_income,
+                amount_total_expense,
+                available_balance_after,
+                frozen_balance_after,
+                total_income_after,
+                total_expense_after,
+                order_number,
+                description,
+                client_id,
+                operator,
+                hash,
+                created_at,
+                transaction_id
+            from
+                account_log
+            where
+                account_id = $1
+                and id > $2
+            order by
+                id
+            limit $3"#,
+            account_id,
+            after_id,
+            limit
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(account_logs)
+    }
+
+    // 按`id`倒序分页查询，`before_id`为上一页最后一条记录的`id`
+    pub async fn fetch_page(
+        executor: impl PgExecutor<'_>,
+        account_id: i32,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> AppResult<Vec<Self>> {
+        let account_logs = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                account_id,
+                action_type_id,
+                amount_available_balance,
+                amount_frozen_balance,
+                amount_total_income,
+                amount_total_expense,
+                available_balance_after,
+                frozen_balance_after,
+                total_income_after,
+                total_expense_after,
+                order_number,
+                description,
+                client_id,
+                operator,
+                hash,
+                created_at,
+                transaction_id
+            from
+                account_log
+            where
+                account_id = $1
+                and ($2::bigint is null or id < $2)
+            order by
+                id desc
+            limit $3"#,
+            account_id,
+            before_id,
+            limit
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(account_logs)
+    }
+
     // 账户操作日志是否存在
     #[allow(dead_code)]
     pub async fn is_exists(
@@ -83,7 +384,7 @@ impl AccountLogModel {
         order_number: &str,
     ) -> bool {
         if let Ok(Some(exists)) = sqlx::query_scalar!(
-            r#"select exists(select 1 from account_log where account_id = $1 and action_type_id = $2 and order_number = $3)"#,
+            r#"select exists(select 1 from account_log_order where account_id = $1 and action_type_id = $2 and order_number = $3)"#,
             account_id,
             action_type_id,
             order_number
@@ -95,4 +396,179 @@ impl AccountLogModel {
         }
         false
     }
+
+    // 按`id`正序分页查询`[start_at, end_at)`时间范围内的日志，`after_id`为上一页最后一条记录的`id`
+    pub async fn fetch_range(
+        executor: impl PgExecutor<'_>,
+        account_id: i32,
+        start_at: NaiveDateTime,
+        end_at: NaiveDateTime,
+        after_id: i64,
+        limit: i64,
+    ) -> AppResult<Vec<Self>> {
+        let account_logs = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                account_id,
+                action_type_id,
+                amount_available_balance,
+                amount_frozen_balance,
+                amount_total_income,
+                amount_total_expense,
+                available_balance_after,
+                frozen_balance_after,
+                total_income_after,
+                total_expense_after,
+                order_number,
+                description,
+                client_id,
+                operator,
+                hash,
+                created_at,
+                transaction_id
+            from
+                account_log
+            where
+                account_id = $1
+                and created_at >= $2
+                and created_at < $3
+                and id > $4
+            order by
+                id
+            limit $5"#,
+            account_id,
+            start_at,
+            end_at,
+            after_id,
+            limit
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(account_logs)
+    }
+
+    // `at`之前的最后一条日志，其操作后余额即`at`时的余额
+    // 该日志已归档时取自归档检查点，检查点之前的时间没有可用的日志
+    pub async fn find_last_before(
+        executor: impl PgExecutor<'_>,
+        account_id: i32,
+        at: NaiveDateTime,
+    ) -> AppResult<Option<Self>> {
+        let account_log = sqlx::query_as!(
+            Self,
+            r#"select
+                id as "id!",
+                tenant_id as "tenant_id!",
+                account_id as "account_id!",
+                action_type_id as "action_type_id!",
+                amount_available_balance as "amount_available_balance!",
+                amount_frozen_balance as "amount_frozen_balance!",
+                amount_total_income as "amount_total_income!",
+                amount_total_expense as "amount_total_expense!",
+                available_balance_after as "available_balance_after!",
+                frozen_balance_after as "frozen_balance_after!",
+                total_income_after as "total_income_after!",
+                total_expense_after as "total_expense_after!",
+                order_number as "order_number!",
+                description as "description!",
+                client_id as "client_id!",
+                operator as "operator!",
+                hash as "hash!",
+                created_at as "created_at!",
+                transaction_id
+            from
+                (
+                    (
+                        select
+                            *
+                        from
+                            account_log
+                        where
+                            account_id = $1
+                            and created_at < $2
+                        order by
+                            created_at desc,
+                            id desc
+                        limit 1
+                    )
+                    union all
+                    (
+                        select
+                            *
+                        from
+                            account_log_checkpoint
+                        where
+                            account_id = $1
+                            and created_at < $2
+                    )
+                ) l
+            order by
+                created_at desc,
+                id desc
+            limit 1"#,
+            account_id,
+            at
+        )
+        .fetch_optional(executor)
+        .await?;
+        Ok(account_log)
+    }
+
+    // 账户已归档的最后一条日志
+    pub async fn find_checkpoint(
+        executor: impl PgExecutor<'_>,
+        account_id: i32,
+    ) -> AppResult<Option<Self>> {
+        let account_log = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                account_id,
+                action_type_id,
+                amount_available_balance,
+                amount_frozen_balance,
+                amount_total_income,
+                amount_total_expense,
+                available_balance_after,
+                frozen_balance_after,
+                total_income_after,
+                total_expense_after,
+                order_number,
+                description,
+                client_id,
+                operator,
+                hash,
+                created_at,
+                transaction_id
+            from
+                account_log_checkpoint
+            where
+                account_id = $1"#,
+            account_id
+        )
+        .fetch_optional(executor)
+        .await?;
+        Ok(account_log)
+    }
+
+    // 按`id`正序分页查询已分离分区表中的日志，`name`需为`account_log_partition`中记录的分区表名
+    pub async fn fetch_detached(
+        executor: impl PgExecutor<'_>,
+        name: &str,
+        after_id: i64,
+        limit: i64,
+    ) -> AppResult<Vec<Self>> {
+        let account_logs = sqlx::query_as(&format!(
+            r#"select * from "{}" where id > $1 order by id limit $2"#,
+            name.replace('"', "\"\"")
+        ))
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(executor)
+        .await?;
+        Ok(account_logs)
+    }
 }
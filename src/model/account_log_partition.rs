@@ -0,0 +1,115 @@
+use axum_kit::AppResult;
+use serde::Serialize;
+use sqlx::{types::chrono::NaiveDateTime, PgExecutor};
+
+#[derive(Serialize, sqlx::Type, PartialEq, Clone, Copy, Debug)]
+#[sqlx(
+    type_name = "account_log_partition_status_enum",
+    rename_all = "UPPERCASE"
+)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum PartitionStatus {
+    // 已挂载，日志查询包括该分区
+    Attached,
+    // 已分离，分区表仍保留，待导出后删除
+    Detached,
+    // 已删除
+    Dropped,
+}
+
+// `account_log`的月分区
+#[derive(Serialize, Clone)]
+pub struct AccountLogPartitionModel {
+    pub name: String,
+    pub range_start: NaiveDateTime,
+    pub range_end: NaiveDateTime,
+    pub status: PartitionStatus,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl AccountLogPartitionModel {
+    // 创建`month`所在月的分区，已创建(包括已归档)时不做任何操作
+    pub async fn create(executor: impl PgExecutor<'_>, month: NaiveDateTime) -> AppResult<()> {
+        sqlx::query!(r#"select from create_account_log_partition($1)"#, month)
+            .execute(executor)
+            .await?;
+        Ok(())
+    }
+
+    // 默认分区中日志所在的月份，需为这些月份创建分区
+    pub async fn fetch_default_months(
+        executor: impl PgExecutor<'_>,
+    ) -> AppResult<Vec<NaiveDateTime>> {
+        let months = sqlx::query_scalar!(
+            r#"select distinct date_trunc('month', created_at) as "month!" from account_log_default"#
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(months)
+    }
+
+    pub async fn fetch_all(executor: impl PgExecutor<'_>) -> AppResult<Vec<Self>> {
+        let partitions = sqlx::query_as!(
+            Self,
+            r#"select
+                name,
+                range_start,
+                range_end,
+                status as "status!: PartitionStatus",
+                created_at,
+                updated_at
+            from
+                account_log_partition
+            order by
+                range_start"#
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(partitions)
+    }
+
+    // 结束时间不晚于`before`且未删除的分区
+    pub async fn fetch_archivable(
+        executor: impl PgExecutor<'_>,
+        before: NaiveDateTime,
+    ) -> AppResult<Vec<Self>> {
+        let partitions = sqlx::query_as!(
+            Self,
+            r#"select
+                name,
+                range_start,
+                range_end,
+                status as "status!: PartitionStatus",
+                created_at,
+                updated_at
+            from
+                account_log_partition
+            where
+                range_end <= $1
+                and status <> 'DROPPED'
+            order by
+                range_start"#,
+            before
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(partitions)
+    }
+
+    // 记录各账户在分区内的最后一条日志作为归档检查点后分离分区，已分离时不做任何操作
+    pub async fn detach(executor: impl PgExecutor<'_>, name: &str) -> AppResult<()> {
+        sqlx::query!(r#"select from detach_account_log_partition($1)"#, name)
+            .execute(executor)
+            .await?;
+        Ok(())
+    }
+
+    // 删除已分离的分区表，未分离时不做任何操作
+    pub async fn drop_table(executor: impl PgExecutor<'_>, name: &str) -> AppResult<()> {
+        sqlx::query!(r#"select from drop_account_log_partition($1)"#, name)
+            .execute(executor)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,343 @@
+use axum_kit::AppResult;
+use serde::{Deserialize, Serialize};
+use sqlx::{types::chrono::NaiveDateTime, PgConnection, PgExecutor};
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema, sqlx::Type, PartialEq, Clone, Copy, Debug)]
+#[sqlx(type_name = "action_job_status_enum", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ActionJobStatus {
+    // 等待处理
+    Pending,
+    // 处理中，服务中断后由其他实例在租约过期后继续处理
+    Running,
+    // 所有账户操作均已处理
+    Completed,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, sqlx::Type, PartialEq, Clone, Copy, Debug)]
+#[sqlx(type_name = "action_job_item_status_enum", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ActionJobItemStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Serialize, ToSchema, Clone, Debug)]
+pub struct ActionJobModel {
+    pub id: i64,
+    #[serde(skip_serializing)]
+    pub tenant_id: i32,
+    pub status: ActionJobStatus,
+    pub chunk_size: i32,
+    pub total_count: i32,
+    pub succeeded_count: i32,
+    pub failed_count: i32,
+    pub client_id: i32,
+    pub operator: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+// 任务中的一个账户操作及其处理结果
+#[derive(Serialize, Clone, Debug)]
+pub struct ActionJobItemModel {
+    #[serde(skip_serializing)]
+    pub job_id: i64,
+    pub seq: i32,
+    pub user_id: String,
+    pub asset_type_id: i32,
+    pub action_type_id: i32,
+    pub amount: f64,
+    pub order_number: String,
+    pub description: String,
+    pub status: ActionJobItemStatus,
+    pub message: String,
+}
+
+impl ActionJobModel {
+    pub async fn create(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        chunk_size: i32,
+        total_count: i32,
+        client_id: i32,
+        operator: &str,
+    ) -> AppResult<Self> {
+        let action_job = sqlx::query_as!(
+            Self,
+            r#"insert into action_job (tenant_id, chunk_size, total_count, client_id, operator)
+            values ($1, $2, $3, $4, $5)
+            returning
+                id,
+                tenant_id,
+                status as "status!: ActionJobStatus",
+                chunk_size,
+                total_count,
+                succeeded_count,
+                failed_count,
+                client_id,
+                operator,
+                created_at,
+                updated_at,
+                finished_at"#,
+            tenant_id,
+            chunk_size,
+            total_count,
+            client_id,
+            operator
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(action_job)
+    }
+
+    pub async fn find(executor: impl PgExecutor<'_>, tenant_id: i32, id: i64) -> AppResult<Self> {
+        let action_job = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                status as "status!: ActionJobStatus",
+                chunk_size,
+                total_count,
+                succeeded_count,
+                failed_count,
+                client_id,
+                operator,
+                created_at,
+                updated_at,
+                finished_at
+            from
+                action_job
+            where
+                tenant_id = $1
+                and id = $2"#,
+            tenant_id,
+            id
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(action_job)
+    }
+
+    // 领取最早提交的待处理任务，或超过`lease_seconds`秒未更新的处理中任务
+    // 多个实例同时领取时跳过已被锁定的任务
+    pub async fn claim(
+        executor: impl PgExecutor<'_>,
+        lease_seconds: f64,
+    ) -> AppResult<Option<Self>> {
+        let action_job = sqlx::query_as!(
+            Self,
+            r#"update action_job
+            set
+                status = 'RUNNING',
+                updated_at = localtimestamp
+            where
+                id = (
+                    select
+                        id
+                    from
+                        action_job
+                    where
+                        status = 'PENDING'
+                        or (
+                            status = 'RUNNING'
+                            and updated_at < localtimestamp - make_interval(secs => $1)
+                        )
+                    order by
+                        id
+                    limit 1
+                    for update skip locked
+                )
+            returning
+                id,
+                tenant_id,
+                status as "status!: ActionJobStatus",
+                chunk_size,
+                total_count,
+                succeeded_count,
+                failed_count,
+                client_id,
+                operator,
+                created_at,
+                updated_at,
+                finished_at"#,
+            lease_seconds
+        )
+        .fetch_optional(executor)
+        .await?;
+        Ok(action_job)
+    }
+
+    pub async fn complete(executor: impl PgExecutor<'_>, id: i64) -> AppResult<Self> {
+        let action_job = sqlx::query_as!(
+            Self,
+            r#"update action_job
+            set
+                status = 'COMPLETED',
+                updated_at = localtimestamp,
+                finished_at = localtimestamp
+            where
+                id = $1
+            returning
+                id,
+                tenant_id,
+                status as "status!: ActionJobStatus",
+                chunk_size,
+                total_count,
+                succeeded_count,
+                failed_count,
+                client_id,
+                operator,
+                created_at,
+                updated_at,
+                finished_at"#,
+            id
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(action_job)
+    }
+}
+
+impl ActionJobItemModel {
+    // 批量写入任务明细，状态均为待处理
+    pub async fn create_multiple(executor: impl PgExecutor<'_>, items: &[Self]) -> AppResult<()> {
+        let mut job_ids = Vec::with_capacity(items.len());
+        let mut seqs = Vec::with_capacity(items.len());
+        let mut user_ids = Vec::with_capacity(items.len());
+        let mut asset_type_ids = Vec::with_capacity(items.len());
+        let mut action_type_ids = Vec::with_capacity(items.len());
+        let mut amounts = Vec::with_capacity(items.len());
+        let mut order_numbers = Vec::with_capacity(items.len());
+        let mut descriptions = Vec::with_capacity(items.len());
+        for item in items {
+            job_ids.push(item.job_id);
+            seqs.push(item.seq);
+            user_ids.push(item.user_id.clone());
+            asset_type_ids.push(item.asset_type_id);
+            action_type_ids.push(item.action_type_id);
+            amounts.push(item.amount);
+            order_numbers.push(item.order_number.clone());
+            descriptions.push(item.description.clone());
+        }
+        sqlx::query!(
+            r#"insert into action_job_item (
+                job_id,
+                seq,
+                user_id,
+                asset_type_id,
+                action_type_id,
+                amount,
+                order_number,
+                description
+            )
+            select
+                *
+            from
+                unnest($1::bigint[], $2::int[], $3::text[], $4::int[], $5::int[], $6::float8[], $7::text[], $8::text[])"#,
+            &job_ids,
+            &seqs,
+            &user_ids,
+            &asset_type_ids,
+            &action_type_ids,
+            &amounts,
+            &order_numbers,
+            &descriptions
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    // 按`seq`正序分页查询，`status`为空表示不限，`after_seq`为上一页最后一条记录的`seq`
+    pub async fn fetch(
+        executor: impl PgExecutor<'_>,
+        job_id: i64,
+        status: Option<ActionJobItemStatus>,
+        after_seq: i32,
+        limit: i64,
+    ) -> AppResult<Vec<Self>> {
+        let items = sqlx::query_as!(
+            Self,
+            r#"select
+                job_id,
+                seq,
+                user_id,
+                asset_type_id,
+                action_type_id,
+                amount,
+                order_number,
+                description,
+                status as "status!: ActionJobItemStatus",
+                message
+            from
+                action_job_item
+            where
+                job_id = $1
+                and ($2::action_job_item_status_enum is null or status = $2)
+                and seq > $3
+            order by
+                seq
+            limit $4"#,
+            job_id,
+            status as Option<ActionJobItemStatus>,
+            after_seq,
+            limit
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(items)
+    }
+
+    // 记录一块账户操作的处理结果并累加任务计数，同时作为处理中任务的心跳
+    pub async fn update_results(
+        conn: &mut PgConnection,
+        job_id: i64,
+        succeeded: &[i32],
+        failed: &[(i32, String)],
+    ) -> AppResult<()> {
+        let (failed_seqs, messages): (Vec<i32>, Vec<String>) = failed.iter().cloned().unzip();
+        sqlx::query!(
+            r#"update action_job_item set status = 'SUCCEEDED' where job_id = $1 and seq = any($2)"#,
+            job_id,
+            succeeded
+        )
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query!(
+            r#"update action_job_item i
+            set
+                status = 'FAILED',
+                message = f.message
+            from
+                unnest($2::int[], $3::text[]) as f (seq, message)
+            where
+                i.job_id = $1
+                and i.seq = f.seq"#,
+            job_id,
+            &failed_seqs,
+            &messages
+        )
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query!(
+            r#"update action_job
+            set
+                succeeded_count = succeeded_count + $2,
+                failed_count = failed_count + $3,
+                updated_at = localtimestamp
+            where
+                id = $1"#,
+            job_id,
+            succeeded.len() as i32,
+            failed.len() as i32
+        )
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+}
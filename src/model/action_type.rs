@@ -5,8 +5,9 @@ use sqlx::{
     types::{chrono::NaiveDateTime, Decimal},
     PgExecutor,
 };
+use utoipa::ToSchema;
 
-#[derive(Serialize, sqlx::Type, PartialEq)]
+#[derive(Serialize, ToSchema, sqlx::Type, PartialEq, Clone, Copy, Debug)]
 #[sqlx(type_name = "change_enum", rename_all = "UPPERCASE")]
 pub enum Change {
     Inc,
@@ -17,24 +18,32 @@ pub enum Change {
 impl Change {
     #[allow(dead_code)]
     pub fn calculate_change(&self, amount: f64) -> Decimal {
-        let decimal_amount = Decimal::from_f64(amount.abs()).unwrap().trunc_with_scale(6);
+        self.apply(Decimal::from_f64(amount.abs()).unwrap().trunc_with_scale(6))
+    }
+
+    // `amount`为精确的正数金额
+    pub fn apply(&self, amount: Decimal) -> Decimal {
         match self {
-            Change::Inc => decimal_amount,
-            Change::Dec => -decimal_amount,
+            Change::Inc => amount,
+            Change::Dec => -amount,
             Change::None => Decimal::ZERO,
         }
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema, Clone)]
 pub struct ActionTypeModel {
     pub id: i32,
+    #[serde(skip_serializing)]
+    pub tenant_id: i32,
     pub name: String,
     pub description: String,
     pub available_balance_change: Change,
     pub frozen_balance_change: Change,
     pub total_income_change: Change,
     pub total_expense_change: Change,
+    /// 是否单边操作，复式记账资产类型的单边操作由系统账户生成对应分录，双边操作需在同一批次内合计为零
+    pub is_single_sided: bool,
     #[allow(dead_code)]
     #[serde(skip_serializing)]
     pub is_active: bool,
@@ -47,17 +56,26 @@ pub struct ActionTypeModel {
 }
 
 impl ActionTypeModel {
+    // 总余额(可用余额 + 冻结余额)的变动金额
+    pub fn balance_change(&self, amount: f64) -> Decimal {
+        self.available_balance_change.calculate_change(amount)
+            + self.frozen_balance_change.calculate_change(amount)
+    }
+
+    // 所有租户已启用的账户操作类型
     pub async fn fetch_all(executor: impl PgExecutor<'_>) -> AppResult<Vec<Self>> {
         let action_types: Vec<Self> = sqlx::query_as!(
             Self,
             r#"select
                 id,
+                tenant_id,
                 name,
                 description,
                 available_balance_change as "available_balance_change!: Change",
                 frozen_balance_change as "frozen_balance_change!: Change",
                 total_income_change as "total_income_change!: Change",
                 total_expense_change as "total_expense_change!: Change",
+                is_single_sided,
                 is_active,
                 created_at,
                 updated_at
@@ -70,4 +88,123 @@ impl ActionTypeModel {
         .await?;
         Ok(action_types)
     }
+
+    // 某租户所有账户操作类型，包括未启用的账户操作类型
+    pub async fn fetch_all_including_inactive(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+    ) -> AppResult<Vec<Self>> {
+        let action_types: Vec<Self> = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                name,
+                description,
+                available_balance_change as "available_balance_change!: Change",
+                frozen_balance_change as "frozen_balance_change!: Change",
+                total_income_change as "total_income_change!: Change",
+                total_expense_change as "total_expense_change!: Change",
+                is_single_sided,
+                is_active,
+                created_at,
+                updated_at
+            from
+                action_type
+            where
+                tenant_id = $1
+            order by
+                id"#,
+            tenant_id
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(action_types)
+    }
+
+    // 包括未启用的账户操作类型
+    pub async fn find_by_name(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        name: &str,
+    ) -> AppResult<Self> {
+        let action_type = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                name,
+                description,
+                available_balance_change as "available_balance_change!: Change",
+                frozen_balance_change as "frozen_balance_change!: Change",
+                total_income_change as "total_income_change!: Change",
+                total_expense_change as "total_expense_change!: Change",
+                is_single_sided,
+                is_active,
+                created_at,
+                updated_at
+            from
+                action_type
+            where
+                tenant_id = $1
+                and name = $2"#,
+            tenant_id,
+            name
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(action_type)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        name: &str,
+        description: &str,
+        available_balance_change: Change,
+        frozen_balance_change: Change,
+        total_income_change: Change,
+        total_expense_change: Change,
+        is_active: bool,
+    ) -> AppResult<Self> {
+        let action_type = sqlx::query_as!(
+            Self,
+            r#"insert into action_type (
+                tenant_id,
+                name,
+                description,
+                available_balance_change,
+                frozen_balance_change,
+                total_income_change,
+                total_expense_change,
+                is_active
+            )
+            values ($1, $2, $3, $4, $5, $6, $7, $8)
+            returning
+                id,
+                tenant_id,
+                name,
+                description,
+                available_balance_change as "available_balance_change!: Change",
+                frozen_balance_change as "frozen_balance_change!: Change",
+                total_income_change as "total_income_change!: Change",
+                total_expense_change as "total_expense_change!: Change",
+                is_single_sided,
+                is_active,
+                created_at,
+                updated_at"#,
+            tenant_id,
+            name,
+            description,
+            available_balance_change as Change,
+            frozen_balance_change as Change,
+            total_income_change as Change,
+            total_expense_change as Change,
+            is_active
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(action_type)
+    }
 }
@@ -0,0 +1,92 @@
+use axum_kit::AppResult;
+use serde::Serialize;
+use sqlx::{types::Decimal, PgExecutor};
+use utoipa::ToSchema;
+
+// 资产流通量统计，不包括发行、销毁系统账户
+#[derive(Serialize, ToSchema, Clone, Debug)]
+pub struct AssetSupplyModel {
+    pub asset_type_id: i32,
+    pub available_balance: Decimal,
+    pub frozen_balance: Decimal,
+    pub total_income: Decimal,
+    pub total_expense: Decimal,
+    /// 发行上限，为空表示不限
+    pub max_supply: Option<Decimal>,
+}
+
+impl AssetSupplyModel {
+    // 某租户已启用资产类型的流通量统计
+    pub async fn fetch_all(executor: impl PgExecutor<'_>, tenant_id: i32) -> AppResult<Vec<Self>> {
+        let asset_supplies = sqlx::query_as!(
+            Self,
+            r#"select
+                asset_type.id as asset_type_id,
+                coalesce(sum(account.available_balance), 0) as "available_balance!",
+                coalesce(sum(account.frozen_balance), 0) as "frozen_balance!",
+                coalesce(sum(account.total_income), 0) as "total_income!",
+                coalesce(sum(account.total_expense), 0) as "total_expense!",
+                asset_type.max_supply
+            from
+                asset_type
+                left join account on account.asset_type_id = asset_type.id
+                    and account.user_id is distinct from asset_type.issuance_user_id
+                    and account.user_id is distinct from asset_type.burn_user_id
+            where
+                asset_type.tenant_id = $1
+                and asset_type.is_active = true
+            group by
+                asset_type.id
+            order by
+                asset_type.id"#,
+            tenant_id
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(asset_supplies)
+    }
+
+    // 累加流通量，不检查发行上限，需在事务内调用
+    pub async fn add_supply(
+        executor: impl PgExecutor<'_>,
+        asset_type_id: i32,
+        amount: Decimal,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            r#"update asset_type set supply = supply + $2 where id = $1"#,
+            asset_type_id,
+            amount
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    // 流通量增加后不超出发行上限时累加，返回是否已累加，需在事务内调用
+    // 更新时锁定资产类型行直至事务结束，并发事务依次检查
+    pub async fn add_capped_supply(
+        executor: impl PgExecutor<'_>,
+        asset_type_id: i32,
+        amount: Decimal,
+    ) -> AppResult<bool> {
+        let updated = sqlx::query_scalar!(
+            r#"update asset_type
+            set
+                supply = supply + $2
+            where
+                id = $1
+                and (
+                    $2::decimal <= 0
+                    or max_supply is null
+                    or supply + $2 <= max_supply
+                )
+            returning
+                id"#,
+            asset_type_id,
+            amount
+        )
+        .fetch_optional(executor)
+        .await?;
+        Ok(updated.is_some())
+    }
+}
@@ -1,12 +1,30 @@
 use axum_kit::AppResult;
 use serde::Serialize;
-use sqlx::{types::chrono::NaiveDateTime, PgExecutor};
+use sqlx::{
+    types::{chrono::NaiveDateTime, Decimal},
+    PgExecutor,
+};
+use utoipa::ToSchema;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema, Clone)]
 pub struct AssetTypeModel {
     pub id: i32,
+    #[serde(skip_serializing)]
+    pub tenant_id: i32,
     pub name: String,
     pub description: String,
+    /// 余额批次有效天数，为空表示不按批次跟踪余额
+    pub lot_ttl_days: Option<i32>,
+    #[serde(skip_serializing)]
+    pub issuance_user_id: Option<String>,
+    #[serde(skip_serializing)]
+    pub burn_user_id: Option<String>,
+    #[serde(skip_serializing)]
+    pub fee_user_id: Option<String>,
+    /// 是否启用复式记账
+    pub is_double_entry: bool,
+    /// 发行上限，为空表示不限
+    pub max_supply: Option<Decimal>,
     #[allow(dead_code)]
     #[serde(skip_serializing)]
     pub is_active: bool,
@@ -19,13 +37,21 @@ pub struct AssetTypeModel {
 }
 
 impl AssetTypeModel {
+    // 所有租户已启用的资产类型
     pub async fn fetch_all(executor: impl PgExecutor<'_>) -> AppResult<Vec<Self>> {
         let asset_types: Vec<Self> = sqlx::query_as!(
             Self,
             r#"select
                 id,
+                tenant_id,
                 name,
                 description,
+                lot_ttl_days,
+                issuance_user_id,
+                burn_user_id,
+                fee_user_id,
+                is_double_entry,
+                max_supply,
                 is_active,
                 created_at,
                 updated_at
@@ -38,4 +64,75 @@ impl AssetTypeModel {
         .await?;
         Ok(asset_types)
     }
+
+    // 某租户所有资产类型，包括未启用的资产类型
+    pub async fn fetch_all_including_inactive(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+    ) -> AppResult<Vec<Self>> {
+        let asset_types: Vec<Self> = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                name,
+                description,
+                lot_ttl_days,
+                issuance_user_id,
+                burn_user_id,
+                fee_user_id,
+                is_double_entry,
+                max_supply,
+                is_active,
+                created_at,
+                updated_at
+            from
+                asset_type
+            where
+                tenant_id = $1
+            order by
+                id"#,
+            tenant_id
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(asset_types)
+    }
+
+    pub async fn create(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        name: &str,
+        description: &str,
+        lot_ttl_days: Option<i32>,
+        is_active: bool,
+    ) -> AppResult<Self> {
+        let asset_type = sqlx::query_as!(
+            Self,
+            r#"insert into asset_type (tenant_id, name, description, lot_ttl_days, is_active)
+                values ($1, $2, $3, $4, $5)
+            returning
+                id,
+                tenant_id,
+                name,
+                description,
+                lot_ttl_days,
+                issuance_user_id,
+                burn_user_id,
+                fee_user_id,
+                is_double_entry,
+                max_supply,
+                is_active,
+                created_at,
+                updated_at"#,
+            tenant_id,
+            name,
+            description,
+            lot_ttl_days,
+            is_active
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(asset_type)
+    }
 }
@@ -0,0 +1,190 @@
+use super::account::AccountModel;
+use axum_kit::AppResult;
+use serde::Serialize;
+use sqlx::{
+    types::{chrono::NaiveDateTime, Decimal},
+    PgConnection, PgExecutor,
+};
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema, Clone)]
+pub struct BalanceLotModel {
+    pub id: i64,
+    #[serde(skip_serializing)]
+    pub tenant_id: i32,
+    pub account_id: i32,
+    pub amount: Decimal,
+    pub remaining_amount: Decimal,
+    pub expires_at: NaiveDateTime,
+    pub order_number: String,
+    pub created_at: NaiveDateTime,
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
+    pub updated_at: NaiveDateTime,
+}
+
+impl BalanceLotModel {
+    // 资产类型未设置`lot_ttl_days`时不产生批次
+    // 有效期从`created_at`起算，为空时使用当前时间，导入历史数据时传入原始时间
+    pub async fn create(
+        executor: impl PgExecutor<'_>,
+        account: &AccountModel,
+        amount: Decimal,
+        order_number: &str,
+        created_at: Option<NaiveDateTime>,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            r#"insert into balance_lot (tenant_id, account_id, amount, remaining_amount, expires_at, order_number, created_at)
+            select
+                $1,
+                $2,
+                $3,
+                $3,
+                coalesce($6, localtimestamp) + make_interval(days => lot_ttl_days),
+                $4,
+                coalesce($6, localtimestamp)
+            from
+                asset_type
+            where
+                id = $5
+                and lot_ttl_days is not null"#,
+            account.tenant_id,
+            account.id,
+            amount,
+            order_number,
+            account.asset_type_id,
+            created_at
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    // 按先进先出扣减批次剩余金额，批次剩余金额不足时只扣减到零
+    // 需在锁定账户行的事务内调用
+    pub async fn consume(
+        conn: &mut PgConnection,
+        account_id: i32,
+        amount: Decimal,
+    ) -> AppResult<()> {
+        let lots = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                account_id,
+                amount,
+                remaining_amount,
+                expires_at,
+                order_number,
+                created_at,
+                updated_at
+            from
+                balance_lot
+            where
+                account_id = $1
+                and remaining_amount > 0
+            order by
+                expires_at,
+                id"#,
+            account_id
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        let mut amount = amount;
+        for lot in lots {
+            if amount.is_zero() {
+                break;
+            }
+            let consumed = amount.min(lot.remaining_amount);
+            sqlx::query!(
+                r#"update balance_lot set remaining_amount = remaining_amount - $2 where id = $1"#,
+                lot.id,
+                consumed
+            )
+            .execute(&mut *conn)
+            .await?;
+            amount -= consumed;
+        }
+        Ok(())
+    }
+
+    // 账户已过期但仍有剩余金额的批次
+    pub async fn fetch_expired(
+        executor: impl PgExecutor<'_>,
+        account_id: i32,
+    ) -> AppResult<Vec<Self>> {
+        let lots = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                account_id,
+                amount,
+                remaining_amount,
+                expires_at,
+                order_number,
+                created_at,
+                updated_at
+            from
+                balance_lot
+            where
+                account_id = $1
+                and remaining_amount > 0
+                and expires_at <= localtimestamp
+            order by
+                expires_at,
+                id"#,
+            account_id
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(lots)
+    }
+
+    // 所有租户存在过期批次且可用余额为正数的账户，按 id 正序返回`after_id`之后的账户
+    // 过期批次的剩余金额处于冻结中时，待解冻后再扣减
+    pub async fn fetch_expired_accounts(
+        executor: impl PgExecutor<'_>,
+        after_id: i32,
+        limit: i64,
+    ) -> AppResult<Vec<AccountModel>> {
+        let accounts = sqlx::query_as!(
+            AccountModel,
+            r#"select
+                id,
+                tenant_id,
+                user_id,
+                asset_type_id,
+                available_balance,
+                frozen_balance,
+                total_income,
+                total_expense,
+                is_active,
+                created_at,
+                updated_at
+            from
+                account
+            where
+                id > $1
+                and available_balance > 0
+                and id in (
+                    select
+                        account_id
+                    from
+                        balance_lot
+                    where
+                        remaining_amount > 0
+                        and expires_at <= localtimestamp
+                )
+            order by
+                id
+            limit $2"#,
+            after_id,
+            limit
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(accounts)
+    }
+}
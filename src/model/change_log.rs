@@ -0,0 +1,79 @@
+use axum_kit::AppResult;
+use serde::Serialize;
+use sqlx::{
+    types::{chrono::NaiveDateTime, JsonValue},
+    PgExecutor,
+};
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct ChangeLogModel {
+    pub log_id: i32,
+    pub table_name: String,
+    pub operation_type: String,
+    #[schema(value_type = Option<Object>)]
+    pub old_data: Option<JsonValue>,
+    #[schema(value_type = Option<Object>)]
+    pub new_data: Option<JsonValue>,
+    pub client_id: i32,
+    pub operator: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl ChangeLogModel {
+    // 按`log_id`倒序分页查询某租户的变更日志，`before_log_id`为上一页最后一条记录的`log_id`
+    pub async fn fetch_page(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        table_name: Option<&str>,
+        before_log_id: Option<i32>,
+        limit: i64,
+    ) -> AppResult<Vec<Self>> {
+        let change_logs = sqlx::query_as!(
+            Self,
+            r#"select
+                log_id,
+                table_name,
+                operation_type,
+                old_data,
+                new_data,
+                client_id,
+                operator,
+                created_at
+            from
+                change_log
+            where
+                tenant_id = $1
+                and ($2::text is null or table_name = $2)
+                and ($3::int is null or log_id < $3)
+            order by
+                log_id desc
+            limit $4"#,
+            tenant_id,
+            table_name,
+            before_log_id,
+            limit
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(change_logs)
+    }
+
+    // 设置当前事务的操作人，供`track_change`触发器写入`change_log`
+    pub async fn set_operator(
+        executor: impl PgExecutor<'_>,
+        client_id: i32,
+        operator: &str,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            r#"select
+                set_config('amazing.client_id', $1, true) as client_id,
+                set_config('amazing.operator', $2, true) as operator"#,
+            client_id.to_string(),
+            operator
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(())
+    }
+}
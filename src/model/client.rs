@@ -0,0 +1,47 @@
+use axum_kit::AppResult;
+use serde::Serialize;
+use sqlx::{types::chrono::NaiveDateTime, PgExecutor};
+
+#[derive(Serialize, Clone)]
+pub struct ClientModel {
+    pub id: i32,
+    #[serde(skip_serializing)]
+    pub tenant_id: i32,
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub api_key_hash: String,
+    pub description: String,
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
+    pub is_active: bool,
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
+    pub created_at: NaiveDateTime,
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
+    pub updated_at: NaiveDateTime,
+}
+
+impl ClientModel {
+    pub async fn fetch_all(executor: impl PgExecutor<'_>) -> AppResult<Vec<Self>> {
+        let clients: Vec<Self> = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                name,
+                api_key_hash,
+                description,
+                is_active,
+                created_at,
+                updated_at
+            from
+                client
+            where
+                is_active = true"#
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(clients)
+    }
+}
@@ -0,0 +1,102 @@
+use super::account::{serialize_user_id, UserId};
+use axum_kit::AppResult;
+use serde::Serialize;
+use sqlx::{
+    types::{chrono::NaiveDateTime, Decimal},
+    PgExecutor,
+};
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema, Clone)]
+pub struct ExchangeLogModel {
+    pub id: i64,
+    #[serde(skip_serializing)]
+    pub tenant_id: i32,
+    #[serde(serialize_with = "serialize_user_id")]
+    #[schema(value_type = UserId)]
+    pub user_id: String,
+    pub exchange_rate_id: i32,
+    pub from_asset_type_id: i32,
+    pub to_asset_type_id: i32,
+    pub rate: Decimal,
+    pub spread: Decimal,
+    pub from_amount: Decimal,
+    pub to_amount: Decimal,
+    pub order_number: String,
+    pub description: String,
+    pub client_id: i32,
+    pub operator: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl ExchangeLogModel {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        user_id: &str,
+        exchange_rate_id: i32,
+        from_asset_type_id: i32,
+        to_asset_type_id: i32,
+        rate: Decimal,
+        spread: Decimal,
+        from_amount: Decimal,
+        to_amount: Decimal,
+        order_number: &str,
+        description: &str,
+        client_id: i32,
+        operator: &str,
+    ) -> AppResult<Self> {
+        let exchange_log = sqlx::query_as!(
+            Self,
+            r#"insert into exchange_log (
+                tenant_id,
+                user_id,
+                exchange_rate_id,
+                from_asset_type_id,
+                to_asset_type_id,
+                rate,
+                spread,
+                from_amount,
+                to_amount,
+                order_number,
+                description,
+                client_id,
+                operator
+            )
+            values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            returning
+                id,
+                tenant_id,
+                user_id,
+                exchange_rate_id,
+                from_asset_type_id,
+                to_asset_type_id,
+                rate,
+                spread,
+                from_amount,
+                to_amount,
+                order_number,
+                description,
+                client_id,
+                operator,
+                created_at"#,
+            tenant_id,
+            user_id,
+            exchange_rate_id,
+            from_asset_type_id,
+            to_asset_type_id,
+            rate,
+            spread,
+            from_amount,
+            to_amount,
+            order_number,
+            description,
+            client_id,
+            operator
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(exchange_log)
+    }
+}
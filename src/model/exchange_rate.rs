@@ -0,0 +1,75 @@
+use axum_kit::AppResult;
+use serde::Serialize;
+use sqlx::{
+    types::{chrono::NaiveDateTime, Decimal},
+    PgExecutor,
+};
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema, Clone)]
+pub struct ExchangeRateModel {
+    pub id: i32,
+    #[serde(skip_serializing)]
+    pub tenant_id: i32,
+    pub from_asset_type_id: i32,
+    pub to_asset_type_id: i32,
+    pub rate: Decimal,
+    pub spread: Decimal,
+    pub effective_at: NaiveDateTime,
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
+    pub is_active: bool,
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
+    pub created_at: NaiveDateTime,
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
+    pub updated_at: NaiveDateTime,
+}
+
+impl ExchangeRateModel {
+    // 当前生效的汇率：已启用且生效时间不晚于当前时间的最新一条
+    pub async fn find_effective(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        from_asset_type_id: i32,
+        to_asset_type_id: i32,
+    ) -> AppResult<Self> {
+        let exchange_rate = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                from_asset_type_id,
+                to_asset_type_id,
+                rate,
+                spread,
+                effective_at,
+                is_active,
+                created_at,
+                updated_at
+            from
+                exchange_rate
+            where
+                tenant_id = $1
+                and from_asset_type_id = $2
+                and to_asset_type_id = $3
+                and is_active = true
+                and effective_at <= now()
+            order by
+                effective_at desc
+            limit 1"#,
+            tenant_id,
+            from_asset_type_id,
+            to_asset_type_id
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(exchange_rate)
+    }
+
+    // 扣除点差后实际使用的汇率
+    pub fn applied_rate(&self) -> Decimal {
+        self.rate * (Decimal::ONE - self.spread)
+    }
+}
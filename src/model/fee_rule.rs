@@ -0,0 +1,74 @@
+use axum_kit::AppResult;
+use serde::Serialize;
+use sqlx::{
+    types::{chrono::NaiveDateTime, Decimal},
+    PgExecutor,
+};
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema, Clone)]
+pub struct FeeRuleModel {
+    pub id: i32,
+    #[serde(skip_serializing)]
+    pub tenant_id: i32,
+    pub asset_type_id: i32,
+    pub action_type_id: i32,
+    pub fixed_amount: Decimal,
+    pub rate: Decimal,
+    pub min_amount: Option<Decimal>,
+    pub max_amount: Option<Decimal>,
+    #[serde(skip_serializing)]
+    pub house_user_id: Option<String>,
+    pub description: String,
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
+    pub is_active: bool,
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
+    pub created_at: NaiveDateTime,
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
+    pub updated_at: NaiveDateTime,
+}
+
+impl FeeRuleModel {
+    // 所有租户已启用的手续费规则
+    pub async fn fetch_all(executor: impl PgExecutor<'_>) -> AppResult<Vec<Self>> {
+        let fee_rules = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                asset_type_id,
+                action_type_id,
+                fixed_amount,
+                rate,
+                min_amount,
+                max_amount,
+                house_user_id,
+                description,
+                is_active,
+                created_at,
+                updated_at
+            from
+                fee_rule
+            where
+                is_active = true"#
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(fee_rules)
+    }
+
+    // 手续费 = 固定手续费 + 操作金额 × 费率，限制在最低、最高手续费之间，保留6位小数
+    pub fn calculate(&self, amount: Decimal) -> Decimal {
+        let mut fee = self.fixed_amount + amount * self.rate;
+        if let Some(min_amount) = self.min_amount {
+            fee = fee.max(min_amount);
+        }
+        if let Some(max_amount) = self.max_amount {
+            fee = fee.min(max_amount);
+        }
+        fee.trunc_with_scale(6)
+    }
+}
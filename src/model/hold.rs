@@ -0,0 +1,293 @@
+use super::account::{serialize_user_id, UserId};
+use axum_kit::AppResult;
+use serde::Serialize;
+use sqlx::{
+    types::{chrono::NaiveDateTime, Decimal},
+    PgExecutor,
+};
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema, sqlx::Type, PartialEq, Clone, Copy, Debug)]
+#[sqlx(type_name = "hold_status_enum", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HoldStatus {
+    // 冻结中，剩余金额可扣款或释放
+    Held,
+    // 已全部扣款
+    Captured,
+    // 剩余金额已主动释放
+    Released,
+    // 剩余金额已过期自动释放
+    Expired,
+}
+
+#[derive(Serialize, ToSchema, Clone)]
+pub struct HoldModel {
+    pub id: i64,
+    #[serde(skip_serializing)]
+    pub tenant_id: i32,
+    #[serde(skip_serializing)]
+    pub account_id: i32,
+    #[serde(serialize_with = "serialize_user_id")]
+    #[schema(value_type = UserId)]
+    pub user_id: String,
+    pub asset_type_id: i32,
+    pub reference: String,
+    pub amount: Decimal,
+    pub captured_amount: Decimal,
+    pub released_amount: Decimal,
+    pub status: HoldStatus,
+    pub expires_at: NaiveDateTime,
+    pub description: String,
+    pub client_id: i32,
+    pub operator: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl HoldModel {
+    // 仍处于冻结中的金额
+    pub fn remaining_amount(&self) -> Decimal {
+        self.amount - self.captured_amount - self.released_amount
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        account_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+        reference: &str,
+        amount: Decimal,
+        expires_in: i64,
+        description: &str,
+        client_id: i32,
+        operator: &str,
+    ) -> AppResult<Self> {
+        let hold = sqlx::query_as!(
+            Self,
+            r#"insert into hold (
+                tenant_id,
+                account_id,
+                user_id,
+                asset_type_id,
+                reference,
+                amount,
+                expires_at,
+                description,
+                client_id,
+                operator
+            )
+            values (
+                $1,
+                $2,
+                $3,
+                $4,
+                $5,
+                $6,
+                localtimestamp + make_interval(secs => $7::bigint),
+                $8,
+                $9,
+                $10
+            )
+            returning
+                id,
+                tenant_id,
+                account_id,
+                user_id,
+                asset_type_id,
+                reference,
+                amount,
+                captured_amount,
+                released_amount,
+                status as "status!: HoldStatus",
+                expires_at,
+                description,
+                client_id,
+                operator,
+                created_at,
+                updated_at"#,
+            tenant_id,
+            account_id,
+            user_id,
+            asset_type_id,
+            reference,
+            amount,
+            expires_in,
+            description,
+            client_id,
+            operator
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(hold)
+    }
+
+    pub async fn find(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        reference: &str,
+    ) -> AppResult<Self> {
+        let hold = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                account_id,
+                user_id,
+                asset_type_id,
+                reference,
+                amount,
+                captured_amount,
+                released_amount,
+                status as "status!: HoldStatus",
+                expires_at,
+                description,
+                client_id,
+                operator,
+                created_at,
+                updated_at
+            from
+                hold
+            where
+                tenant_id = $1
+                and reference = $2"#,
+            tenant_id,
+            reference
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(hold)
+    }
+
+    // 锁定预授权行，保证同一预授权的扣款、释放串行执行，需在事务内调用
+    pub async fn find_for_update(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        reference: &str,
+    ) -> AppResult<Self> {
+        let hold = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                account_id,
+                user_id,
+                asset_type_id,
+                reference,
+                amount,
+                captured_amount,
+                released_amount,
+                status as "status!: HoldStatus",
+                expires_at,
+                description,
+                client_id,
+                operator,
+                created_at,
+                updated_at
+            from
+                hold
+            where
+                tenant_id = $1
+                and reference = $2
+            for update"#,
+            tenant_id,
+            reference
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(hold)
+    }
+
+    // 所有租户已过期但仍处于冻结中的预授权，按过期时间正序
+    pub async fn fetch_expired(executor: impl PgExecutor<'_>, limit: i64) -> AppResult<Vec<Self>> {
+        let holds = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                account_id,
+                user_id,
+                asset_type_id,
+                reference,
+                amount,
+                captured_amount,
+                released_amount,
+                status as "status!: HoldStatus",
+                expires_at,
+                description,
+                client_id,
+                operator,
+                created_at,
+                updated_at
+            from
+                hold
+            where
+                status = 'HELD'
+                and expires_at <= localtimestamp
+            order by
+                expires_at
+            limit $1"#,
+            limit
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(holds)
+    }
+
+    // 按数据库时间判断是否已过期，与创建及过期释放使用同一时钟
+    pub async fn is_expired(
+        executor: impl PgExecutor<'_>,
+        expires_at: NaiveDateTime,
+    ) -> AppResult<bool> {
+        let expired =
+            sqlx::query_scalar!(r#"select $1 <= localtimestamp as "expired!""#, expires_at)
+                .fetch_one(executor)
+                .await?;
+        Ok(expired)
+    }
+
+    pub async fn update(
+        executor: impl PgExecutor<'_>,
+        id: i64,
+        captured_amount: Decimal,
+        released_amount: Decimal,
+        status: HoldStatus,
+    ) -> AppResult<Self> {
+        let hold = sqlx::query_as!(
+            Self,
+            r#"update hold
+            set
+                captured_amount = $2,
+                released_amount = $3,
+                status = $4
+            where
+                id = $1
+            returning
+                id,
+                tenant_id,
+                account_id,
+                user_id,
+                asset_type_id,
+                reference,
+                amount,
+                captured_amount,
+                released_amount,
+                status as "status!: HoldStatus",
+                expires_at,
+                description,
+                client_id,
+                operator,
+                created_at,
+                updated_at"#,
+            id,
+            captured_amount,
+            released_amount,
+            status as HoldStatus
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(hold)
+    }
+}
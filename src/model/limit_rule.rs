@@ -0,0 +1,152 @@
+use axum_kit::AppResult;
+use serde::Serialize;
+use sqlx::{
+    types::{chrono::NaiveDateTime, Decimal},
+    PgExecutor,
+};
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema, Clone)]
+pub struct LimitRuleModel {
+    pub id: i32,
+    #[serde(skip_serializing)]
+    pub tenant_id: i32,
+    pub asset_type_id: Option<i32>,
+    pub action_type_id: Option<i32>,
+    pub window_seconds: i32,
+    pub max_amount: Option<Decimal>,
+    pub max_count: Option<i32>,
+    pub description: String,
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
+    pub is_active: bool,
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
+    pub created_at: NaiveDateTime,
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
+    pub updated_at: NaiveDateTime,
+}
+
+// 用户在某个窗口内已使用的限额
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct LimitUsage {
+    pub count: i64,
+    pub amount: Decimal,
+}
+
+impl LimitRuleModel {
+    // 所有租户已启用的限额规则
+    pub async fn fetch_all(executor: impl PgExecutor<'_>) -> AppResult<Vec<Self>> {
+        let limit_rules = sqlx::query_as!(
+            Self,
+            r#"select
+                id,
+                tenant_id,
+                asset_type_id,
+                action_type_id,
+                window_seconds,
+                max_amount,
+                max_count,
+                description,
+                is_active,
+                created_at,
+                updated_at
+            from
+                limit_rule
+            where
+                is_active = true
+            order by
+                id"#
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(limit_rules)
+    }
+
+    // 是否适用于该资产类型及账户操作类型
+    pub fn matches(&self, asset_type_id: i32, action_type_id: i32) -> bool {
+        let asset_type_matches = match self.asset_type_id {
+            Some(id) => id == asset_type_id,
+            None => true,
+        };
+        let action_type_matches = match self.action_type_id {
+            Some(id) => id == action_type_id,
+            None => true,
+        };
+        asset_type_matches && action_type_matches
+    }
+
+    pub fn is_exceeded(&self, usage: LimitUsage) -> bool {
+        self.max_count
+            .is_some_and(|max_count| usage.count > i64::from(max_count))
+            || self
+                .max_amount
+                .is_some_and(|max_amount| usage.amount > max_amount)
+    }
+
+    // 以事务级咨询锁锁定用户已使用的限额，返回当前事务时间，需在事务内调用
+    pub async fn lock_usage(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        user_id: &str,
+    ) -> AppResult<NaiveDateTime> {
+        let now = sqlx::query_scalar!(
+            r#"select
+                localtimestamp as "now!"
+            from
+                pg_advisory_xact_lock(hashtextextended('amazing:limit:' || $1::int || ':' || $2, 0))"#,
+            tenant_id,
+            user_id
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(now)
+    }
+
+    // 以账户操作日志为准统计用户自`since`起已使用的限额
+    // 操作金额取日志中各项变动金额绝对值的最大值，即请求中的操作金额
+    // 未指定账户操作类型时只统计已启用的账户操作类型，手续费等系统产生的日志不计入
+    // 指定账户操作类型时按该类型统计，兑换等未启用类型的限额规则同样生效
+    pub async fn fetch_usage(
+        executor: impl PgExecutor<'_>,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: Option<i32>,
+        action_type_id: Option<i32>,
+        since: NaiveDateTime,
+    ) -> AppResult<LimitUsage> {
+        let usage = sqlx::query_as!(
+            LimitUsage,
+            r#"select
+                count(*) as "count!",
+                coalesce(sum(greatest(
+                    abs(account_log.amount_available_balance),
+                    abs(account_log.amount_frozen_balance),
+                    abs(account_log.amount_total_income),
+                    abs(account_log.amount_total_expense)
+                )), 0) as "amount!"
+            from
+                account_log
+                join account on account.id = account_log.account_id
+                join action_type on action_type.id = account_log.action_type_id
+            where
+                account.tenant_id = $1
+                and account.user_id = $2
+                and ($3::int is null or account.asset_type_id = $3)
+                and (
+                    ($4::int is null and action_type.is_active = true)
+                    or account_log.action_type_id = $4
+                )
+                and account_log.created_at >= $5"#,
+            tenant_id,
+            user_id,
+            asset_type_id,
+            action_type_id,
+            since
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(usage)
+    }
+}
@@ -1,4 +1,16 @@
 pub mod account;
 pub mod account_log;
+pub mod account_log_partition;
+pub mod action_job;
 pub mod action_type;
+pub mod asset_supply;
 pub mod asset_type;
+pub mod balance_lot;
+pub mod change_log;
+pub mod client;
+pub mod exchange_log;
+pub mod exchange_rate;
+pub mod fee_rule;
+pub mod hold;
+pub mod limit_rule;
+pub mod reconciliation;
@@ -0,0 +1,89 @@
+use axum_kit::AppResult;
+use serde::Serialize;
+use sqlx::{types::Decimal, PgExecutor};
+
+// 账户余额与最新一条账户操作日志的操作后余额对账结果
+#[derive(Serialize)]
+pub struct ReconciliationModel {
+    pub account_id: i32,
+    pub tenant_id: i32,
+    pub user_id: String,
+    pub asset_type_id: i32,
+    pub available_balance: Decimal,
+    pub frozen_balance: Decimal,
+    pub total_income: Decimal,
+    pub total_expense: Decimal,
+    pub available_balance_after: Decimal,
+    pub frozen_balance_after: Decimal,
+    pub total_income_after: Decimal,
+    pub total_expense_after: Decimal,
+}
+
+impl ReconciliationModel {
+    // 账户余额与该账户最新一条日志的操作后余额不一致的账户，没有日志时以 0 为准
+    // 日志均已归档时以归档检查点为准
+    // 通常由直接修改数据库中的账户余额导致
+    pub async fn fetch_mismatched(executor: impl PgExecutor<'_>) -> AppResult<Vec<Self>> {
+        let reconciliations = sqlx::query_as!(
+            Self,
+            r#"select
+                a.id as account_id,
+                a.tenant_id,
+                a.user_id,
+                a.asset_type_id,
+                a.available_balance,
+                a.frozen_balance,
+                a.total_income,
+                a.total_expense,
+                coalesce(l.available_balance_after, 0) as "available_balance_after!",
+                coalesce(l.frozen_balance_after, 0) as "frozen_balance_after!",
+                coalesce(l.total_income_after, 0) as "total_income_after!",
+                coalesce(l.total_expense_after, 0) as "total_expense_after!"
+            from
+                account a
+                left join lateral (
+                    select
+                        available_balance_after,
+                        frozen_balance_after,
+                        total_income_after,
+                        total_expense_after
+                    from
+                        (
+                            (
+                                select
+                                    *
+                                from
+                                    account_log
+                                where
+                                    account_id = a.id
+                                order by
+                                    id desc
+                                limit 1
+                            )
+                            union all
+                            (
+                                select
+                                    *
+                                from
+                                    account_log_checkpoint
+                                where
+                                    account_id = a.id
+                            )
+                        ) t
+                    order by
+                        id desc
+                    limit 1
+                ) l on true
+            where
+                a.available_balance <> coalesce(l.available_balance_after, 0)
+                or a.frozen_balance <> coalesce(l.frozen_balance_after, 0)
+                or a.total_income <> coalesce(l.total_income_after, 0)
+                or a.total_expense <> coalesce(l.total_expense_after, 0)
+            order by
+                a.id"#
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(reconciliations)
+    }
+}
@@ -0,0 +1,56 @@
+use crate::model::account::AccountModel;
+use axum_kit::AppResult;
+use sqlx::types::Decimal;
+use std::future::Future;
+
+// 账户的存储
+pub trait AccountRepository: Send + Sync {
+    fn create_account(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> impl Future<Output = AppResult<AccountModel>> + Send;
+
+    fn find_account(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> impl Future<Output = AppResult<AccountModel>> + Send;
+
+    fn find_accounts(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_ids: Vec<i32>,
+    ) -> impl Future<Output = AppResult<Vec<AccountModel>>> + Send;
+}
+
+// 事务内的账户写操作
+pub trait AccountTransaction: Send {
+    fn is_account_active(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> impl Future<Output = bool> + Send;
+
+    // 锁定账户行，之后对该账户的余额及余额批次变更均在同一锁内完成
+    fn lock_account(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> impl Future<Output = AppResult<AccountModel>> + Send;
+
+    // `amounts`依次为可用余额、冻结余额、累计收入、累计支出的变动金额
+    // 需锁定账户行，保证同一账户的余额变更及日志写入串行执行
+    fn update_balance(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+        amounts: [Decimal; 4],
+    ) -> impl Future<Output = AppResult<AccountModel>> + Send;
+}
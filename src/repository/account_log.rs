@@ -0,0 +1,86 @@
+use crate::model::{
+    account::AccountModel,
+    account_log::{AccountLogModel, AccountTransactionLegModel},
+};
+use axum_kit::AppResult;
+use sqlx::types::{chrono::NaiveDateTime, Decimal};
+use std::future::Future;
+
+// 账户操作日志的存储
+pub trait AccountLogRepository: Send + Sync {
+    fn is_account_log_exists(
+        &self,
+        account_id: i32,
+        action_type_id: i32,
+        order_number: &str,
+    ) -> impl Future<Output = bool> + Send;
+
+    // 按`id`倒序分页查询，`before_id`为上一页最后一条记录的`id`
+    fn fetch_account_logs(
+        &self,
+        account_id: i32,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<AccountLogModel>>> + Send;
+
+    // 按`id`正序分页查询，`after_id`为上一页最后一条记录的`id`
+    fn fetch_account_log_chain(
+        &self,
+        account_id: i32,
+        after_id: i64,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<AccountLogModel>>> + Send;
+
+    // 按`id`正序分页查询`[start_at, end_at)`时间范围内的日志
+    fn fetch_account_log_range(
+        &self,
+        account_id: i32,
+        start_at: NaiveDateTime,
+        end_at: NaiveDateTime,
+        after_id: i64,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<AccountLogModel>>> + Send;
+
+    // `at`之前的最后一条日志
+    fn find_last_account_log_before(
+        &self,
+        account_id: i32,
+        at: NaiveDateTime,
+    ) -> impl Future<Output = AppResult<Option<AccountLogModel>>> + Send;
+
+    // 账户已归档的最后一条日志，哈希链从该日志的哈希继续
+    fn find_account_log_checkpoint(
+        &self,
+        account_id: i32,
+    ) -> impl Future<Output = AppResult<Option<AccountLogModel>>> + Send;
+
+    // 哈希链上线前最后一条没有哈希的日志 id
+    fn fetch_account_log_hash_cutover(&self) -> impl Future<Output = AppResult<i64>> + Send;
+
+    // 交易的所有日志按`id`正序排列
+    fn fetch_account_transaction(
+        &self,
+        tenant_id: i32,
+        transaction_id: i64,
+    ) -> impl Future<Output = AppResult<Vec<AccountTransactionLegModel>>> + Send;
+}
+
+// 事务内的账户操作日志写操作
+pub trait AccountLogTransaction: Send {
+    // 本事务的交易id，首次调用时生成，事务内记录的账户操作日志均使用该id
+    fn transaction_id(&mut self) -> impl Future<Output = AppResult<i64>> + Send;
+
+    // 记录账户操作日志，租户及操作后余额取自`account`，`created_at`为空时为当前时间
+    #[allow(clippy::too_many_arguments)]
+    fn create_account_log(
+        &mut self,
+        account: &AccountModel,
+        action_type_id: i32,
+        amounts: [Decimal; 4],
+        order_number: &str,
+        description: &str,
+        client_id: i32,
+        operator: &str,
+        created_at: Option<NaiveDateTime>,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+}
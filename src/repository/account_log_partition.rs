@@ -0,0 +1,18 @@
+use axum_kit::AppResult;
+use sqlx::types::chrono::NaiveDate;
+use std::future::Future;
+
+// 账户操作日志分区的存储
+pub trait AccountLogPartitionRepository: Send + Sync {
+    // 创建`month`所在月的账户操作日志分区，已创建时不做任何操作
+    // 默认分区中该月的日志移入新分区
+    fn create_account_log_partition(
+        &self,
+        month: NaiveDate,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    // 默认分区中日志所在的月份(月初)
+    fn fetch_account_log_default_months(
+        &self,
+    ) -> impl Future<Output = AppResult<Vec<NaiveDate>>> + Send;
+}
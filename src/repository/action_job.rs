@@ -0,0 +1,57 @@
+use crate::model::action_job::{ActionJobItemModel, ActionJobItemStatus, ActionJobModel};
+use axum_kit::AppResult;
+use std::future::Future;
+
+// 批量账户操作任务的存储
+pub trait ActionJobRepository: Send + Sync {
+    fn find_action_job(
+        &self,
+        tenant_id: i32,
+        id: i64,
+    ) -> impl Future<Output = AppResult<ActionJobModel>> + Send;
+
+    // 所有租户中领取一个待处理任务，或超过`lease_seconds`秒未更新的处理中任务
+    fn claim_action_job(
+        &self,
+        lease_seconds: f64,
+    ) -> impl Future<Output = AppResult<Option<ActionJobModel>>> + Send;
+
+    // 按`seq`正序分页查询任务明细，`status`为空表示不限
+    fn fetch_action_job_items(
+        &self,
+        job_id: i64,
+        status: Option<ActionJobItemStatus>,
+        after_seq: i32,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<ActionJobItemModel>>> + Send;
+
+    fn complete_action_job(
+        &self,
+        id: i64,
+    ) -> impl Future<Output = AppResult<ActionJobModel>> + Send;
+}
+
+// 事务内的批量账户操作任务写操作
+pub trait ActionJobTransaction: Send {
+    fn create_action_job(
+        &mut self,
+        tenant_id: i32,
+        chunk_size: i32,
+        total_count: i32,
+        client_id: i32,
+        operator: &str,
+    ) -> impl Future<Output = AppResult<ActionJobModel>> + Send;
+
+    fn create_action_job_items(
+        &mut self,
+        items: &[ActionJobItemModel],
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    // 记录一块账户操作的处理结果并累加任务计数，`failed`为序号及失败原因
+    fn update_action_job_results(
+        &mut self,
+        job_id: i64,
+        succeeded: &[i32],
+        failed: &[(i32, String)],
+    ) -> impl Future<Output = AppResult<()>> + Send;
+}
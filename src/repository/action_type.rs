@@ -0,0 +1,22 @@
+use crate::model::action_type::ActionTypeModel;
+use axum_kit::AppResult;
+use std::future::Future;
+
+// 账户操作类型的存储
+pub trait ActionTypeRepository: Send + Sync {
+    // 所有租户已启用的账户操作类型
+    fn fetch_action_types(&self) -> impl Future<Output = AppResult<Vec<ActionTypeModel>>> + Send;
+
+    // 某租户所有账户操作类型，包括未启用的账户操作类型
+    fn fetch_action_types_including_inactive(
+        &self,
+        tenant_id: i32,
+    ) -> impl Future<Output = AppResult<Vec<ActionTypeModel>>> + Send;
+
+    // 按名称查找账户操作类型，包括未启用的
+    fn find_action_type(
+        &self,
+        tenant_id: i32,
+        name: &str,
+    ) -> impl Future<Output = AppResult<ActionTypeModel>> + Send;
+}
@@ -0,0 +1,34 @@
+use crate::model::{asset_supply::AssetSupplyModel, asset_type::AssetTypeModel};
+use axum_kit::AppResult;
+use sqlx::types::Decimal;
+use std::future::Future;
+
+// 资产类型及其流通量的存储
+pub trait AssetTypeRepository: Send + Sync {
+    // 所有租户已启用的资产类型
+    fn fetch_asset_types(&self) -> impl Future<Output = AppResult<Vec<AssetTypeModel>>> + Send;
+
+    // 某租户已启用资产类型的流通量统计
+    fn fetch_asset_supplies(
+        &self,
+        tenant_id: i32,
+    ) -> impl Future<Output = AppResult<Vec<AssetSupplyModel>>> + Send;
+}
+
+// 事务内的资产类型及其流通量写操作
+pub trait AssetTypeTransaction: Send {
+    // 累加未设置发行上限的资产类型的流通量，提交事务时按资产类型顺序写入
+    fn add_asset_supply(
+        &mut self,
+        asset_type_id: i32,
+        amount: Decimal,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    // 立即累加设置了发行上限的资产类型的流通量，流通量增加且超出发行上限时不累加并返回假
+    // 在保存点内调用时随保存点回滚
+    fn add_capped_asset_supply(
+        &mut self,
+        asset_type_id: i32,
+        amount: Decimal,
+    ) -> impl Future<Output = AppResult<bool>> + Send;
+}
@@ -0,0 +1,39 @@
+use crate::model::{account::AccountModel, balance_lot::BalanceLotModel};
+use axum_kit::AppResult;
+use sqlx::types::{chrono::NaiveDateTime, Decimal};
+use std::future::Future;
+
+// 余额批次的存储
+pub trait BalanceLotRepository: Send + Sync {
+    // 所有租户存在过期余额批次的账户，按 id 正序返回`after_id`之后的账户
+    fn fetch_accounts_with_expired_balance_lots(
+        &self,
+        after_id: i32,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<AccountModel>>> + Send;
+}
+
+// 事务内的余额批次写操作
+pub trait BalanceLotTransaction: Send {
+    // 资产类型按批次跟踪余额时产生新批次，否则不做任何操作
+    // 有效期从`created_at`起算，为空时使用当前时间
+    fn create_balance_lot(
+        &mut self,
+        account: &AccountModel,
+        amount: Decimal,
+        order_number: &str,
+        created_at: Option<NaiveDateTime>,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    // 按先进先出扣减账户余额批次，需在`update_balance`之后调用
+    fn consume_balance_lots(
+        &mut self,
+        account_id: i32,
+        amount: Decimal,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    fn fetch_expired_balance_lots(
+        &mut self,
+        account_id: i32,
+    ) -> impl Future<Output = AppResult<Vec<BalanceLotModel>>> + Send;
+}
@@ -0,0 +1,29 @@
+use crate::model::{
+    action_type::ActionTypeModel, asset_type::AssetTypeModel, client::ClientModel,
+    fee_rule::FeeRuleModel, limit_rule::LimitRuleModel,
+};
+use std::{collections::HashMap, sync::RwLock};
+
+// 已启用的资产类型、账户操作类型、客户端、手续费规则及限额规则，由对应服务加载及查询
+// 各项未加载时为空，查询时返回 503
+#[derive(Default)]
+pub struct ConfigCache {
+    // 按租户分组，下同
+    pub asset_types: RwLock<Option<HashMap<i32, Vec<AssetTypeModel>>>>,
+    pub action_types: RwLock<Option<HashMap<i32, Vec<ActionTypeModel>>>>,
+    pub clients: RwLock<Option<Vec<ClientModel>>>,
+    pub fee_rules: RwLock<Option<HashMap<i32, Vec<FeeRuleModel>>>>,
+    pub limit_rules: RwLock<Option<LimitRules>>,
+}
+
+#[derive(Clone)]
+pub struct LimitRules {
+    pub rules: HashMap<i32, Vec<LimitRuleModel>>,
+    // 是否使用 Redis 缓存已使用的限额
+    pub use_redis: bool,
+}
+
+// 存储持有配置缓存，其开启的事务与存储读取同一份缓存
+pub trait CacheRepository {
+    fn cache(&self) -> &ConfigCache;
+}
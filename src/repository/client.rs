@@ -0,0 +1,9 @@
+use crate::model::client::ClientModel;
+use axum_kit::AppResult;
+use std::future::Future;
+
+// 客户端的存储
+pub trait ClientRepository: Send + Sync {
+    // 已启用的客户端
+    fn fetch_clients(&self) -> impl Future<Output = AppResult<Vec<ClientModel>>> + Send;
+}
@@ -0,0 +1,32 @@
+use crate::model::{exchange_log::ExchangeLogModel, exchange_rate::ExchangeRateModel};
+use axum_kit::AppResult;
+use sqlx::types::Decimal;
+use std::future::Future;
+
+// 汇率及资产兑换日志的存储
+pub trait ExchangeRepository: Send + Sync {
+    // 当前生效的汇率
+    fn find_exchange_rate(
+        &self,
+        tenant_id: i32,
+        from_asset_type_id: i32,
+        to_asset_type_id: i32,
+    ) -> impl Future<Output = AppResult<ExchangeRateModel>> + Send;
+}
+
+// 事务内的汇率及资产兑换日志写操作
+pub trait ExchangeTransaction: Send {
+    // 记录资产兑换日志，租户、资产类型及汇率取自`exchange_rate`
+    #[allow(clippy::too_many_arguments)]
+    fn create_exchange_log(
+        &mut self,
+        user_id: &str,
+        exchange_rate: &ExchangeRateModel,
+        from_amount: Decimal,
+        to_amount: Decimal,
+        order_number: &str,
+        description: &str,
+        client_id: i32,
+        operator: &str,
+    ) -> impl Future<Output = AppResult<ExchangeLogModel>> + Send;
+}
@@ -0,0 +1,9 @@
+use crate::model::fee_rule::FeeRuleModel;
+use axum_kit::AppResult;
+use std::future::Future;
+
+// 手续费规则的存储
+pub trait FeeRuleRepository: Send + Sync {
+    // 所有租户已启用的手续费规则
+    fn fetch_fee_rules(&self) -> impl Future<Output = AppResult<Vec<FeeRuleModel>>> + Send;
+}
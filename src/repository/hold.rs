@@ -0,0 +1,57 @@
+use crate::model::{
+    account::AccountModel,
+    hold::{HoldModel, HoldStatus},
+};
+use axum_kit::AppResult;
+use sqlx::types::Decimal;
+use std::future::Future;
+
+// 预授权的存储
+pub trait HoldRepository: Send + Sync {
+    fn find_hold(
+        &self,
+        tenant_id: i32,
+        reference: &str,
+    ) -> impl Future<Output = AppResult<HoldModel>> + Send;
+
+    // 所有租户已过期但仍处于冻结中的预授权
+    fn fetch_expired_holds(
+        &self,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<HoldModel>>> + Send;
+}
+
+// 事务内的预授权写操作
+pub trait HoldTransaction: Send {
+    // 创建预授权，租户、用户及资产类型取自`account`，`expires_in`秒后过期
+    #[allow(clippy::too_many_arguments)]
+    fn create_hold(
+        &mut self,
+        account: &AccountModel,
+        reference: &str,
+        amount: Decimal,
+        expires_in: i64,
+        description: &str,
+        client_id: i32,
+        operator: &str,
+    ) -> impl Future<Output = AppResult<HoldModel>> + Send;
+
+    // 需锁定预授权行，保证同一预授权的扣款、释放串行执行
+    fn find_hold_for_update(
+        &mut self,
+        tenant_id: i32,
+        reference: &str,
+    ) -> impl Future<Output = AppResult<HoldModel>> + Send;
+
+    // 预授权在当前事务时间是否已过期
+    fn is_hold_expired(&mut self, hold: &HoldModel)
+        -> impl Future<Output = AppResult<bool>> + Send;
+
+    fn update_hold(
+        &mut self,
+        id: i64,
+        captured_amount: Decimal,
+        released_amount: Decimal,
+        status: HoldStatus,
+    ) -> impl Future<Output = AppResult<HoldModel>> + Send;
+}
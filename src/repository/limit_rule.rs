@@ -0,0 +1,30 @@
+use crate::model::limit_rule::{LimitRuleModel, LimitUsage};
+use axum_kit::AppResult;
+use sqlx::types::chrono::NaiveDateTime;
+use std::future::Future;
+
+// 限额规则及已使用的限额的存储
+pub trait LimitRuleRepository: Send + Sync {
+    // 所有租户已启用的限额规则
+    fn fetch_limit_rules(&self) -> impl Future<Output = AppResult<Vec<LimitRuleModel>>> + Send;
+}
+
+// 事务内已使用的限额，检查及累加需在同一事务内加锁后进行
+pub trait LimitRuleTransaction: Send {
+    // 锁定用户已使用的限额直至事务结束，返回当前事务时间
+    fn lock_limit_usage(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+    ) -> impl Future<Output = AppResult<NaiveDateTime>> + Send;
+
+    // 以账户操作日志为准统计用户自`since`起已使用的限额，资产类型、账户操作类型为空表示不限
+    fn fetch_limit_usage(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: Option<i32>,
+        action_type_id: Option<i32>,
+        since: NaiveDateTime,
+    ) -> impl Future<Output = AppResult<LimitUsage>> + Send;
+}
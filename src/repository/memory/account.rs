@@ -0,0 +1,116 @@
+use super::{MemoryRepository, MemoryTransaction};
+use crate::{
+    model::account::AccountModel,
+    repository::account::{AccountRepository, AccountTransaction},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use sqlx::types::{chrono::Utc, Decimal};
+
+impl AccountRepository for MemoryRepository {
+    async fn create_account(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> AppResult<AccountModel> {
+        let mut state = self.state.lock().await;
+        if state
+            .find_account(tenant_id, user_id, asset_type_id)
+            .is_ok()
+        {
+            return Err(Error::Custom(
+                StatusCode::CONFLICT,
+                "账户已存在".to_string(),
+            ));
+        }
+        let now = Utc::now().naive_utc();
+        let account = AccountModel {
+            id: state.accounts.len() as i32 + 1,
+            tenant_id,
+            user_id: user_id.to_string(),
+            asset_type_id,
+            available_balance: Decimal::ZERO,
+            frozen_balance: Decimal::ZERO,
+            total_income: Decimal::ZERO,
+            total_expense: Decimal::ZERO,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        };
+        state.accounts.push(account.clone());
+        Ok(account)
+    }
+
+    async fn find_account(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> AppResult<AccountModel> {
+        let mut state = self.state.lock().await;
+        state
+            .find_account(tenant_id, user_id, asset_type_id)
+            .map(|account| account.clone())
+    }
+
+    async fn find_accounts(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_ids: Vec<i32>,
+    ) -> AppResult<Vec<AccountModel>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .accounts
+            .iter()
+            .filter(|account| {
+                account.tenant_id == tenant_id
+                    && account.user_id == user_id
+                    && asset_type_ids.contains(&account.asset_type_id)
+            })
+            .cloned()
+            .collect())
+    }
+}
+
+impl AccountTransaction for MemoryTransaction {
+    async fn is_account_active(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> bool {
+        self.state
+            .find_account(tenant_id, user_id, asset_type_id)
+            .is_ok_and(|account| account.is_active)
+    }
+
+    async fn lock_account(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> AppResult<AccountModel> {
+        self.state
+            .find_account(tenant_id, user_id, asset_type_id)
+            .map(|account| account.clone())
+    }
+
+    async fn update_balance(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+        amounts: [Decimal; 4],
+    ) -> AppResult<AccountModel> {
+        let account = self.state.find_account(tenant_id, user_id, asset_type_id)?;
+        let [available_balance, frozen_balance, total_income, total_expense] = amounts;
+        account.available_balance += available_balance;
+        account.frozen_balance += frozen_balance;
+        account.total_income += total_income;
+        account.total_expense += total_expense;
+        account.updated_at = Utc::now().naive_utc();
+        Ok(account.clone())
+    }
+}
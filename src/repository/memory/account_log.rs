@@ -0,0 +1,206 @@
+use super::{MemoryRepository, MemoryTransaction};
+use crate::{
+    model::{
+        account::AccountModel,
+        account_log::{AccountLogModel, AccountTransactionLegModel},
+    },
+    repository::account_log::{AccountLogRepository, AccountLogTransaction},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use sqlx::types::{
+    chrono::{NaiveDateTime, Utc},
+    Decimal,
+};
+
+impl AccountLogRepository for MemoryRepository {
+    async fn is_account_log_exists(
+        &self,
+        account_id: i32,
+        action_type_id: i32,
+        order_number: &str,
+    ) -> bool {
+        let state = self.state.lock().await;
+        state.account_logs.iter().any(|account_log| {
+            account_log.account_id == account_id
+                && account_log.action_type_id == action_type_id
+                && account_log.order_number == order_number
+        })
+    }
+
+    async fn fetch_account_logs(
+        &self,
+        account_id: i32,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> AppResult<Vec<AccountLogModel>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .account_logs
+            .iter()
+            .rev()
+            .filter(|account_log| account_log.account_id == account_id)
+            .filter(|account_log| match before_id {
+                Some(before_id) => account_log.id < before_id,
+                None => true,
+            })
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn fetch_account_log_chain(
+        &self,
+        account_id: i32,
+        after_id: i64,
+        limit: i64,
+    ) -> AppResult<Vec<AccountLogModel>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .account_logs
+            .iter()
+            .filter(|account_log| account_log.account_id == account_id && account_log.id > after_id)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn fetch_account_log_range(
+        &self,
+        account_id: i32,
+        start_at: NaiveDateTime,
+        end_at: NaiveDateTime,
+        after_id: i64,
+        limit: i64,
+    ) -> AppResult<Vec<AccountLogModel>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .account_logs
+            .iter()
+            .filter(|account_log| {
+                account_log.account_id == account_id
+                    && account_log.created_at >= start_at
+                    && account_log.created_at < end_at
+                    && account_log.id > after_id
+            })
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn find_last_account_log_before(
+        &self,
+        account_id: i32,
+        at: NaiveDateTime,
+    ) -> AppResult<Option<AccountLogModel>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .account_logs
+            .iter()
+            .filter(|account_log| {
+                account_log.account_id == account_id && account_log.created_at < at
+            })
+            .max_by_key(|account_log| (account_log.created_at, account_log.id))
+            .cloned())
+    }
+
+    // 内存存储不分区，日志不会归档
+    async fn find_account_log_checkpoint(
+        &self,
+        _account_id: i32,
+    ) -> AppResult<Option<AccountLogModel>> {
+        Ok(None)
+    }
+
+    // 内存中的日志均有哈希
+    async fn fetch_account_log_hash_cutover(&self) -> AppResult<i64> {
+        Ok(0)
+    }
+
+    async fn fetch_account_transaction(
+        &self,
+        tenant_id: i32,
+        transaction_id: i64,
+    ) -> AppResult<Vec<AccountTransactionLegModel>> {
+        let state = self.state.lock().await;
+        let mut legs = Vec::new();
+        for account_log in state.account_logs.iter().filter(|account_log| {
+            account_log.tenant_id == tenant_id && account_log.transaction_id == Some(transaction_id)
+        }) {
+            let account = state
+                .accounts
+                .iter()
+                .find(|account| account.id == account_log.account_id)
+                .ok_or(sqlx::Error::RowNotFound)?;
+            legs.push(AccountTransactionLegModel {
+                user_id: account.user_id.clone(),
+                asset_type_id: account.asset_type_id,
+                account_log: account_log.clone(),
+            });
+        }
+        Ok(legs)
+    }
+}
+
+impl AccountLogTransaction for MemoryTransaction {
+    async fn transaction_id(&mut self) -> AppResult<i64> {
+        Ok(self.transaction_id)
+    }
+
+    async fn create_account_log(
+        &mut self,
+        account: &AccountModel,
+        action_type_id: i32,
+        amounts: [Decimal; 4],
+        order_number: &str,
+        description: &str,
+        client_id: i32,
+        operator: &str,
+        created_at: Option<NaiveDateTime>,
+    ) -> AppResult<()> {
+        let account_logs = &mut self.state.account_logs;
+        // 与`account_log`表的唯一约束保持一致
+        if account_logs.iter().any(|account_log| {
+            account_log.account_id == account.id
+                && account_log.action_type_id == action_type_id
+                && account_log.order_number == order_number
+        }) {
+            return Err(Error::Custom(
+                StatusCode::CONFLICT,
+                "操作失败，存在已处理的订单".to_string(),
+            ));
+        }
+        let prev_hash = account_logs
+            .iter()
+            .rev()
+            .find(|account_log| account_log.account_id == account.id)
+            .map(|account_log| account_log.hash.clone())
+            .unwrap_or_default();
+        let [amount_available_balance, amount_frozen_balance, amount_total_income, amount_total_expense] =
+            amounts;
+        let mut account_log = AccountLogModel {
+            id: account_logs.len() as i64 + 1,
+            tenant_id: account.tenant_id,
+            account_id: account.id,
+            action_type_id,
+            amount_available_balance,
+            amount_frozen_balance,
+            amount_total_income,
+            amount_total_expense,
+            available_balance_after: account.available_balance,
+            frozen_balance_after: account.frozen_balance,
+            total_income_after: account.total_income,
+            total_expense_after: account.total_expense,
+            order_number: order_number.to_string(),
+            description: description.to_string(),
+            client_id,
+            operator: operator.to_string(),
+            hash: String::new(),
+            created_at: created_at.unwrap_or_else(|| Utc::now().naive_utc()),
+            transaction_id: Some(self.transaction_id),
+        };
+        account_log.hash = account_log.chain_hash(&prev_hash);
+        account_logs.push(account_log);
+        Ok(())
+    }
+}
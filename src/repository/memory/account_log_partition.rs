@@ -0,0 +1,14 @@
+use super::MemoryRepository;
+use crate::repository::account_log_partition::AccountLogPartitionRepository;
+use axum_kit::AppResult;
+use sqlx::types::chrono::NaiveDate;
+
+impl AccountLogPartitionRepository for MemoryRepository {
+    async fn create_account_log_partition(&self, _month: NaiveDate) -> AppResult<()> {
+        Ok(())
+    }
+
+    async fn fetch_account_log_default_months(&self) -> AppResult<Vec<NaiveDate>> {
+        Ok(Vec::new())
+    }
+}
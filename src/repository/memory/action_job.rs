@@ -0,0 +1,129 @@
+use super::{MemoryRepository, MemoryTransaction};
+use crate::{
+    model::action_job::{ActionJobItemModel, ActionJobItemStatus, ActionJobModel, ActionJobStatus},
+    repository::action_job::{ActionJobRepository, ActionJobTransaction},
+};
+use axum_kit::AppResult;
+use chrono::Duration;
+use sqlx::types::chrono::Utc;
+
+impl ActionJobRepository for MemoryRepository {
+    async fn find_action_job(&self, tenant_id: i32, id: i64) -> AppResult<ActionJobModel> {
+        let mut state = self.state.lock().await;
+        state
+            .find_action_job(id)
+            .map(|action_job| action_job.clone())
+            .and_then(|action_job| {
+                (action_job.tenant_id == tenant_id)
+                    .then_some(action_job)
+                    .ok_or_else(|| sqlx::Error::RowNotFound.into())
+            })
+    }
+
+    async fn claim_action_job(&self, lease_seconds: f64) -> AppResult<Option<ActionJobModel>> {
+        let mut state = self.state.lock().await;
+        let now = Utc::now().naive_utc();
+        let stale_before = now - Duration::milliseconds((lease_seconds * 1000.0) as i64);
+        let Some(action_job) = state.action_jobs.iter_mut().find(|action_job| {
+            action_job.status == ActionJobStatus::Pending
+                || (action_job.status == ActionJobStatus::Running
+                    && action_job.updated_at < stale_before)
+        }) else {
+            return Ok(None);
+        };
+        action_job.status = ActionJobStatus::Running;
+        action_job.updated_at = now;
+        Ok(Some(action_job.clone()))
+    }
+
+    async fn fetch_action_job_items(
+        &self,
+        job_id: i64,
+        status: Option<ActionJobItemStatus>,
+        after_seq: i32,
+        limit: i64,
+    ) -> AppResult<Vec<ActionJobItemModel>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .action_job_items
+            .iter()
+            .filter(|item| {
+                item.job_id == job_id
+                    && (status.is_none() || status == Some(item.status))
+                    && item.seq > after_seq
+            })
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn complete_action_job(&self, id: i64) -> AppResult<ActionJobModel> {
+        let mut state = self.state.lock().await;
+        let action_job = state.find_action_job(id)?;
+        let now = Utc::now().naive_utc();
+        action_job.status = ActionJobStatus::Completed;
+        action_job.updated_at = now;
+        action_job.finished_at = Some(now);
+        Ok(action_job.clone())
+    }
+}
+
+impl ActionJobTransaction for MemoryTransaction {
+    async fn create_action_job(
+        &mut self,
+        tenant_id: i32,
+        chunk_size: i32,
+        total_count: i32,
+        client_id: i32,
+        operator: &str,
+    ) -> AppResult<ActionJobModel> {
+        let now = Utc::now().naive_utc();
+        let action_job = ActionJobModel {
+            id: self.state.action_jobs.len() as i64 + 1,
+            tenant_id,
+            status: ActionJobStatus::Pending,
+            chunk_size,
+            total_count,
+            succeeded_count: 0,
+            failed_count: 0,
+            client_id,
+            operator: operator.to_string(),
+            created_at: now,
+            updated_at: now,
+            finished_at: None,
+        };
+        self.state.action_jobs.push(action_job.clone());
+        Ok(action_job)
+    }
+
+    async fn create_action_job_items(&mut self, items: &[ActionJobItemModel]) -> AppResult<()> {
+        self.state.action_job_items.extend_from_slice(items);
+        Ok(())
+    }
+
+    async fn update_action_job_results(
+        &mut self,
+        job_id: i64,
+        succeeded: &[i32],
+        failed: &[(i32, String)],
+    ) -> AppResult<()> {
+        for item in self
+            .state
+            .action_job_items
+            .iter_mut()
+            .filter(|item| item.job_id == job_id)
+        {
+            if succeeded.contains(&item.seq) {
+                item.status = ActionJobItemStatus::Succeeded;
+            } else if let Some((_, message)) = failed.iter().find(|(seq, _)| *seq == item.seq) {
+                item.status = ActionJobItemStatus::Failed;
+                item.message = message.clone();
+            }
+        }
+        let action_job = self.state.find_action_job(job_id)?;
+        action_job.succeeded_count += succeeded.len() as i32;
+        action_job.failed_count += failed.len() as i32;
+        action_job.updated_at = Utc::now().naive_utc();
+        Ok(())
+    }
+}
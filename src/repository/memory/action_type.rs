@@ -0,0 +1,40 @@
+use super::MemoryRepository;
+use crate::{model::action_type::ActionTypeModel, repository::action_type::ActionTypeRepository};
+use axum_kit::AppResult;
+
+impl ActionTypeRepository for MemoryRepository {
+    async fn fetch_action_types(&self) -> AppResult<Vec<ActionTypeModel>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .action_types
+            .iter()
+            .filter(|action_type| action_type.is_active)
+            .cloned()
+            .collect())
+    }
+
+    async fn fetch_action_types_including_inactive(
+        &self,
+        tenant_id: i32,
+    ) -> AppResult<Vec<ActionTypeModel>> {
+        let state = self.state.lock().await;
+        let mut action_types: Vec<ActionTypeModel> = state
+            .action_types
+            .iter()
+            .filter(|action_type| action_type.tenant_id == tenant_id)
+            .cloned()
+            .collect();
+        action_types.sort_by_key(|action_type| action_type.id);
+        Ok(action_types)
+    }
+
+    async fn find_action_type(&self, tenant_id: i32, name: &str) -> AppResult<ActionTypeModel> {
+        let state = self.state.lock().await;
+        state
+            .action_types
+            .iter()
+            .find(|action_type| action_type.tenant_id == tenant_id && action_type.name == name)
+            .cloned()
+            .ok_or_else(|| sqlx::Error::RowNotFound.into())
+    }
+}
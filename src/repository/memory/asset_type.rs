@@ -0,0 +1,59 @@
+use super::{MemoryRepository, MemoryTransaction};
+use crate::{
+    model::{asset_supply::AssetSupplyModel, asset_type::AssetTypeModel},
+    repository::asset_type::{AssetTypeRepository, AssetTypeTransaction},
+};
+use axum_kit::AppResult;
+use sqlx::types::Decimal;
+
+impl AssetTypeRepository for MemoryRepository {
+    async fn fetch_asset_types(&self) -> AppResult<Vec<AssetTypeModel>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .asset_types
+            .iter()
+            .filter(|asset_type| asset_type.is_active)
+            .cloned()
+            .collect())
+    }
+
+    async fn fetch_asset_supplies(&self, tenant_id: i32) -> AppResult<Vec<AssetSupplyModel>> {
+        let state = self.state.lock().await;
+        let mut asset_types: Vec<&AssetTypeModel> = state
+            .asset_types
+            .iter()
+            .filter(|asset_type| asset_type.tenant_id == tenant_id && asset_type.is_active)
+            .collect();
+        asset_types.sort_by_key(|asset_type| asset_type.id);
+        Ok(asset_types
+            .into_iter()
+            .map(|asset_type| state.asset_supply(asset_type))
+            .collect())
+    }
+}
+
+// 内存存储的流通量按账户汇总，已包括本事务内的余额变更
+impl AssetTypeTransaction for MemoryTransaction {
+    async fn add_asset_supply(&mut self, _asset_type_id: i32, _amount: Decimal) -> AppResult<()> {
+        Ok(())
+    }
+
+    async fn add_capped_asset_supply(
+        &mut self,
+        asset_type_id: i32,
+        amount: Decimal,
+    ) -> AppResult<bool> {
+        let asset_type = self
+            .state
+            .asset_types
+            .iter()
+            .find(|asset_type| asset_type.id == asset_type_id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        let asset_supply = self.state.asset_supply(asset_type);
+        let supply = asset_supply.available_balance + asset_supply.frozen_balance;
+        Ok(match asset_type.max_supply {
+            Some(max_supply) => amount <= Decimal::ZERO || supply <= max_supply,
+            None => true,
+        })
+    }
+}
@@ -0,0 +1,115 @@
+use super::{MemoryRepository, MemoryTransaction};
+use crate::{
+    model::{account::AccountModel, balance_lot::BalanceLotModel},
+    repository::balance_lot::{BalanceLotRepository, BalanceLotTransaction},
+};
+use axum_kit::AppResult;
+use chrono::Duration;
+use sqlx::types::{
+    chrono::{NaiveDateTime, Utc},
+    Decimal,
+};
+
+impl BalanceLotRepository for MemoryRepository {
+    async fn fetch_accounts_with_expired_balance_lots(
+        &self,
+        after_id: i32,
+        limit: i64,
+    ) -> AppResult<Vec<AccountModel>> {
+        let state = self.state.lock().await;
+        let now = Utc::now().naive_utc();
+        let mut accounts: Vec<AccountModel> = state
+            .accounts
+            .iter()
+            .filter(|account| {
+                account.id > after_id
+                    && account.available_balance > Decimal::ZERO
+                    && state.balance_lots.iter().any(|lot| {
+                        lot.account_id == account.id
+                            && lot.remaining_amount > Decimal::ZERO
+                            && lot.expires_at <= now
+                    })
+            })
+            .cloned()
+            .collect();
+        accounts.sort_by_key(|account| account.id);
+        accounts.truncate(limit as usize);
+        Ok(accounts)
+    }
+}
+
+impl BalanceLotTransaction for MemoryTransaction {
+    async fn create_balance_lot(
+        &mut self,
+        account: &AccountModel,
+        amount: Decimal,
+        order_number: &str,
+        created_at: Option<NaiveDateTime>,
+    ) -> AppResult<()> {
+        let Some(lot_ttl_days) = self
+            .state
+            .asset_types
+            .iter()
+            .find(|asset_type| asset_type.id == account.asset_type_id)
+            .and_then(|asset_type| asset_type.lot_ttl_days)
+        else {
+            return Ok(());
+        };
+        let now = Utc::now().naive_utc();
+        let created_at = created_at.unwrap_or(now);
+        let id = self.state.balance_lots.len() as i64 + 1;
+        self.state.balance_lots.push(BalanceLotModel {
+            id,
+            tenant_id: account.tenant_id,
+            account_id: account.id,
+            amount,
+            remaining_amount: amount,
+            expires_at: created_at + Duration::days(lot_ttl_days.into()),
+            order_number: order_number.to_string(),
+            created_at,
+            updated_at: now,
+        });
+        Ok(())
+    }
+
+    async fn consume_balance_lots(&mut self, account_id: i32, amount: Decimal) -> AppResult<()> {
+        let mut lots: Vec<&mut BalanceLotModel> = self
+            .state
+            .balance_lots
+            .iter_mut()
+            .filter(|lot| lot.account_id == account_id && lot.remaining_amount > Decimal::ZERO)
+            .collect();
+        lots.sort_by_key(|lot| (lot.expires_at, lot.id));
+        let mut amount = amount;
+        for lot in lots {
+            if amount.is_zero() {
+                break;
+            }
+            let consumed = amount.min(lot.remaining_amount);
+            lot.remaining_amount -= consumed;
+            lot.updated_at = Utc::now().naive_utc();
+            amount -= consumed;
+        }
+        Ok(())
+    }
+
+    async fn fetch_expired_balance_lots(
+        &mut self,
+        account_id: i32,
+    ) -> AppResult<Vec<BalanceLotModel>> {
+        let now = Utc::now().naive_utc();
+        let mut lots: Vec<BalanceLotModel> = self
+            .state
+            .balance_lots
+            .iter()
+            .filter(|lot| {
+                lot.account_id == account_id
+                    && lot.remaining_amount > Decimal::ZERO
+                    && lot.expires_at <= now
+            })
+            .cloned()
+            .collect();
+        lots.sort_by_key(|lot| (lot.expires_at, lot.id));
+        Ok(lots)
+    }
+}
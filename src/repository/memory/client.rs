@@ -0,0 +1,15 @@
+use super::MemoryRepository;
+use crate::{model::client::ClientModel, repository::client::ClientRepository};
+use axum_kit::AppResult;
+
+impl ClientRepository for MemoryRepository {
+    async fn fetch_clients(&self) -> AppResult<Vec<ClientModel>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .clients
+            .iter()
+            .filter(|client| client.is_active)
+            .cloned()
+            .collect())
+    }
+}
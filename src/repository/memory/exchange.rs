@@ -0,0 +1,78 @@
+use super::{MemoryRepository, MemoryTransaction};
+use crate::{
+    model::{exchange_log::ExchangeLogModel, exchange_rate::ExchangeRateModel},
+    repository::exchange::{ExchangeRepository, ExchangeTransaction},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use sqlx::types::{chrono::Utc, Decimal};
+
+impl ExchangeRepository for MemoryRepository {
+    async fn find_exchange_rate(
+        &self,
+        tenant_id: i32,
+        from_asset_type_id: i32,
+        to_asset_type_id: i32,
+    ) -> AppResult<ExchangeRateModel> {
+        let state = self.state.lock().await;
+        let now = Utc::now().naive_utc();
+        state
+            .exchange_rates
+            .iter()
+            .filter(|exchange_rate| {
+                exchange_rate.tenant_id == tenant_id
+                    && exchange_rate.from_asset_type_id == from_asset_type_id
+                    && exchange_rate.to_asset_type_id == to_asset_type_id
+                    && exchange_rate.is_active
+                    && exchange_rate.effective_at <= now
+            })
+            .max_by_key(|exchange_rate| exchange_rate.effective_at)
+            .cloned()
+            .ok_or_else(|| sqlx::Error::RowNotFound.into())
+    }
+}
+
+impl ExchangeTransaction for MemoryTransaction {
+    async fn create_exchange_log(
+        &mut self,
+        user_id: &str,
+        exchange_rate: &ExchangeRateModel,
+        from_amount: Decimal,
+        to_amount: Decimal,
+        order_number: &str,
+        description: &str,
+        client_id: i32,
+        operator: &str,
+    ) -> AppResult<ExchangeLogModel> {
+        let exchange_logs = &mut self.state.exchange_logs;
+        // 与`exchange_log`表的唯一约束保持一致
+        if exchange_logs.iter().any(|exchange_log| {
+            exchange_log.tenant_id == exchange_rate.tenant_id
+                && exchange_log.order_number == order_number
+        }) {
+            return Err(Error::Custom(
+                StatusCode::CONFLICT,
+                "操作失败，存在已处理的订单".to_string(),
+            ));
+        }
+        let exchange_log = ExchangeLogModel {
+            id: exchange_logs.len() as i64 + 1,
+            tenant_id: exchange_rate.tenant_id,
+            user_id: user_id.to_string(),
+            exchange_rate_id: exchange_rate.id,
+            from_asset_type_id: exchange_rate.from_asset_type_id,
+            to_asset_type_id: exchange_rate.to_asset_type_id,
+            rate: exchange_rate.rate,
+            spread: exchange_rate.spread,
+            from_amount,
+            to_amount,
+            order_number: order_number.to_string(),
+            description: description.to_string(),
+            client_id,
+            operator: operator.to_string(),
+            created_at: Utc::now().naive_utc(),
+        };
+        exchange_logs.push(exchange_log.clone());
+        Ok(exchange_log)
+    }
+}
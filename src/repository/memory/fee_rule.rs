@@ -0,0 +1,15 @@
+use super::MemoryRepository;
+use crate::{model::fee_rule::FeeRuleModel, repository::fee_rule::FeeRuleRepository};
+use axum_kit::AppResult;
+
+impl FeeRuleRepository for MemoryRepository {
+    async fn fetch_fee_rules(&self) -> AppResult<Vec<FeeRuleModel>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .fee_rules
+            .iter()
+            .filter(|fee_rule| fee_rule.is_active)
+            .cloned()
+            .collect())
+    }
+}
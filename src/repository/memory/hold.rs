@@ -0,0 +1,111 @@
+use super::{MemoryRepository, MemoryTransaction};
+use crate::{
+    model::{
+        account::AccountModel,
+        hold::{HoldModel, HoldStatus},
+    },
+    repository::hold::{HoldRepository, HoldTransaction},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use chrono::{Duration, Utc};
+use sqlx::types::Decimal;
+
+impl HoldRepository for MemoryRepository {
+    async fn find_hold(&self, tenant_id: i32, reference: &str) -> AppResult<HoldModel> {
+        let mut state = self.state.lock().await;
+        state
+            .find_hold(tenant_id, reference)
+            .map(|hold| hold.clone())
+    }
+
+    async fn fetch_expired_holds(&self, limit: i64) -> AppResult<Vec<HoldModel>> {
+        let state = self.state.lock().await;
+        let now = Utc::now().naive_utc();
+        let mut holds: Vec<HoldModel> = state
+            .holds
+            .iter()
+            .filter(|hold| hold.status == HoldStatus::Held && hold.expires_at <= now)
+            .cloned()
+            .collect();
+        holds.sort_by_key(|hold| hold.expires_at);
+        holds.truncate(limit as usize);
+        Ok(holds)
+    }
+}
+
+impl HoldTransaction for MemoryTransaction {
+    async fn create_hold(
+        &mut self,
+        account: &AccountModel,
+        reference: &str,
+        amount: Decimal,
+        expires_in: i64,
+        description: &str,
+        client_id: i32,
+        operator: &str,
+    ) -> AppResult<HoldModel> {
+        // 与`hold`表的唯一约束保持一致
+        if self.state.find_hold(account.tenant_id, reference).is_ok() {
+            return Err(Error::Custom(
+                StatusCode::CONFLICT,
+                "预授权已存在".to_string(),
+            ));
+        }
+        let now = Utc::now().naive_utc();
+        let hold = HoldModel {
+            id: self.state.holds.len() as i64 + 1,
+            tenant_id: account.tenant_id,
+            account_id: account.id,
+            user_id: account.user_id.clone(),
+            asset_type_id: account.asset_type_id,
+            reference: reference.to_string(),
+            amount,
+            captured_amount: Decimal::ZERO,
+            released_amount: Decimal::ZERO,
+            status: HoldStatus::Held,
+            expires_at: now + Duration::seconds(expires_in),
+            description: description.to_string(),
+            client_id,
+            operator: operator.to_string(),
+            created_at: now,
+            updated_at: now,
+        };
+        self.state.holds.push(hold.clone());
+        Ok(hold)
+    }
+
+    async fn find_hold_for_update(
+        &mut self,
+        tenant_id: i32,
+        reference: &str,
+    ) -> AppResult<HoldModel> {
+        self.state
+            .find_hold(tenant_id, reference)
+            .map(|hold| hold.clone())
+    }
+
+    async fn is_hold_expired(&mut self, hold: &HoldModel) -> AppResult<bool> {
+        Ok(hold.expires_at <= Utc::now().naive_utc())
+    }
+
+    async fn update_hold(
+        &mut self,
+        id: i64,
+        captured_amount: Decimal,
+        released_amount: Decimal,
+        status: HoldStatus,
+    ) -> AppResult<HoldModel> {
+        let hold = self
+            .state
+            .holds
+            .iter_mut()
+            .find(|hold| hold.id == id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        hold.captured_amount = captured_amount;
+        hold.released_amount = released_amount;
+        hold.status = status;
+        hold.updated_at = Utc::now().naive_utc();
+        Ok(hold.clone())
+    }
+}
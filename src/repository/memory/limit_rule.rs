@@ -0,0 +1,81 @@
+use super::{MemoryRepository, MemoryTransaction};
+use crate::{
+    model::limit_rule::{LimitRuleModel, LimitUsage},
+    repository::limit_rule::{LimitRuleRepository, LimitRuleTransaction},
+};
+use axum_kit::AppResult;
+use sqlx::types::chrono::{NaiveDateTime, Utc};
+
+impl LimitRuleRepository for MemoryRepository {
+    async fn fetch_limit_rules(&self) -> AppResult<Vec<LimitRuleModel>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .limit_rules
+            .iter()
+            .filter(|limit_rule| limit_rule.is_active)
+            .cloned()
+            .collect())
+    }
+}
+
+// 内存事务持有整个存储的锁，事务之间本已串行执行
+impl LimitRuleTransaction for MemoryTransaction {
+    async fn lock_limit_usage(
+        &mut self,
+        _tenant_id: i32,
+        _user_id: &str,
+    ) -> AppResult<NaiveDateTime> {
+        Ok(Utc::now().naive_utc())
+    }
+
+    async fn fetch_limit_usage(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: Option<i32>,
+        action_type_id: Option<i32>,
+        since: NaiveDateTime,
+    ) -> AppResult<LimitUsage> {
+        let state = &self.state;
+        let mut usage = LimitUsage::default();
+        for account_log in &state.account_logs {
+            let Some(account) = state
+                .accounts
+                .iter()
+                .find(|account| account.id == account_log.account_id)
+            else {
+                continue;
+            };
+            let asset_type_matches = match asset_type_id {
+                Some(id) => id == account.asset_type_id,
+                None => true,
+            };
+            // 与`LimitRuleModel::fetch_usage`一致，未指定账户操作类型时只统计已启用的类型
+            let action_type_matches = match action_type_id {
+                Some(id) => id == account_log.action_type_id,
+                None => state.action_types.iter().any(|action_type| {
+                    action_type.id == account_log.action_type_id && action_type.is_active
+                }),
+            };
+            if account.tenant_id == tenant_id
+                && account.user_id == user_id
+                && asset_type_matches
+                && action_type_matches
+                && account_log.created_at >= since
+            {
+                usage.count += 1;
+                usage.amount += [
+                    account_log.amount_available_balance,
+                    account_log.amount_frozen_balance,
+                    account_log.amount_total_income,
+                    account_log.amount_total_expense,
+                ]
+                .map(|amount| amount.abs())
+                .into_iter()
+                .max()
+                .unwrap_or_default();
+            }
+        }
+        Ok(usage)
+    }
+}
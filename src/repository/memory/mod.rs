@@ -0,0 +1,231 @@
+mod account;
+mod account_log;
+mod account_log_partition;
+mod action_job;
+mod action_type;
+mod asset_type;
+mod balance_lot;
+mod client;
+mod exchange;
+mod fee_rule;
+mod hold;
+mod limit_rule;
+
+use super::{
+    cache::{CacheRepository, ConfigCache},
+    TransactionControl, TransactionalRepository,
+};
+use crate::model::{
+    account::AccountModel,
+    account_log::AccountLogModel,
+    action_job::{ActionJobItemModel, ActionJobModel},
+    action_type::ActionTypeModel,
+    asset_supply::AssetSupplyModel,
+    asset_type::AssetTypeModel,
+    balance_lot::BalanceLotModel,
+    client::ClientModel,
+    exchange_log::ExchangeLogModel,
+    exchange_rate::ExchangeRateModel,
+    fee_rule::FeeRuleModel,
+    hold::HoldModel,
+    limit_rule::LimitRuleModel,
+};
+use axum_kit::AppResult;
+use sqlx::types::Decimal;
+use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+#[derive(Clone, Default)]
+struct MemoryState {
+    asset_types: Vec<AssetTypeModel>,
+    action_types: Vec<ActionTypeModel>,
+    clients: Vec<ClientModel>,
+    accounts: Vec<AccountModel>,
+    account_logs: Vec<AccountLogModel>,
+    exchange_rates: Vec<ExchangeRateModel>,
+    exchange_logs: Vec<ExchangeLogModel>,
+    holds: Vec<HoldModel>,
+    balance_lots: Vec<BalanceLotModel>,
+    limit_rules: Vec<LimitRuleModel>,
+    fee_rules: Vec<FeeRuleModel>,
+    action_jobs: Vec<ActionJobModel>,
+    action_job_items: Vec<ActionJobItemModel>,
+    last_transaction_id: i64,
+}
+
+impl MemoryState {
+    fn find_account(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> AppResult<&mut AccountModel> {
+        self.accounts
+            .iter_mut()
+            .find(|account| {
+                account.tenant_id == tenant_id
+                    && account.user_id == user_id
+                    && account.asset_type_id == asset_type_id
+            })
+            .ok_or_else(|| sqlx::Error::RowNotFound.into())
+    }
+
+    // 与`AssetSupplyModel`一致，不包括发行、销毁系统账户
+    fn asset_supply(&self, asset_type: &AssetTypeModel) -> AssetSupplyModel {
+        let mut asset_supply = AssetSupplyModel {
+            asset_type_id: asset_type.id,
+            available_balance: Decimal::ZERO,
+            frozen_balance: Decimal::ZERO,
+            total_income: Decimal::ZERO,
+            total_expense: Decimal::ZERO,
+            max_supply: asset_type.max_supply,
+        };
+        for account in self.accounts.iter().filter(|account| {
+            account.asset_type_id == asset_type.id
+                && asset_type.issuance_user_id.as_ref() != Some(&account.user_id)
+                && asset_type.burn_user_id.as_ref() != Some(&account.user_id)
+        }) {
+            asset_supply.available_balance += account.available_balance;
+            asset_supply.frozen_balance += account.frozen_balance;
+            asset_supply.total_income += account.total_income;
+            asset_supply.total_expense += account.total_expense;
+        }
+        asset_supply
+    }
+
+    fn find_hold(&mut self, tenant_id: i32, reference: &str) -> AppResult<&mut HoldModel> {
+        self.holds
+            .iter_mut()
+            .find(|hold| hold.tenant_id == tenant_id && hold.reference == reference)
+            .ok_or_else(|| sqlx::Error::RowNotFound.into())
+    }
+
+    fn find_action_job(&mut self, id: i64) -> AppResult<&mut ActionJobModel> {
+        self.action_jobs
+            .iter_mut()
+            .find(|action_job| action_job.id == id)
+            .ok_or_else(|| sqlx::Error::RowNotFound.into())
+    }
+}
+
+// 内存存储，行为与`PgRepository`保持一致
+// 事务持有整个存储的锁，事务之间串行执行；事务未提交前，不能在同一任务内再通过存储读写
+#[derive(Clone, Default)]
+pub struct MemoryRepository {
+    state: Arc<Mutex<MemoryState>>,
+    cache: Arc<ConfigCache>,
+}
+
+impl MemoryRepository {
+    pub fn new(asset_types: Vec<AssetTypeModel>, action_types: Vec<ActionTypeModel>) -> Self {
+        let state = MemoryState {
+            asset_types,
+            action_types,
+            ..Default::default()
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            cache: Arc::default(),
+        }
+    }
+
+    // 直接设置账户状态及余额，用于模拟管理员直接修改数据库
+    pub async fn set_account(&self, account: AccountModel) {
+        let mut state = self.state.lock().await;
+        state.accounts.retain(|existing| existing.id != account.id);
+        state.accounts.push(account);
+    }
+
+    pub async fn set_client(&self, client: ClientModel) {
+        let mut state = self.state.lock().await;
+        state.clients.retain(|existing| existing.id != client.id);
+        state.clients.push(client);
+    }
+
+    pub async fn set_fee_rule(&self, fee_rule: FeeRuleModel) {
+        let mut state = self.state.lock().await;
+        state
+            .fee_rules
+            .retain(|existing| existing.id != fee_rule.id);
+        state.fee_rules.push(fee_rule);
+    }
+
+    pub async fn set_limit_rule(&self, limit_rule: LimitRuleModel) {
+        let mut state = self.state.lock().await;
+        state
+            .limit_rules
+            .retain(|existing| existing.id != limit_rule.id);
+        state.limit_rules.push(limit_rule);
+    }
+
+    pub async fn set_exchange_rate(&self, exchange_rate: ExchangeRateModel) {
+        let mut state = self.state.lock().await;
+        state
+            .exchange_rates
+            .retain(|existing| existing.id != exchange_rate.id);
+        state.exchange_rates.push(exchange_rate);
+    }
+}
+
+pub struct MemoryTransaction {
+    guard: OwnedMutexGuard<MemoryState>,
+    cache: Arc<ConfigCache>,
+    state: MemoryState,
+    savepoint: Option<MemoryState>,
+    transaction_id: i64,
+}
+
+impl TransactionalRepository for MemoryRepository {
+    type Transaction = MemoryTransaction;
+
+    async fn begin(&self) -> AppResult<MemoryTransaction> {
+        let mut guard = self.state.clone().lock_owned().await;
+        // 与序列相同，事务回滚后交易id也不会重复使用
+        guard.last_transaction_id += 1;
+        let transaction_id = guard.last_transaction_id;
+        let state = guard.clone();
+        Ok(MemoryTransaction {
+            guard,
+            cache: self.cache.clone(),
+            state,
+            savepoint: None,
+            transaction_id,
+        })
+    }
+}
+
+impl CacheRepository for MemoryRepository {
+    fn cache(&self) -> &ConfigCache {
+        &self.cache
+    }
+}
+
+impl CacheRepository for MemoryTransaction {
+    fn cache(&self) -> &ConfigCache {
+        &self.cache
+    }
+}
+
+impl TransactionControl for MemoryTransaction {
+    async fn savepoint(&mut self) -> AppResult<()> {
+        self.savepoint = Some(self.state.clone());
+        Ok(())
+    }
+
+    async fn release_savepoint(&mut self) -> AppResult<()> {
+        self.savepoint = None;
+        Ok(())
+    }
+
+    async fn rollback_to_savepoint(&mut self) -> AppResult<()> {
+        if let Some(state) = self.savepoint.take() {
+            self.state = state;
+        }
+        Ok(())
+    }
+
+    async fn commit(mut self) -> AppResult<()> {
+        *self.guard = self.state;
+        Ok(())
+    }
+}
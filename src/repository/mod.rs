@@ -0,0 +1,121 @@
+pub mod account;
+pub mod account_log;
+pub mod account_log_partition;
+pub mod action_job;
+pub mod action_type;
+pub mod asset_type;
+pub mod balance_lot;
+pub mod cache;
+pub mod client;
+pub mod exchange;
+pub mod fee_rule;
+pub mod hold;
+pub mod limit_rule;
+pub mod memory;
+pub mod postgres;
+
+use self::{
+    account::{AccountRepository, AccountTransaction},
+    account_log::{AccountLogRepository, AccountLogTransaction},
+    account_log_partition::AccountLogPartitionRepository,
+    action_job::{ActionJobRepository, ActionJobTransaction},
+    action_type::ActionTypeRepository,
+    asset_type::{AssetTypeRepository, AssetTypeTransaction},
+    balance_lot::{BalanceLotRepository, BalanceLotTransaction},
+    cache::CacheRepository,
+    client::ClientRepository,
+    exchange::{ExchangeRepository, ExchangeTransaction},
+    fee_rule::FeeRuleRepository,
+    hold::{HoldRepository, HoldTransaction},
+    limit_rule::{LimitRuleRepository, LimitRuleTransaction},
+};
+use axum_kit::AppResult;
+use std::future::Future;
+
+// 开启事务，事务类型需实现所有聚合的事务内写操作
+pub trait TransactionalRepository: Send + Sync {
+    type Transaction: RepositoryTransaction;
+
+    fn begin(&self) -> impl Future<Output = AppResult<Self::Transaction>> + Send;
+}
+
+// 事务内的保存点及提交，未调用`commit`即被丢弃时回滚
+pub trait TransactionControl: Send {
+    // 创建保存点，之后可只回滚保存点之后的变更，不支持嵌套
+    fn savepoint(&mut self) -> impl Future<Output = AppResult<()>> + Send;
+
+    fn release_savepoint(&mut self) -> impl Future<Output = AppResult<()>> + Send;
+
+    // 回滚保存点之后的变更并释放保存点
+    fn rollback_to_savepoint(&mut self) -> impl Future<Output = AppResult<()>> + Send;
+
+    fn commit(self) -> impl Future<Output = AppResult<()>> + Send;
+}
+
+// 账户、账户操作日志及资产类型、账户操作类型、客户端等各聚合的存储
+// 服务层只依赖该抽象：`PgRepository`用于生产环境，`MemoryRepository`用于测试或嵌入其他工具
+// 各聚合的读写分别在同名模块中定义，新增聚合时在此加入其存储
+pub trait Repository:
+    TransactionalRepository
+    + AccountRepository
+    + AccountLogRepository
+    + AccountLogPartitionRepository
+    + ActionJobRepository
+    + ActionTypeRepository
+    + AssetTypeRepository
+    + BalanceLotRepository
+    + CacheRepository
+    + ClientRepository
+    + ExchangeRepository
+    + FeeRuleRepository
+    + HoldRepository
+    + LimitRuleRepository
+{
+}
+
+impl<T> Repository for T where
+    T: TransactionalRepository
+        + AccountRepository
+        + AccountLogRepository
+        + AccountLogPartitionRepository
+        + ActionJobRepository
+        + ActionTypeRepository
+        + AssetTypeRepository
+        + BalanceLotRepository
+        + CacheRepository
+        + ClientRepository
+        + ExchangeRepository
+        + FeeRuleRepository
+        + HoldRepository
+        + LimitRuleRepository
+{
+}
+
+// 事务内各聚合的写操作
+pub trait RepositoryTransaction:
+    TransactionControl
+    + AccountTransaction
+    + AccountLogTransaction
+    + ActionJobTransaction
+    + AssetTypeTransaction
+    + BalanceLotTransaction
+    + CacheRepository
+    + ExchangeTransaction
+    + HoldTransaction
+    + LimitRuleTransaction
+{
+}
+
+impl<T> RepositoryTransaction for T where
+    T: TransactionControl
+        + AccountTransaction
+        + AccountLogTransaction
+        + ActionJobTransaction
+        + AssetTypeTransaction
+        + BalanceLotTransaction
+        + CacheRepository
+        + ExchangeTransaction
+        + HoldTransaction
+        + LimitRuleTransaction
+{
+}
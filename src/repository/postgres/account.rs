@@ -0,0 +1,77 @@
+use super::{PgRepository, PgTransaction};
+use crate::{
+    model::account::AccountModel,
+    repository::account::{AccountRepository, AccountTransaction},
+};
+use axum_kit::AppResult;
+use sqlx::types::Decimal;
+
+impl AccountRepository for PgRepository {
+    async fn create_account(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> AppResult<AccountModel> {
+        AccountModel::create(self.pool(), tenant_id, user_id, asset_type_id).await
+    }
+
+    async fn find_account(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> AppResult<AccountModel> {
+        AccountModel::find(self.pool(), tenant_id, user_id, asset_type_id).await
+    }
+
+    async fn find_accounts(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_ids: Vec<i32>,
+    ) -> AppResult<Vec<AccountModel>> {
+        AccountModel::find_multiple(self.pool(), tenant_id, user_id, asset_type_ids).await
+    }
+}
+
+impl AccountTransaction for PgTransaction {
+    async fn is_account_active(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> bool {
+        AccountModel::is_active(&mut *self.tx, tenant_id, user_id, asset_type_id).await
+    }
+
+    async fn lock_account(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> AppResult<AccountModel> {
+        AccountModel::find_for_update(&mut *self.tx, tenant_id, user_id, asset_type_id).await
+    }
+
+    async fn update_balance(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+        amounts: [Decimal; 4],
+    ) -> AppResult<AccountModel> {
+        let [available_balance, frozen_balance, total_income, total_expense] = amounts;
+        AccountModel::update_balance(
+            &mut *self.tx,
+            tenant_id,
+            user_id,
+            asset_type_id,
+            available_balance,
+            frozen_balance,
+            total_income,
+            total_expense,
+        )
+        .await
+    }
+}
@@ -0,0 +1,136 @@
+use super::{PgRepository, PgTransaction};
+use crate::{
+    model::{
+        account::AccountModel,
+        account_log::{AccountLogModel, AccountTransactionLegModel},
+    },
+    repository::account_log::{AccountLogRepository, AccountLogTransaction},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use sqlx::types::{chrono::NaiveDateTime, Decimal};
+
+impl AccountLogRepository for PgRepository {
+    async fn is_account_log_exists(
+        &self,
+        account_id: i32,
+        action_type_id: i32,
+        order_number: &str,
+    ) -> bool {
+        AccountLogModel::is_exists(self.pool(), account_id, action_type_id, order_number).await
+    }
+
+    async fn fetch_account_logs(
+        &self,
+        account_id: i32,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> AppResult<Vec<AccountLogModel>> {
+        AccountLogModel::fetch_page(self.pool(), account_id, before_id, limit).await
+    }
+
+    async fn fetch_account_log_chain(
+        &self,
+        account_id: i32,
+        after_id: i64,
+        limit: i64,
+    ) -> AppResult<Vec<AccountLogModel>> {
+        AccountLogModel::fetch_chain(self.pool(), account_id, after_id, limit).await
+    }
+
+    async fn fetch_account_log_range(
+        &self,
+        account_id: i32,
+        start_at: NaiveDateTime,
+        end_at: NaiveDateTime,
+        after_id: i64,
+        limit: i64,
+    ) -> AppResult<Vec<AccountLogModel>> {
+        AccountLogModel::fetch_range(self.pool(), account_id, start_at, end_at, after_id, limit)
+            .await
+    }
+
+    async fn find_last_account_log_before(
+        &self,
+        account_id: i32,
+        at: NaiveDateTime,
+    ) -> AppResult<Option<AccountLogModel>> {
+        AccountLogModel::find_last_before(self.pool(), account_id, at).await
+    }
+
+    async fn find_account_log_checkpoint(
+        &self,
+        account_id: i32,
+    ) -> AppResult<Option<AccountLogModel>> {
+        AccountLogModel::find_checkpoint(self.pool(), account_id).await
+    }
+
+    async fn fetch_account_log_hash_cutover(&self) -> AppResult<i64> {
+        AccountLogModel::fetch_hash_cutover(self.pool()).await
+    }
+
+    async fn fetch_account_transaction(
+        &self,
+        tenant_id: i32,
+        transaction_id: i64,
+    ) -> AppResult<Vec<AccountTransactionLegModel>> {
+        AccountLogModel::fetch_transaction(self.pool(), tenant_id, transaction_id).await
+    }
+}
+
+impl AccountLogTransaction for PgTransaction {
+    async fn transaction_id(&mut self) -> AppResult<i64> {
+        if let Some(transaction_id) = self.transaction_id {
+            return Ok(transaction_id);
+        }
+        let transaction_id = AccountLogModel::next_transaction_id(&mut *self.tx).await?;
+        self.transaction_id = Some(transaction_id);
+        Ok(transaction_id)
+    }
+
+    async fn create_account_log(
+        &mut self,
+        account: &AccountModel,
+        action_type_id: i32,
+        amounts: [Decimal; 4],
+        order_number: &str,
+        description: &str,
+        client_id: i32,
+        operator: &str,
+        created_at: Option<NaiveDateTime>,
+    ) -> AppResult<()> {
+        let transaction_id = self.transaction_id().await?;
+        let [available_balance, frozen_balance, total_income, total_expense] = amounts;
+        let result = AccountLogModel::create(
+            &mut self.tx,
+            account.tenant_id,
+            account.id,
+            action_type_id,
+            available_balance,
+            frozen_balance,
+            total_income,
+            total_expense,
+            account.available_balance,
+            account.frozen_balance,
+            account.total_income,
+            account.total_expense,
+            order_number,
+            description,
+            client_id,
+            operator,
+            created_at,
+            transaction_id,
+        )
+        .await;
+        // 并发请求使用相同订单号时，开启事务前的检查可能均已通过，由唯一约束兜底
+        match result {
+            Err(Error::Sqlx(sqlx::Error::Database(err))) if err.is_unique_violation() => {
+                Err(Error::Custom(
+                    StatusCode::CONFLICT,
+                    "操作失败，存在已处理的订单".to_string(),
+                ))
+            }
+            result => result,
+        }
+    }
+}
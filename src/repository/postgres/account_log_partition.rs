@@ -0,0 +1,18 @@
+use super::PgRepository;
+use crate::{
+    model::account_log_partition::AccountLogPartitionModel,
+    repository::account_log_partition::AccountLogPartitionRepository,
+};
+use axum_kit::AppResult;
+use sqlx::types::chrono::NaiveDate;
+
+impl AccountLogPartitionRepository for PgRepository {
+    async fn create_account_log_partition(&self, month: NaiveDate) -> AppResult<()> {
+        AccountLogPartitionModel::create(self.pool(), month.and_time(Default::default())).await
+    }
+
+    async fn fetch_account_log_default_months(&self) -> AppResult<Vec<NaiveDate>> {
+        let months = AccountLogPartitionModel::fetch_default_months(self.pool()).await?;
+        Ok(months.into_iter().map(|month| month.date()).collect())
+    }
+}
@@ -0,0 +1,64 @@
+use super::{PgRepository, PgTransaction};
+use crate::{
+    model::action_job::{ActionJobItemModel, ActionJobItemStatus, ActionJobModel},
+    repository::action_job::{ActionJobRepository, ActionJobTransaction},
+};
+use axum_kit::AppResult;
+
+impl ActionJobRepository for PgRepository {
+    async fn find_action_job(&self, tenant_id: i32, id: i64) -> AppResult<ActionJobModel> {
+        ActionJobModel::find(self.pool(), tenant_id, id).await
+    }
+
+    async fn claim_action_job(&self, lease_seconds: f64) -> AppResult<Option<ActionJobModel>> {
+        ActionJobModel::claim(self.pool(), lease_seconds).await
+    }
+
+    async fn fetch_action_job_items(
+        &self,
+        job_id: i64,
+        status: Option<ActionJobItemStatus>,
+        after_seq: i32,
+        limit: i64,
+    ) -> AppResult<Vec<ActionJobItemModel>> {
+        ActionJobItemModel::fetch(self.pool(), job_id, status, after_seq, limit).await
+    }
+
+    async fn complete_action_job(&self, id: i64) -> AppResult<ActionJobModel> {
+        ActionJobModel::complete(self.pool(), id).await
+    }
+}
+
+impl ActionJobTransaction for PgTransaction {
+    async fn create_action_job(
+        &mut self,
+        tenant_id: i32,
+        chunk_size: i32,
+        total_count: i32,
+        client_id: i32,
+        operator: &str,
+    ) -> AppResult<ActionJobModel> {
+        ActionJobModel::create(
+            &mut *self.tx,
+            tenant_id,
+            chunk_size,
+            total_count,
+            client_id,
+            operator,
+        )
+        .await
+    }
+
+    async fn create_action_job_items(&mut self, items: &[ActionJobItemModel]) -> AppResult<()> {
+        ActionJobItemModel::create_multiple(&mut *self.tx, items).await
+    }
+
+    async fn update_action_job_results(
+        &mut self,
+        job_id: i64,
+        succeeded: &[i32],
+        failed: &[(i32, String)],
+    ) -> AppResult<()> {
+        ActionJobItemModel::update_results(&mut self.tx, job_id, succeeded, failed).await
+    }
+}
@@ -0,0 +1,20 @@
+use super::PgRepository;
+use crate::{model::action_type::ActionTypeModel, repository::action_type::ActionTypeRepository};
+use axum_kit::AppResult;
+
+impl ActionTypeRepository for PgRepository {
+    async fn fetch_action_types(&self) -> AppResult<Vec<ActionTypeModel>> {
+        ActionTypeModel::fetch_all(self.pool()).await
+    }
+
+    async fn fetch_action_types_including_inactive(
+        &self,
+        tenant_id: i32,
+    ) -> AppResult<Vec<ActionTypeModel>> {
+        ActionTypeModel::fetch_all_including_inactive(self.pool(), tenant_id).await
+    }
+
+    async fn find_action_type(&self, tenant_id: i32, name: &str) -> AppResult<ActionTypeModel> {
+        ActionTypeModel::find_by_name(self.pool(), tenant_id, name).await
+    }
+}
@@ -0,0 +1,32 @@
+use super::{PgRepository, PgTransaction};
+use crate::{
+    model::{asset_supply::AssetSupplyModel, asset_type::AssetTypeModel},
+    repository::asset_type::{AssetTypeRepository, AssetTypeTransaction},
+};
+use axum_kit::AppResult;
+use sqlx::types::Decimal;
+
+impl AssetTypeRepository for PgRepository {
+    async fn fetch_asset_types(&self) -> AppResult<Vec<AssetTypeModel>> {
+        AssetTypeModel::fetch_all(self.pool()).await
+    }
+
+    async fn fetch_asset_supplies(&self, tenant_id: i32) -> AppResult<Vec<AssetSupplyModel>> {
+        AssetSupplyModel::fetch_all(self.pool(), tenant_id).await
+    }
+}
+
+impl AssetTypeTransaction for PgTransaction {
+    async fn add_asset_supply(&mut self, asset_type_id: i32, amount: Decimal) -> AppResult<()> {
+        *self.supply.entry(asset_type_id).or_default() += amount;
+        Ok(())
+    }
+
+    async fn add_capped_asset_supply(
+        &mut self,
+        asset_type_id: i32,
+        amount: Decimal,
+    ) -> AppResult<bool> {
+        AssetSupplyModel::add_capped_supply(&mut *self.tx, asset_type_id, amount).await
+    }
+}
@@ -0,0 +1,40 @@
+use super::{PgRepository, PgTransaction};
+use crate::{
+    model::{account::AccountModel, balance_lot::BalanceLotModel},
+    repository::balance_lot::{BalanceLotRepository, BalanceLotTransaction},
+};
+use axum_kit::AppResult;
+use sqlx::types::{chrono::NaiveDateTime, Decimal};
+
+impl BalanceLotRepository for PgRepository {
+    async fn fetch_accounts_with_expired_balance_lots(
+        &self,
+        after_id: i32,
+        limit: i64,
+    ) -> AppResult<Vec<AccountModel>> {
+        BalanceLotModel::fetch_expired_accounts(self.pool(), after_id, limit).await
+    }
+}
+
+impl BalanceLotTransaction for PgTransaction {
+    async fn create_balance_lot(
+        &mut self,
+        account: &AccountModel,
+        amount: Decimal,
+        order_number: &str,
+        created_at: Option<NaiveDateTime>,
+    ) -> AppResult<()> {
+        BalanceLotModel::create(&mut *self.tx, account, amount, order_number, created_at).await
+    }
+
+    async fn consume_balance_lots(&mut self, account_id: i32, amount: Decimal) -> AppResult<()> {
+        BalanceLotModel::consume(&mut self.tx, account_id, amount).await
+    }
+
+    async fn fetch_expired_balance_lots(
+        &mut self,
+        account_id: i32,
+    ) -> AppResult<Vec<BalanceLotModel>> {
+        BalanceLotModel::fetch_expired(&mut *self.tx, account_id).await
+    }
+}
@@ -0,0 +1,9 @@
+use super::PgRepository;
+use crate::{model::client::ClientModel, repository::client::ClientRepository};
+use axum_kit::AppResult;
+
+impl ClientRepository for PgRepository {
+    async fn fetch_clients(&self) -> AppResult<Vec<ClientModel>> {
+        ClientModel::fetch_all(self.pool()).await
+    }
+}
@@ -0,0 +1,66 @@
+use super::{PgRepository, PgTransaction};
+use crate::{
+    model::{exchange_log::ExchangeLogModel, exchange_rate::ExchangeRateModel},
+    repository::exchange::{ExchangeRepository, ExchangeTransaction},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use sqlx::types::Decimal;
+
+impl ExchangeRepository for PgRepository {
+    async fn find_exchange_rate(
+        &self,
+        tenant_id: i32,
+        from_asset_type_id: i32,
+        to_asset_type_id: i32,
+    ) -> AppResult<ExchangeRateModel> {
+        ExchangeRateModel::find_effective(
+            self.pool(),
+            tenant_id,
+            from_asset_type_id,
+            to_asset_type_id,
+        )
+        .await
+    }
+}
+
+impl ExchangeTransaction for PgTransaction {
+    async fn create_exchange_log(
+        &mut self,
+        user_id: &str,
+        exchange_rate: &ExchangeRateModel,
+        from_amount: Decimal,
+        to_amount: Decimal,
+        order_number: &str,
+        description: &str,
+        client_id: i32,
+        operator: &str,
+    ) -> AppResult<ExchangeLogModel> {
+        let result = ExchangeLogModel::create(
+            &mut *self.tx,
+            exchange_rate.tenant_id,
+            user_id,
+            exchange_rate.id,
+            exchange_rate.from_asset_type_id,
+            exchange_rate.to_asset_type_id,
+            exchange_rate.rate,
+            exchange_rate.spread,
+            from_amount,
+            to_amount,
+            order_number,
+            description,
+            client_id,
+            operator,
+        )
+        .await;
+        match result {
+            Err(Error::Sqlx(sqlx::Error::Database(err))) if err.is_unique_violation() => {
+                Err(Error::Custom(
+                    StatusCode::CONFLICT,
+                    "操作失败，存在已处理的订单".to_string(),
+                ))
+            }
+            result => result,
+        }
+    }
+}
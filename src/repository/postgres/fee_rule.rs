@@ -0,0 +1,9 @@
+use super::PgRepository;
+use crate::{model::fee_rule::FeeRuleModel, repository::fee_rule::FeeRuleRepository};
+use axum_kit::AppResult;
+
+impl FeeRuleRepository for PgRepository {
+    async fn fetch_fee_rules(&self) -> AppResult<Vec<FeeRuleModel>> {
+        FeeRuleModel::fetch_all(self.pool()).await
+    }
+}
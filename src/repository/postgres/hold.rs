@@ -0,0 +1,77 @@
+use super::{PgRepository, PgTransaction};
+use crate::{
+    model::{
+        account::AccountModel,
+        hold::{HoldModel, HoldStatus},
+    },
+    repository::hold::{HoldRepository, HoldTransaction},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use sqlx::types::Decimal;
+
+impl HoldRepository for PgRepository {
+    async fn find_hold(&self, tenant_id: i32, reference: &str) -> AppResult<HoldModel> {
+        HoldModel::find(self.pool(), tenant_id, reference).await
+    }
+
+    async fn fetch_expired_holds(&self, limit: i64) -> AppResult<Vec<HoldModel>> {
+        HoldModel::fetch_expired(self.pool(), limit).await
+    }
+}
+
+impl HoldTransaction for PgTransaction {
+    async fn create_hold(
+        &mut self,
+        account: &AccountModel,
+        reference: &str,
+        amount: Decimal,
+        expires_in: i64,
+        description: &str,
+        client_id: i32,
+        operator: &str,
+    ) -> AppResult<HoldModel> {
+        let result = HoldModel::create(
+            &mut *self.tx,
+            account.tenant_id,
+            account.id,
+            &account.user_id,
+            account.asset_type_id,
+            reference,
+            amount,
+            expires_in,
+            description,
+            client_id,
+            operator,
+        )
+        .await;
+        match result {
+            Err(Error::Sqlx(sqlx::Error::Database(err))) if err.is_unique_violation() => Err(
+                Error::Custom(StatusCode::CONFLICT, "预授权已存在".to_string()),
+            ),
+            result => result,
+        }
+    }
+
+    async fn find_hold_for_update(
+        &mut self,
+        tenant_id: i32,
+        reference: &str,
+    ) -> AppResult<HoldModel> {
+        HoldModel::find_for_update(&mut *self.tx, tenant_id, reference).await
+    }
+
+    async fn is_hold_expired(&mut self, hold: &HoldModel) -> AppResult<bool> {
+        HoldModel::is_expired(&mut *self.tx, hold.expires_at).await
+    }
+
+    async fn update_hold(
+        &mut self,
+        id: i64,
+        captured_amount: Decimal,
+        released_amount: Decimal,
+        status: HoldStatus,
+    ) -> AppResult<HoldModel> {
+        HoldModel::update(&mut *self.tx, id, captured_amount, released_amount, status).await
+    }
+}
@@ -0,0 +1,42 @@
+use super::{PgRepository, PgTransaction};
+use crate::{
+    model::limit_rule::{LimitRuleModel, LimitUsage},
+    repository::limit_rule::{LimitRuleRepository, LimitRuleTransaction},
+};
+use axum_kit::AppResult;
+use sqlx::types::chrono::NaiveDateTime;
+
+impl LimitRuleRepository for PgRepository {
+    async fn fetch_limit_rules(&self) -> AppResult<Vec<LimitRuleModel>> {
+        LimitRuleModel::fetch_all(self.pool()).await
+    }
+}
+
+impl LimitRuleTransaction for PgTransaction {
+    async fn lock_limit_usage(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+    ) -> AppResult<NaiveDateTime> {
+        LimitRuleModel::lock_usage(&mut *self.tx, tenant_id, user_id).await
+    }
+
+    async fn fetch_limit_usage(
+        &mut self,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: Option<i32>,
+        action_type_id: Option<i32>,
+        since: NaiveDateTime,
+    ) -> AppResult<LimitUsage> {
+        LimitRuleModel::fetch_usage(
+            &mut *self.tx,
+            tenant_id,
+            user_id,
+            asset_type_id,
+            action_type_id,
+            since,
+        )
+        .await
+    }
+}
@@ -0,0 +1,130 @@
+mod account;
+mod account_log;
+mod account_log_partition;
+mod action_job;
+mod action_type;
+mod asset_type;
+mod balance_lot;
+mod client;
+mod exchange;
+mod fee_rule;
+mod hold;
+mod limit_rule;
+
+use super::{
+    cache::{CacheRepository, ConfigCache},
+    TransactionControl, TransactionalRepository,
+};
+use crate::model::asset_supply::AssetSupplyModel;
+use axum_kit::AppResult;
+use sqlx::{types::Decimal, PgConnection, PgPool, Postgres, Transaction};
+use std::{collections::BTreeMap, sync::Arc};
+
+// 克隆的存储共享同一份配置缓存
+#[derive(Clone, Default)]
+pub struct PgRepository {
+    pool: Option<PgPool>,
+    cache: Arc<ConfigCache>,
+}
+
+impl PgRepository {
+    // 使用指定连接池，未指定时使用服务启动时初始化的连接池
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool: Some(pool),
+            cache: Arc::default(),
+        }
+    }
+
+    fn pool(&self) -> &PgPool {
+        match &self.pool {
+            Some(pool) => pool,
+            None => axum_kit::postgres::conn(),
+        }
+    }
+}
+
+pub struct PgTransaction {
+    tx: Transaction<'static, Postgres>,
+    cache: Arc<ConfigCache>,
+    transaction_id: Option<i64>,
+    // 本事务内各资产类型的流通量变更，提交时写入
+    supply: BTreeMap<i32, Decimal>,
+    // 创建保存点时的流通量变更，回滚保存点时恢复
+    savepoint_supply: Option<BTreeMap<i32, Decimal>>,
+}
+
+impl PgTransaction {
+    // 供需要在同一事务内执行其他语句的调用方使用
+    pub fn conn(&mut self) -> &mut PgConnection {
+        &mut self.tx
+    }
+}
+
+impl TransactionalRepository for PgRepository {
+    type Transaction = PgTransaction;
+
+    async fn begin(&self) -> AppResult<PgTransaction> {
+        let tx = self.pool().begin().await?;
+        Ok(PgTransaction {
+            tx,
+            cache: self.cache.clone(),
+            transaction_id: None,
+            supply: BTreeMap::new(),
+            savepoint_supply: None,
+        })
+    }
+}
+
+impl CacheRepository for PgRepository {
+    fn cache(&self) -> &ConfigCache {
+        &self.cache
+    }
+}
+
+impl CacheRepository for PgTransaction {
+    fn cache(&self) -> &ConfigCache {
+        &self.cache
+    }
+}
+
+impl TransactionControl for PgTransaction {
+    async fn savepoint(&mut self) -> AppResult<()> {
+        sqlx::query!("savepoint account_action")
+            .execute(&mut *self.tx)
+            .await?;
+        self.savepoint_supply = Some(self.supply.clone());
+        Ok(())
+    }
+
+    async fn release_savepoint(&mut self) -> AppResult<()> {
+        sqlx::query!("release savepoint account_action")
+            .execute(&mut *self.tx)
+            .await?;
+        self.savepoint_supply = None;
+        Ok(())
+    }
+
+    async fn rollback_to_savepoint(&mut self) -> AppResult<()> {
+        sqlx::query!("rollback to savepoint account_action")
+            .execute(&mut *self.tx)
+            .await?;
+        if let Some(supply) = self.savepoint_supply.take() {
+            self.supply = supply;
+        }
+        self.release_savepoint().await
+    }
+
+    // 所有账户变更之后按资产类型顺序写入未设置发行上限的流通量，各事务锁定资产类型行的顺序一致
+    // 每个资产类型只写入一次净额，缩短锁定资产类型行的时间
+    async fn commit(mut self) -> AppResult<()> {
+        for (asset_type_id, amount) in &self.supply {
+            if amount.is_zero() {
+                continue;
+            }
+            AssetSupplyModel::add_supply(&mut *self.tx, *asset_type_id, *amount).await?;
+        }
+        self.tx.commit().await?;
+        Ok(())
+    }
+}
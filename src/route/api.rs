@@ -1,25 +1,32 @@
-use crate::handler;
+use super::openapi::ApiDoc;
+use crate::{handler, middleware::auth, repository::postgres::PgRepository};
 use axum::{
-    routing::{get, post},
-    Router,
+    extract::DefaultBodyLimit,
+    handler::Handler,
+    http::Method,
+    middleware::from_fn,
+    routing::{get, post, MethodRouter},
+    Extension, Router,
 };
 use axum_kit::middleware::{cors, request_id, trace, trace_body};
 use tower::ServiceBuilder;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 pub fn init() -> Router {
-    Router::new()
-        // 获取资产类型
-        .route("/assets", get(handler::asset_type::list))
-        // 获取账户操作类型
-        .route("/actions", get(handler::action_type::list))
-        // 添加资产账户
-        .route("/accounts/new", post(handler::account::create))
-        // 获取资产账户信息
-        .route("/accounts/info", post(handler::account::info))
-        // 获取某`user_id`所有资产账户信息
-        .route("/accounts/infos", post(handler::account::infos))
-        // 资产账户操作
-        .route("/accounts/actions", post(handler::account::actions))
+    router(PgRepository::default())
+}
+
+// 使用指定存储构建路由，集成测试借此连接一次性数据库
+pub fn router(repository: PgRepository) -> Router {
+    let authenticated = build(authenticated_routes());
+    let public = build(public_routes());
+    authenticated
+        .route_layer(from_fn(auth::auth))
+        .merge(public)
+        // OpenAPI 文档及 Swagger UI
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", ApiDoc::openapi()))
+        .layer(Extension(repository))
         .layer(
             ServiceBuilder::new()
                 .layer(request_id::set_request_id())
@@ -29,3 +36,117 @@ pub fn init() -> Router {
                 .layer(trace_body::trace_body()),
         )
 }
+
+// 已注册路由的请求方法及路径，不包括接口文档，用于校验 OpenAPI 文档与路由一致
+pub fn routes() -> Vec<(Method, &'static str)> {
+    authenticated_routes()
+        .into_iter()
+        .chain(public_routes())
+        .map(|route| (route.method, route.path))
+        .collect()
+}
+
+fn build(routes: Vec<Route>) -> Router {
+    routes.into_iter().fold(Router::new(), |router, route| {
+        router.route(route.path, route.method_router)
+    })
+}
+
+// 请求方法与处理函数在同一处指定，路由与`routes`不会不一致
+struct Route {
+    method: Method,
+    path: &'static str,
+    method_router: MethodRouter,
+}
+
+impl Route {
+    fn get<H, T>(path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, ()>,
+        T: 'static,
+    {
+        Self {
+            method: Method::GET,
+            path,
+            method_router: get(handler),
+        }
+    }
+
+    fn post<H, T>(path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, ()>,
+        T: 'static,
+    {
+        Self {
+            method: Method::POST,
+            path,
+            method_router: post(handler),
+        }
+    }
+
+    fn body_limit(mut self, limit: usize) -> Self {
+        self.method_router = self.method_router.layer(DefaultBodyLimit::max(limit));
+        self
+    }
+}
+
+// 需认证的接口
+fn authenticated_routes() -> Vec<Route> {
+    vec![
+        // 获取资产类型
+        Route::get("/assets", handler::asset_type::list),
+        // 获取资产流通量统计
+        Route::get("/assets/supply", handler::asset_type::supply),
+        // 获取账户操作类型
+        Route::get("/actions", handler::action_type::list),
+        // 添加资产账户
+        Route::post("/accounts/new", handler::account::create),
+        // 获取资产账户信息
+        Route::post("/accounts/info", handler::account::info),
+        // 获取某`user_id`所有资产账户信息
+        Route::post("/accounts/infos", handler::account::infos),
+        // 资产账户操作
+        Route::post("/accounts/actions", handler::account::actions),
+        // 资产兑换
+        Route::post("/accounts/exchange", handler::exchange::exchange),
+        // 获取资产账户操作日志
+        Route::post("/accounts/logs", handler::account::logs),
+        // 导出资产账户账单
+        Route::post("/accounts/statement", handler::account::statement),
+        // 校验资产账户操作日志哈希链
+        Route::post("/accounts/logs/verify", handler::account::verify_logs),
+        // 获取交易的所有账户操作日志
+        Route::post("/accounts/transactions/info", handler::account::transaction),
+        // 创建预授权
+        Route::post("/holds/new", handler::hold::create),
+        // 获取预授权信息
+        Route::post("/holds/info", handler::hold::info),
+        // 预授权扣款
+        Route::post("/holds/capture", handler::hold::capture),
+        // 释放预授权
+        Route::post("/holds/release", handler::hold::release),
+        // 提交批量账户操作任务
+        Route::post("/jobs/actions/new", handler::action_job::create)
+            .body_limit(handler::action_job::ACTION_JOB_BODY_LIMIT),
+        // 获取批量账户操作任务进度
+        Route::post("/jobs/actions/info", handler::action_job::info),
+        // 订阅批量账户操作任务进度
+        Route::post("/jobs/actions/events", handler::action_job::events),
+        // 导出批量账户操作任务明细
+        Route::post("/jobs/actions/report", handler::action_job::report),
+        // 获取数据变更日志
+        Route::post("/change_logs", handler::change_log::list),
+    ]
+}
+
+// 无需认证的接口
+fn public_routes() -> Vec<Route> {
+    vec![
+        // Prometheus 指标
+        Route::get("/metrics", handler::metric::render),
+        // 存活检查
+        Route::get("/healthz", handler::health::healthz),
+        // 就绪检查
+        Route::get("/readyz", handler::health::readyz),
+    ]
+}
@@ -0,0 +1,132 @@
+use crate::handler;
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "amazing", description = "虚拟资产管理系统"),
+    paths(
+        handler::asset_type::list,
+        handler::asset_type::supply,
+        handler::action_type::list,
+        handler::account::create,
+        handler::account::info,
+        handler::account::infos,
+        handler::account::actions,
+        handler::exchange::exchange,
+        handler::account::logs,
+        handler::account::statement,
+        handler::account::verify_logs,
+        handler::account::transaction,
+        handler::hold::create,
+        handler::hold::info,
+        handler::hold::capture,
+        handler::hold::release,
+        handler::action_job::create,
+        handler::action_job::info,
+        handler::action_job::events,
+        handler::action_job::report,
+        handler::change_log::list,
+        handler::metric::render,
+        handler::health::healthz,
+        handler::health::readyz,
+    ),
+    modifiers(&SecurityAddon)
+)]
+pub struct ApiDoc;
+
+// 客户端认证方式，见`middleware::auth`
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ApiDoc;
+    use crate::route::api;
+    use axum::{body::Body, http::Request};
+    use std::collections::BTreeSet;
+    use tower::ServiceExt;
+    use utoipa::OpenApi;
+
+    fn routes() -> BTreeSet<(String, String)> {
+        api::routes()
+            .into_iter()
+            .map(|(method, path)| (method.as_str().to_lowercase(), path.to_string()))
+            .collect()
+    }
+
+    fn operations() -> BTreeSet<(String, String)> {
+        let openapi = ApiDoc::openapi();
+        let mut operations = BTreeSet::new();
+        for (path, item) in openapi.paths.paths {
+            let methods = [
+                ("get", item.get.is_some()),
+                ("post", item.post.is_some()),
+                ("put", item.put.is_some()),
+                ("delete", item.delete.is_some()),
+                ("patch", item.patch.is_some()),
+            ];
+            for (method, exists) in methods {
+                if exists {
+                    operations.insert((method.to_string(), path.clone()));
+                }
+            }
+        }
+        operations
+    }
+
+    #[test]
+    fn openapi_matches_routes() {
+        assert_eq!(routes(), operations());
+    }
+
+    #[test]
+    fn openapi_operation_ids_are_unique() {
+        let openapi = ApiDoc::openapi();
+        let mut operation_ids = BTreeSet::new();
+        for item in openapi.paths.paths.values() {
+            for operation in [&item.get, &item.post].into_iter().flatten() {
+                let operation_id = operation.operation_id.clone().unwrap_or_default();
+                assert!(
+                    operation_ids.insert(operation_id.clone()),
+                    "duplicate operation id {operation_id}"
+                );
+            }
+        }
+    }
+
+    // 文档中的路径均已注册：已注册路径使用未注册的请求方法时返回 405，未注册路径返回 404
+    #[tokio::test]
+    async fn openapi_paths_are_routed() {
+        for (_, path) in operations() {
+            let request = Request::builder()
+                .method("OPTIONS")
+                .uri(&path)
+                .body(Body::empty())
+                .unwrap();
+            let response = api::init().oneshot(request).await.unwrap();
+            assert_ne!(response.status(), 404, "{path} is not routed");
+        }
+    }
+
+    #[tokio::test]
+    async fn openapi_json_is_served() {
+        let request = Request::builder()
+            .uri("/openapi.json")
+            .body(Body::empty())
+            .unwrap();
+        let response = api::init().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), 200);
+    }
+}
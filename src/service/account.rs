@@ -1,23 +1,87 @@
-use super::{action_type::ActionTypeService, asset_type::AssetTypeService};
+use super::{
+    action_type::ActionTypeService,
+    asset_type::AssetTypeService,
+    client::Operator,
+    fee_rule::{Fee, FeeRuleService, Fees},
+    limit_rule::{LimitRuleService, LimitUsages},
+    metric::{MetricService, RejectReason},
+    system_account::{CounterEntry, SystemAccountService, SystemActionTypes},
+};
 use crate::{
-    handler::account::{AccountActionRequest, AccountRequest, AccountsRequest},
+    handler::account::{
+        AccountActionMode, AccountActionRequest, AccountActionResult, AccountActionStatus,
+        AccountActionsPreview, AccountLogsRequest, AccountLogsVerifyResponse, AccountRequest,
+        AccountsRequest,
+    },
     model::{
         account::AccountModel,
-        account_log::AccountLogModel,
+        account_log::{AccountLogModel, AccountTransactionLegModel},
         action_type::{ActionTypeModel, Change},
     },
+    repository::{
+        account::AccountTransaction, cache::ConfigCache, Repository, RepositoryTransaction,
+        TransactionControl,
+    },
 };
-use axum::http::StatusCode;
-use axum_kit::{error::Error, postgres, AppResult};
+use axum::{http::StatusCode, response::IntoResponse};
+use axum_kit::{error::Error, AppResult};
 use num_traits::FromPrimitive;
-use sqlx::types::Decimal;
+use sqlx::types::{chrono::NaiveDateTime, Decimal};
+use std::{collections::HashSet, slice, time::Instant};
 use validator::Validate;
 
+// 校验哈希链时每次读取的日志条数
+const VERIFY_CHUNK_SIZE: i64 = 1000;
+
+// 入账的一条分录，金额为精确的正数，账户操作按 6 位小数截断，对应分录取净额
+struct Entry<'a> {
+    user_id: &'a str,
+    asset_type_id: i32,
+    amount: Decimal,
+    order_number: &'a str,
+    description: &'a str,
+}
+
+impl<'a> From<&'a AccountActionRequest> for Entry<'a> {
+    fn from(account_action_request: &'a AccountActionRequest) -> Self {
+        Self {
+            user_id: &account_action_request.user_id,
+            asset_type_id: account_action_request.asset_type_id,
+            amount: Decimal::from_f64(account_action_request.amount.abs())
+                .unwrap()
+                .trunc_with_scale(6),
+            order_number: &account_action_request.order_number,
+            description: &account_action_request.description,
+        }
+    }
+}
+
+impl<'a> From<&'a CounterEntry> for Entry<'a> {
+    fn from(counter_entry: &'a CounterEntry) -> Self {
+        Self {
+            user_id: &counter_entry.user_id,
+            asset_type_id: counter_entry.asset_type_id,
+            amount: counter_entry.amount,
+            order_number: &counter_entry.order_number,
+            description: &counter_entry.description,
+        }
+    }
+}
+
 pub struct AccountService;
 
 impl AccountService {
-    pub async fn check_account_is_active(user_id: i32, asset_type_id: i32) -> AppResult<()> {
-        if !AccountModel::is_active(postgres::conn(), user_id, asset_type_id).await {
+    pub async fn check_account_is_active(
+        tx: &mut impl RepositoryTransaction,
+        tenant_id: i32,
+        user_id: &str,
+        asset_type_id: i32,
+    ) -> AppResult<()> {
+        if !tx
+            .is_account_active(tenant_id, user_id, asset_type_id)
+            .await
+        {
+            MetricService::record_reject(RejectReason::InactiveAccount);
             return Err(Error::Custom(
                 StatusCode::FORBIDDEN,
                 "操作失败，存在未启用账户".to_string(),
@@ -35,6 +99,7 @@ impl AccountService {
             && account.available_balance < amount)
             || (action_type.frozen_balance_change == Change::Dec && account.frozen_balance < amount)
         {
+            MetricService::record_reject(RejectReason::InsufficientBalance);
             return Err(Error::Custom(
                 StatusCode::PAYMENT_REQUIRED,
                 "操作失败，存在余额不足的账户".to_string(),
@@ -52,6 +117,7 @@ impl AccountService {
             || (action_type.frozen_balance_change == Change::Dec
                 && account.frozen_balance.is_sign_negative())
         {
+            MetricService::record_reject(RejectReason::InsufficientBalance);
             return Err(Error::Custom(
                 StatusCode::PAYMENT_REQUIRED,
                 "操作失败，存在余额不足的账户".to_string(),
@@ -61,13 +127,16 @@ impl AccountService {
     }
 
     pub async fn check_account_log_exists(
+        repository: &impl Repository,
         account_id: i32,
         action_type_id: i32,
         order_number: &str,
     ) -> AppResult<()> {
-        if AccountLogModel::is_exists(postgres::conn(), account_id, action_type_id, order_number)
+        if repository
+            .is_account_log_exists(account_id, action_type_id, order_number)
             .await
         {
+            MetricService::record_reject(RejectReason::DuplicateOrder);
             return Err(Error::Custom(
                 StatusCode::CONFLICT,
                 "操作失败，存在已处理的订单".to_string(),
@@ -76,126 +145,1089 @@ impl AccountService {
         Ok(())
     }
 
-    pub async fn create(account_request: &AccountRequest) -> AppResult<AccountModel> {
+    pub async fn create(
+        repository: &impl Repository,
+        tenant_id: i32,
+        account_request: &AccountRequest,
+    ) -> AppResult<AccountModel> {
         account_request.validate()?;
-        let pool = postgres::conn();
-        let account =
-            AccountModel::create(pool, account_request.user_id, account_request.asset_type_id)
-                .await?;
+        AssetTypeService::by_id(repository.cache(), tenant_id, account_request.asset_type_id)?;
+        let account = repository
+            .create_account(
+                tenant_id,
+                &account_request.user_id,
+                account_request.asset_type_id,
+            )
+            .await?;
         Ok(account)
     }
 
-    pub async fn info(account_request: &AccountRequest) -> AppResult<AccountModel> {
+    pub async fn info(
+        repository: &impl Repository,
+        tenant_id: i32,
+        account_request: &AccountRequest,
+    ) -> AppResult<AccountModel> {
         account_request.validate()?;
-        let account = AccountModel::find(
-            postgres::conn(),
-            account_request.user_id,
-            account_request.asset_type_id,
-        )
-        .await?;
+        AssetTypeService::by_id(repository.cache(), tenant_id, account_request.asset_type_id)?;
+        let account = repository
+            .find_account(
+                tenant_id,
+                &account_request.user_id,
+                account_request.asset_type_id,
+            )
+            .await?;
         Ok(account)
     }
 
-    pub async fn infos(accounts_request: &AccountsRequest) -> AppResult<Vec<AccountModel>> {
-        let accounts = AccountModel::find_multiple(
-            postgres::conn(),
-            accounts_request.user_id,
-            AssetTypeService::ids(),
+    pub async fn infos(
+        repository: &impl Repository,
+        tenant_id: i32,
+        accounts_request: &AccountsRequest,
+    ) -> AppResult<Vec<AccountModel>> {
+        let accounts = repository
+            .find_accounts(
+                tenant_id,
+                &accounts_request.user_id,
+                AssetTypeService::ids(repository.cache(), tenant_id)?,
+            )
+            .await?;
+        Ok(accounts)
+    }
+
+    pub async fn logs(
+        repository: &impl Repository,
+        tenant_id: i32,
+        account_logs_request: &AccountLogsRequest,
+    ) -> AppResult<Vec<AccountLogModel>> {
+        account_logs_request.validate()?;
+        AssetTypeService::by_id(
+            repository.cache(),
+            tenant_id,
+            account_logs_request.asset_type_id,
+        )?;
+        let account = repository
+            .find_account(
+                tenant_id,
+                &account_logs_request.user_id,
+                account_logs_request.asset_type_id,
+            )
+            .await?;
+        let account_logs = repository
+            .fetch_account_logs(
+                account.id,
+                account_logs_request.before_id,
+                account_logs_request.limit,
+            )
+            .await?;
+        Ok(account_logs)
+    }
+
+    pub async fn transaction(
+        repository: &impl Repository,
+        tenant_id: i32,
+        transaction_id: i64,
+    ) -> AppResult<Vec<AccountTransactionLegModel>> {
+        let legs = repository
+            .fetch_account_transaction(tenant_id, transaction_id)
+            .await?;
+        if legs.is_empty() {
+            return Err(Error::Custom(
+                StatusCode::NOT_FOUND,
+                "交易不存在".to_string(),
+            ));
+        }
+        Ok(legs)
+    }
+
+    // 按写入顺序遍历账户日志，逐条校验哈希链，遇到第一条校验失败的日志即停止
+    pub async fn verify_logs(
+        repository: &impl Repository,
+        tenant_id: i32,
+        account_request: &AccountRequest,
+    ) -> AppResult<AccountLogsVerifyResponse> {
+        account_request.validate()?;
+        AssetTypeService::by_id(repository.cache(), tenant_id, account_request.asset_type_id)?;
+        let account = repository
+            .find_account(
+                tenant_id,
+                &account_request.user_id,
+                account_request.asset_type_id,
+            )
+            .await?;
+        let mut response = AccountLogsVerifyResponse {
+            account_id: account.id,
+            verified: 0,
+            unhashed: 0,
+            broken_log_id: None,
+        };
+        // 已归档的日志无法校验，哈希链从归档检查点的哈希继续
+        let mut prev_hash = repository
+            .find_account_log_checkpoint(account.id)
+            .await?
+            .map(|account_log| account_log.hash)
+            .unwrap_or_default();
+        let hash_cutover = repository.fetch_account_log_hash_cutover().await?;
+        let mut after_id = 0;
+        loop {
+            let account_logs = repository
+                .fetch_account_log_chain(account.id, after_id, VERIFY_CHUNK_SIZE)
+                .await?;
+            let Some(last) = account_logs.last() else {
+                break;
+            };
+            after_id = last.id;
+            for account_log in account_logs {
+                // 哈希链上线前的历史日志没有哈希，哈希链从第一条有哈希的日志开始
+                // 格式切换后写入的日志及哈希链开始后的日志哈希为空时校验失败
+                if prev_hash.is_empty()
+                    && account_log.hash.is_empty()
+                    && account_log.id <= hash_cutover
+                {
+                    response.unhashed += 1;
+                    continue;
+                }
+                if !account_log.verify_hash(&prev_hash, hash_cutover) {
+                    response.broken_log_id = Some(account_log.id);
+                    return Ok(response);
+                }
+                prev_hash = account_log.hash;
+                response.verified += 1;
+            }
+        }
+        Ok(response)
+    }
+
+    // 返回交易id，本批次所有操作及手续费、系统账户对应分录的日志均记录该id
+    pub async fn actions(
+        repository: &impl Repository,
+        account_action_requests: &Vec<AccountActionRequest>,
+        operator: &Operator,
+    ) -> AppResult<i64> {
+        let started_at = Instant::now();
+        let result =
+            Self::process_actions(repository, account_action_requests, operator, false).await;
+        MetricService::record_actions_duration(started_at.elapsed(), result.is_ok());
+        let (transaction_id, _) = result?;
+        Self::record_actions(repository.cache(), account_action_requests, operator);
+        Ok(transaction_id)
+    }
+
+    // 部分成功模式：每个账户操作在同一事务内的独立保存点中处理，失败时只回滚该操作
+    // 参数错误、读取手续费规则或系统账户操作类型失败时整批失败
+    pub async fn actions_partial(
+        repository: &impl Repository,
+        account_action_requests: &Vec<AccountActionRequest>,
+        operator: &Operator,
+    ) -> AppResult<(i64, Vec<AccountActionResult>)> {
+        let started_at = Instant::now();
+        let result =
+            Self::process_actions_partial(repository, account_action_requests, operator, false)
+                .await;
+        MetricService::record_actions_duration(started_at.elapsed(), result.is_ok());
+        let (transaction_id, results, _) = result?;
+        Self::record_succeeded_actions(
+            repository.cache(),
+            account_action_requests,
+            &results,
+            operator,
+        );
+        Ok((
+            transaction_id,
+            results.into_iter().map(Self::action_result).collect(),
+        ))
+    }
+
+    // 预览账户操作：与实际操作的处理过程相同，但最后回滚事务，返回操作后的账户
+    // 整批处理时任一操作失败返回错误，部分成功模式返回每个操作的处理结果
+    pub async fn preview(
+        repository: &impl Repository,
+        account_action_requests: &Vec<AccountActionRequest>,
+        operator: &Operator,
+        mode: AccountActionMode,
+    ) -> AppResult<AccountActionsPreview> {
+        // 预览未实际执行操作，不计入被拒绝的次数
+        let (results, accounts) = MetricService::skip_rejects(async {
+            match mode {
+                AccountActionMode::Atomic => {
+                    let (_, accounts) =
+                        Self::process_actions(repository, account_action_requests, operator, true)
+                            .await?;
+                    let results = account_action_requests.iter().map(|_| Ok(())).collect();
+                    Ok::<_, Error>((results, accounts))
+                }
+                AccountActionMode::Partial => {
+                    let (_, results, accounts) = Self::process_actions_partial(
+                        repository,
+                        account_action_requests,
+                        operator,
+                        true,
+                    )
+                    .await?;
+                    Ok((results, accounts))
+                }
+            }
+        })
+        .await?;
+        Ok(AccountActionsPreview {
+            accounts,
+            results: results.into_iter().map(Self::action_result).collect(),
+        })
+    }
+
+    // 失败时的错误码与整批处理时该账户操作失败返回的 HTTP 状态码相同
+    fn action_result(result: AppResult<()>) -> AccountActionResult {
+        match result {
+            Ok(()) => AccountActionResult {
+                status: AccountActionStatus::Succeeded,
+                code: None,
+                message: None,
+            },
+            Err(err) => AccountActionResult {
+                status: AccountActionStatus::Failed,
+                message: Some(err.to_string()),
+                code: Some(err.into_response().status().as_u16()),
+            },
+        }
+    }
+
+    fn record_succeeded_actions(
+        cache: &ConfigCache,
+        account_action_requests: &[AccountActionRequest],
+        results: &[AppResult<()>],
+        operator: &Operator,
+    ) {
+        let succeeded: Vec<AccountActionRequest> = account_action_requests
+            .iter()
+            .zip(results)
+            .filter(|(_, result)| result.is_ok())
+            .map(|(account_action_request, _)| account_action_request.clone())
+            .collect();
+        Self::record_actions(cache, &succeeded, operator);
+    }
+
+    // 记录账户操作指标，需在事务提交后调用
+    pub fn record_actions(
+        cache: &ConfigCache,
+        account_action_requests: &[AccountActionRequest],
+        operator: &Operator,
+    ) {
+        for account_action_request in account_action_requests {
+            MetricService::record_action(
+                &AssetTypeService::name(
+                    cache,
+                    operator.tenant_id,
+                    account_action_request.asset_type_id,
+                ),
+                &ActionTypeService::name(
+                    cache,
+                    operator.tenant_id,
+                    account_action_request.action_type_id,
+                ),
+                account_action_request.amount,
+            );
+        }
+    }
+
+    // 返回交易id，`dry_run`为真时回滚事务并返回操作后的账户
+    async fn process_actions(
+        repository: &impl Repository,
+        account_action_requests: &Vec<AccountActionRequest>,
+        operator: &Operator,
+        dry_run: bool,
+    ) -> AppResult<(i64, Vec<AccountModel>)> {
+        account_action_requests.validate()?;
+        let actions = account_action_requests
+            .iter()
+            .map(|account_action_request| {
+                Self::action(
+                    repository.cache(),
+                    operator.tenant_id,
+                    account_action_request,
+                )
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+        let (tx, involved, limit_usages) =
+            Self::begin_actions(repository, &actions, operator).await?;
+        Self::complete_actions(tx, &involved, &limit_usages, operator, dry_run).await
+    }
+
+    // 整批处理账户操作但不提交事务，任一操作失败则整批失败
+    // 账户操作类型由调用方指定，兑换借此使用未启用、不能直接提交的账户操作类型
+    // 按规则收取手续费，开启事务前检查账户状态、余额及订单号，从而避免不必要的数据库操作开销
+    // 限额在事务内加锁后检查
+    // 返回未提交的事务、入账涉及的账户及本事务已使用的限额
+    // 调用方提交前调用`LimitRuleService::record`，提交后记录监控指标
+    pub async fn begin_actions<R: Repository>(
+        repository: &R,
+        actions: &[(AccountActionRequest, ActionTypeModel)],
+        operator: &Operator,
+    ) -> AppResult<(R::Transaction, Vec<(String, i32)>, LimitUsages)> {
+        let account_action_requests: Vec<AccountActionRequest> = actions
+            .iter()
+            .map(|(account_action_request, _)| account_action_request.clone())
+            .collect();
+        let fees =
+            FeeRuleService::fees(repository, operator.tenant_id, &account_action_requests).await?;
+        for (index, (account_action_request, action_type)) in actions.iter().enumerate() {
+            Self::check_action(
+                repository,
+                account_action_request,
+                action_type,
+                Self::fee(&fees, index),
+                operator,
+            )
+            .await?;
+        }
+        let system_action_types =
+            SystemAccountService::action_types(repository, operator.tenant_id).await?;
+        let movements = Self::movements(actions.iter().enumerate(), &fees);
+        let mut tx = repository.begin().await?;
+        let mut limit_usages =
+            LimitRuleService::lock(&mut tx, operator.tenant_id, &account_action_requests).await?;
+        LimitRuleService::check(repository.cache(), &limit_usages, &account_action_requests)?;
+        let counter_entries = Self::post_movements(
+            &mut tx,
+            system_action_types.as_ref(),
+            &movements,
+            operator,
+            None,
         )
         .await?;
-        Ok(accounts)
+        let involved = Self::involved(&movements, &counter_entries);
+        LimitRuleService::add(
+            repository.cache(),
+            &mut limit_usages,
+            &account_action_requests,
+        );
+        Ok((tx, involved, limit_usages))
     }
 
-    pub async fn actions(account_action_requests: &Vec<AccountActionRequest>) -> AppResult<()> {
+    // 按提交顺序返回每个账户操作的处理结果
+    // 开启事务前逐个检查，通过检查的操作在事务内各自的保存点中处理
+    async fn process_actions_partial(
+        repository: &impl Repository,
+        account_action_requests: &Vec<AccountActionRequest>,
+        operator: &Operator,
+        dry_run: bool,
+    ) -> AppResult<(i64, Vec<AppResult<()>>, Vec<AccountModel>)> {
         account_action_requests.validate()?;
-        // 开启事务前检查账户状态、余额是否充足以及订单号是否已处理，从而避免不必要的数据库操作开销
-        for account_action_request in account_action_requests {
-            let action_type =
-                ActionTypeService::by_id(account_action_request.action_type_id).unwrap();
-            let account = AccountModel::find(
-                postgres::conn(),
-                account_action_request.user_id,
+        let fees =
+            FeeRuleService::fees(repository, operator.tenant_id, account_action_requests).await?;
+        let mut results = Vec::with_capacity(account_action_requests.len());
+        for (index, account_action_request) in account_action_requests.iter().enumerate() {
+            let result = match Self::action(
+                repository.cache(),
+                operator.tenant_id,
+                account_action_request,
+            ) {
+                Ok((account_action_request, action_type)) => {
+                    Self::check_action(
+                        repository,
+                        &account_action_request,
+                        &action_type,
+                        Self::fee(&fees, index),
+                        operator,
+                    )
+                    .await
+                }
+                Err(err) => Err(err),
+            };
+            results.push(result);
+        }
+        let (tx, results, involved, limit_usages) = Self::post_partial(
+            repository,
+            account_action_requests,
+            &fees,
+            results,
+            operator,
+            None,
+        )
+        .await?;
+        let (transaction_id, accounts) =
+            Self::complete_actions(tx, &involved, &limit_usages, operator, dry_run).await?;
+        Ok((transaction_id, results, accounts))
+    }
+
+    // 部分成功模式处理账户操作但不提交事务，用于批量任务及导入历史数据
+    // 参数由调用方校验；不在开启事务前检查账户及余额，同一批次内前面的操作可为后面的操作提供余额
+    // `created_at`为空时日志时间为当前时间，否则按顺序与账户操作一一对应
+    // 调用方可在同一事务内记录处理结果，提交前调用`LimitRuleService::record`，提交后对成功的操作调用`record_actions`
+    pub async fn begin_actions_partial<R: Repository>(
+        repository: &R,
+        account_action_requests: &[AccountActionRequest],
+        created_at: Option<&[NaiveDateTime]>,
+        operator: &Operator,
+    ) -> AppResult<(R::Transaction, Vec<AppResult<()>>, LimitUsages)> {
+        let fees =
+            FeeRuleService::fees(repository, operator.tenant_id, account_action_requests).await?;
+        let results = account_action_requests.iter().map(|_| Ok(())).collect();
+        let (tx, results, _, limit_usages) = Self::post_partial(
+            repository,
+            account_action_requests,
+            &fees,
+            results,
+            operator,
+            created_at,
+        )
+        .await?;
+        Ok((tx, results, limit_usages))
+    }
+
+    // 对`results`中已通过检查的账户操作继续检查双边操作，之后在事务内各自的保存点中检查限额并入账
+    // `created_at`为空时日志时间为当前时间，否则按顺序与账户操作一一对应
+    // 返回未提交的事务、每个账户操作的处理结果、入账涉及的账户及本事务已使用的限额
+    async fn post_partial<R: Repository>(
+        repository: &R,
+        account_action_requests: &[AccountActionRequest],
+        fees: &Option<Fees>,
+        mut results: Vec<AppResult<()>>,
+        operator: &Operator,
+        created_at: Option<&[NaiveDateTime]>,
+    ) -> AppResult<(
+        R::Transaction,
+        Vec<AppResult<()>>,
+        Vec<(String, i32)>,
+        LimitUsages,
+    )> {
+        let system_action_types =
+            SystemAccountService::action_types(repository, operator.tenant_id).await?;
+        // 已通过检查的账户操作及其账户操作类型
+        let mut actions = Vec::with_capacity(account_action_requests.len());
+        for (index, account_action_request) in account_action_requests.iter().enumerate() {
+            let mut action = None;
+            if results[index].is_ok() {
+                match Self::action(
+                    repository.cache(),
+                    operator.tenant_id,
+                    account_action_request,
+                ) {
+                    Ok(resolved) => action = Some(resolved),
+                    Err(err) => results[index] = Err(err),
+                }
+            }
+            actions.push(action);
+        }
+        // 部分成功模式中每个账户操作连同手续费的双边操作需各自合计为零
+        for (index, action) in actions.iter().enumerate() {
+            if let Some(action) = action.as_ref().filter(|_| results[index].is_ok()) {
+                let movements = Self::movements([(index, action)], fees);
+                results[index] = SystemAccountService::check_balanced(
+                    repository.cache(),
+                    operator.tenant_id,
+                    &movements,
+                );
+            }
+        }
+        let checked: Vec<AccountActionRequest> = actions
+            .iter()
+            .zip(&results)
+            .filter(|(_, result)| result.is_ok())
+            .filter_map(|(action, _)| action.as_ref())
+            .map(|(account_action_request, _)| account_action_request.clone())
+            .collect();
+        let mut tx = repository.begin().await?;
+        let mut limit_usages =
+            LimitRuleService::lock(&mut tx, operator.tenant_id, &checked).await?;
+        // 先锁定所有用户账户，之后逐个入账时才锁定平台账户及系统账户
+        // 所有事务均按先用户账户、后平台账户的顺序加锁，避免死锁；保存点回滚不释放锁
+        for (index, account_action_request) in account_action_requests.iter().enumerate() {
+            if results[index].is_ok() {
+                if let Err(err) = tx
+                    .lock_account(
+                        operator.tenant_id,
+                        &account_action_request.user_id,
+                        account_action_request.asset_type_id,
+                    )
+                    .await
+                {
+                    results[index] = Err(err);
+                }
+            }
+        }
+        // 每个账户操作连同其手续费、平台账户入账及系统账户对应分录在同一保存点中处理
+        // 限额按已入账的操作与当前操作合计检查，超出限额、发行上限等失败只回滚该操作
+        let mut involved = Vec::new();
+        for (index, action) in actions.iter().enumerate() {
+            let Some(action) = action.as_ref().filter(|_| results[index].is_ok()) else {
+                continue;
+            };
+            let account_action_requests = slice::from_ref(&action.0);
+            if let Err(err) =
+                LimitRuleService::check(repository.cache(), &limit_usages, account_action_requests)
+            {
+                results[index] = Err(err);
+                continue;
+            }
+            let movements = Self::movements([(index, action)], fees);
+            tx.savepoint().await?;
+            match Self::post_movements(
+                &mut tx,
+                system_action_types.as_ref(),
+                &movements,
+                operator,
+                created_at.map(|created_at| created_at[index]),
+            )
+            .await
+            {
+                Ok(counter_entries) => {
+                    tx.release_savepoint().await?;
+                    LimitRuleService::add(
+                        repository.cache(),
+                        &mut limit_usages,
+                        account_action_requests,
+                    );
+                    involved.extend(Self::involved(&movements, &counter_entries));
+                }
+                Err(err) => {
+                    tx.rollback_to_savepoint().await?;
+                    results[index] = Err(err);
+                }
+            }
+        }
+        Ok((tx, results, involved, limit_usages))
+    }
+
+    fn fee(fees: &Option<Fees>, index: usize) -> Option<(&Fee, &ActionTypeModel)> {
+        let fees = fees.as_ref()?;
+        Some((fees.items[index].as_ref()?, &fees.expense))
+    }
+
+    // 账户操作及其已启用的账户操作类型，资产类型需已启用
+    fn action(
+        cache: &ConfigCache,
+        tenant_id: i32,
+        account_action_request: &AccountActionRequest,
+    ) -> AppResult<(AccountActionRequest, ActionTypeModel)> {
+        AssetTypeService::by_id(cache, tenant_id, account_action_request.asset_type_id)?;
+        let action_type =
+            ActionTypeService::by_id(cache, tenant_id, account_action_request.action_type_id)?;
+        Ok((account_action_request.clone(), action_type))
+    }
+
+    // 检查账户状态、余额是否充足以及订单号是否已处理
+    async fn check_action(
+        repository: &impl Repository,
+        account_action_request: &AccountActionRequest,
+        action_type: &ActionTypeModel,
+        fee: Option<(&Fee, &ActionTypeModel)>,
+        operator: &Operator,
+    ) -> AppResult<()> {
+        AssetTypeService::by_id(
+            repository.cache(),
+            operator.tenant_id,
+            account_action_request.asset_type_id,
+        )?;
+        let account = repository
+            .find_account(
+                operator.tenant_id,
+                &account_action_request.user_id,
                 account_action_request.asset_type_id,
             )
             .await?;
-            if !account.is_active {
-                return Err(Error::Custom(
-                    StatusCode::FORBIDDEN,
-                    "操作失败，存在未启用账户".to_string(),
-                ));
+        if !account.is_active {
+            MetricService::record_reject(RejectReason::InactiveAccount);
+            return Err(Error::Custom(
+                StatusCode::FORBIDDEN,
+                "操作失败，存在未启用账户".to_string(),
+            ));
+        }
+        // 操作前检查余额是否充足
+        let amount = Decimal::from_f64(account_action_request.amount.abs())
+            .unwrap()
+            .trunc_with_scale(6);
+        Self::check_balance_before_update(action_type, &account, amount).await?;
+        // 收取手续费时，可用余额加上本操作的变动还需足够支付手续费，增加可用余额的操作可从所得中支付
+        if let Some((fee, expense)) = fee {
+            let amount = fee.amount
+                - action_type
+                    .available_balance_change
+                    .calculate_change(account_action_request.amount);
+            if amount > Decimal::ZERO {
+                Self::check_balance_before_update(expense, &account, amount).await?;
+            }
+        }
+        Self::check_account_log_exists(
+            repository,
+            account.id,
+            action_type.id,
+            account_action_request.order_number.as_str(),
+        )
+        .await
+    }
+
+    // 按入账顺序排列账户操作及其手续费：用户账户操作、手续费扣减，最后平台账户入账
+    // 平台账户最后统一入账，所有事务均按先用户账户、后平台账户的顺序加锁，避免死锁
+    fn movements<'a>(
+        actions: impl IntoIterator<Item = (usize, &'a (AccountActionRequest, ActionTypeModel))>,
+        fees: &Option<Fees>,
+    ) -> Vec<(AccountActionRequest, ActionTypeModel)> {
+        let mut movements = Vec::new();
+        let mut credits = Vec::new();
+        for (index, action) in actions {
+            movements.push(action.clone());
+            if let (Some((fee, expense)), Some(fees)) = (Self::fee(fees, index), fees) {
+                movements.push((fee.debit.clone(), expense.clone()));
+                credits.push((fee.credit.clone(), fees.income.clone()));
             }
-            // 操作前检查余额是否充足
-            Self::check_balance_before_update(
+        }
+        movements.extend(credits);
+        movements
+    }
+
+    // 累加 Redis 中已使用的限额后提交事务，返回交易id，`dry_run`为真时按顺序返回涉及的账户并回滚事务
+    async fn complete_actions(
+        mut tx: impl RepositoryTransaction,
+        involved: &[(String, i32)],
+        limit_usages: &LimitUsages,
+        operator: &Operator,
+        dry_run: bool,
+    ) -> AppResult<(i64, Vec<AccountModel>)> {
+        let transaction_id = tx.transaction_id().await?;
+        if !dry_run {
+            LimitRuleService::record(limit_usages).await;
+            tx.commit().await?;
+            return Ok((transaction_id, Vec::new()));
+        }
+        let mut keys = HashSet::new();
+        let mut accounts = Vec::new();
+        for (user_id, asset_type_id) in involved {
+            if keys.insert((user_id, asset_type_id)) {
+                accounts.push(
+                    tx.lock_account(operator.tenant_id, user_id, *asset_type_id)
+                        .await?,
+                );
+            }
+        }
+        Ok((transaction_id, accounts))
+    }
+
+    // 在事务内依次变更账户余额，之后按资产类型的净额生成并记录系统账户的对应分录
+    // 复式记账资产类型的所有余额变更均需经过此处，`system_action_types`需在开启事务前读取
+    // `created_at`为空时日志时间为当前时间，返回生成的对应分录
+    pub async fn post_movements(
+        tx: &mut impl RepositoryTransaction,
+        system_action_types: Option<&SystemActionTypes>,
+        movements: &[(AccountActionRequest, ActionTypeModel)],
+        operator: &Operator,
+        created_at: Option<NaiveDateTime>,
+    ) -> AppResult<Vec<CounterEntry>> {
+        let counter_entries = SystemAccountService::counter_entries(
+            tx.cache(),
+            system_action_types,
+            operator.tenant_id,
+            movements,
+        )?;
+        for (account_action_request, action_type) in movements {
+            Self::post_entry(
+                tx,
+                &Entry::from(account_action_request),
                 action_type,
-                &account,
-                Decimal::from_f64(account_action_request.amount.abs())
-                    .unwrap()
-                    .trunc_with_scale(6),
+                operator,
+                true,
+                created_at,
             )
             .await?;
-            Self::check_account_log_exists(
-                account.id,
-                action_type.id,
-                account_action_request.order_number.as_str(),
+        }
+        // 发行系统账户余额为负数，不检查余额是否充足
+        for counter_entry in &counter_entries {
+            Self::post_entry(
+                tx,
+                &Entry::from(counter_entry),
+                &counter_entry.action_type,
+                operator,
+                false,
+                created_at,
             )
             .await?;
         }
-        let mut tx = postgres::conn().begin().await?;
-        for account_action_request in account_action_requests {
-            Self::update_balance(&mut tx, account_action_request).await?;
-        }
-        tx.commit().await?;
-        Ok(())
+        Ok(counter_entries)
     }
 
-    async fn update_balance(
-        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    // 入账涉及的账户，按入账顺序
+    fn involved(
+        movements: &[(AccountActionRequest, ActionTypeModel)],
+        counter_entries: &[CounterEntry],
+    ) -> Vec<(String, i32)> {
+        movements
+            .iter()
+            .map(|(account_action_request, _)| {
+                (
+                    account_action_request.user_id.clone(),
+                    account_action_request.asset_type_id,
+                )
+            })
+            .chain(
+                counter_entries.iter().map(|counter_entry| {
+                    (counter_entry.user_id.clone(), counter_entry.asset_type_id)
+                }),
+            )
+            .collect()
+    }
+
+    // 在事务内按账户操作类型变更账户余额并记录日志
+    // 账户操作类型由调用方传入，管理工具可借此使用未启用的修复类操作类型
+    // 账户所属租户即账户操作类型所属租户，返回变更后的账户
+    pub async fn update_balance(
+        tx: &mut impl RepositoryTransaction,
         account_action_request: &AccountActionRequest,
-    ) -> AppResult<()> {
+        action_type: &ActionTypeModel,
+        operator: &Operator,
+    ) -> AppResult<AccountModel> {
+        Self::post_entry(
+            tx,
+            &Entry::from(account_action_request),
+            action_type,
+            operator,
+            true,
+            None,
+        )
+        .await
+    }
+
+    // `check_balance`为假时允许扣减后余额为负数，仅用于系统账户
+    async fn post_entry(
+        tx: &mut impl RepositoryTransaction,
+        entry: &Entry<'_>,
+        action_type: &ActionTypeModel,
+        operator: &Operator,
+        check_balance: bool,
+        created_at: Option<NaiveDateTime>,
+    ) -> AppResult<AccountModel> {
         // 存在外部校验时间过长的可能，需要重新校验账户状态
         Self::check_account_is_active(
-            account_action_request.user_id,
-            account_action_request.asset_type_id,
+            tx,
+            action_type.tenant_id,
+            entry.user_id,
+            entry.asset_type_id,
         )
         .await?;
-        let amount = account_action_request.amount;
-        let action_type = ActionTypeService::by_id(account_action_request.action_type_id).unwrap();
-        let amount_available_balance = action_type
-            .available_balance_change
-            .calculate_change(amount);
-        let amount_frozen_balance = action_type.frozen_balance_change.calculate_change(amount);
-        let amount_total_income = action_type.total_income_change.calculate_change(amount);
-        let amount_total_expense = action_type.total_expense_change.calculate_change(amount);
-        let account = AccountModel::update_balance(
-            &mut **tx,
-            account_action_request.user_id,
-            account_action_request.asset_type_id,
+        let amount = entry.amount;
+        let amount_available_balance = action_type.available_balance_change.apply(amount);
+        let amount_frozen_balance = action_type.frozen_balance_change.apply(amount);
+        let amount_total_income = action_type.total_income_change.apply(amount);
+        let amount_total_expense = action_type.total_expense_change.apply(amount);
+        let amounts = [
             amount_available_balance,
             amount_frozen_balance,
             amount_total_income,
             amount_total_expense,
-        )
-        .await?;
+        ];
+        let account = tx
+            .update_balance(
+                action_type.tenant_id,
+                entry.user_id,
+                entry.asset_type_id,
+                amounts,
+            )
+            .await?;
         // 扣减`可用余额/冻结余额`时，不允许`可用余额/冻结余额`为负数
         // 增加`可用余额/冻结余额`时，允许`可用余额/冻结余额`为负数
         // 因为管理员可能直接操作数据库修改用户`可用余额/冻结余额`，所以只在扣减操作才判断
-        Self::check_balance_after_update(action_type, &account).await?;
-        AccountLogModel::create(
-            &mut **tx,
-            account.id,
+        if check_balance {
+            Self::check_balance_after_update(action_type, &account).await?;
+        }
+        // 资产类型按批次跟踪余额时，批次跟踪总余额(可用余额 + 冻结余额)
+        // 增加总余额产生新批次，扣减总余额按先进先出扣减批次
+        // 冻结、解冻不改变总余额，不影响批次及其过期时间
+        let amount_holding = amount_available_balance + amount_frozen_balance;
+        if amount_holding > Decimal::ZERO {
+            tx.create_balance_lot(&account, amount_holding, entry.order_number, created_at)
+                .await?;
+        } else if amount_holding < Decimal::ZERO {
+            tx.consume_balance_lots(account.id, -amount_holding).await?;
+        }
+        Self::change_supply(tx, &account, amount_holding).await?;
+        tx.create_account_log(
+            &account,
             action_type.id,
-            amount_available_balance,
-            amount_frozen_balance,
-            amount_total_income,
-            amount_total_expense,
-            account.available_balance,
-            account.frozen_balance,
-            account.total_income,
-            account.total_expense,
-            account_action_request.order_number.as_ref(),
-            account_action_request.description.as_ref(),
+            amounts,
+            entry.order_number,
+            entry.description,
+            operator.client_id,
+            operator.name.as_ref(),
+            created_at,
         )
         .await?;
+        Ok(account)
+    }
+
+    // 累加资产类型的流通量，发行、销毁系统账户不计入流通量
+    // 设置了发行上限的资产类型立即写入并检查，流通量包括已提交及本事务内之前的变更，超出时只回滚所在保存点
+    async fn change_supply(
+        tx: &mut impl RepositoryTransaction,
+        account: &AccountModel,
+        amount: Decimal,
+    ) -> AppResult<()> {
+        let asset_type =
+            AssetTypeService::by_id(tx.cache(), account.tenant_id, account.asset_type_id)?;
+        if amount.is_zero()
+            || asset_type.issuance_user_id.as_ref() == Some(&account.user_id)
+            || asset_type.burn_user_id.as_ref() == Some(&account.user_id)
+        {
+            return Ok(());
+        }
+        if asset_type.max_supply.is_none() {
+            return tx.add_asset_supply(asset_type.id, amount).await;
+        }
+        if !tx.add_capped_asset_supply(asset_type.id, amount).await? {
+            MetricService::record_reject(RejectReason::MaxSupplyExceeded);
+            return Err(Error::Custom(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "操作失败，超出资产发行上限".to_string(),
+            ));
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        model::asset_type::AssetTypeModel,
+        repository::{
+            account::AccountRepository, account_log::AccountLogRepository, memory::MemoryRepository,
+        },
+    };
+    use sqlx::types::chrono::Utc;
+
+    const TENANT_ID: i32 = 1;
+    const USER_ID: &str = "1";
+    const GOLD: i32 = 1;
+    const AB_INC: i32 = 1;
+    const AB_EXP: i32 = 3;
+
+    // 与`migrations`中的初始数据保持一致
+    fn repository() -> MemoryRepository {
+        let now = Utc::now().naive_utc();
+        let asset_types = ["GOLD", "SILVER", "COPPER"]
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| AssetTypeModel {
+                id: index as i32 + 1,
+                tenant_id: TENANT_ID,
+                name: name.to_string(),
+                description: String::new(),
+                lot_ttl_days: None,
+                issuance_user_id: None,
+                burn_user_id: None,
+                fee_user_id: None,
+                is_double_entry: false,
+                max_supply: None,
+                is_active: true,
+                created_at: now,
+                updated_at: now,
+            })
+            .collect();
+        let action_types = [
+            (
+                "AB_INC",
+                Change::Inc,
+                Change::None,
+                Change::Inc,
+                Change::None,
+            ),
+            (
+                "AB_INC_RTN",
+                Change::Dec,
+                Change::None,
+                Change::Dec,
+                Change::None,
+            ),
+            (
+                "AB_EXP",
+                Change::Dec,
+                Change::None,
+                Change::None,
+                Change::Inc,
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(
+            |(index, (name, available_balance, frozen_balance, total_income, total_expense))| {
+                ActionTypeModel {
+                    id: index as i32 + 1,
+                    tenant_id: TENANT_ID,
+                    name: name.to_string(),
+                    description: String::new(),
+                    available_balance_change: available_balance,
+                    frozen_balance_change: frozen_balance,
+                    total_income_change: total_income,
+                    total_expense_change: total_expense,
+                    is_single_sided: true,
+                    is_active: true,
+                    created_at: now,
+                    updated_at: now,
+                }
+            },
+        )
+        .collect();
+        MemoryRepository::new(asset_types, action_types)
+    }
+
+    async fn setup() -> MemoryRepository {
+        let repository = repository();
+        AssetTypeService::init(&repository).await.unwrap();
+        ActionTypeService::init(&repository).await.unwrap();
+        LimitRuleService::init(&repository, false).await.unwrap();
+        FeeRuleService::init(&repository).await.unwrap();
+        AccountService::create(
+            &repository,
+            TENANT_ID,
+            &AccountRequest {
+                user_id: USER_ID.to_string(),
+                asset_type_id: GOLD,
+            },
+        )
+        .await
+        .unwrap();
+        repository
+    }
+
+    fn request(action_type_id: i32, amount: f64, order_number: &str) -> AccountActionRequest {
+        AccountActionRequest {
+            user_id: USER_ID.to_string(),
+            asset_type_id: GOLD,
+            action_type_id,
+            amount,
+            order_number: format!("{order_number:0>32}"),
+            description: "test".to_string(),
+        }
+    }
+
+    fn operator() -> Operator {
+        Operator {
+            client_id: 1,
+            tenant_id: TENANT_ID,
+            name: "test".to_string(),
+        }
+    }
+
+    async fn available_balance(repository: &MemoryRepository) -> Decimal {
+        repository
+            .find_account(TENANT_ID, USER_ID, GOLD)
+            .await
+            .unwrap()
+            .available_balance
+    }
+
+    fn status<T>(result: AppResult<T>) -> StatusCode {
+        match result {
+            Err(Error::Custom(status, _)) => status,
+            _ => panic!("unexpected result"),
+        }
+    }
+
+    #[tokio::test]
+    async fn actions_update_balance_and_log() {
+        let repository = setup().await;
+        for requests in [
+            vec![request(AB_INC, 100.5, "1")],
+            vec![request(AB_EXP, 0.5, "2")],
+        ] {
+            AccountService::actions(&repository, &requests, &operator())
+                .await
+                .unwrap();
+        }
+        assert_eq!(available_balance(&repository).await, Decimal::from(100));
+        let verify = AccountService::verify_logs(
+            &repository,
+            TENANT_ID,
+            &AccountRequest {
+                user_id: USER_ID.to_string(),
+                asset_type_id: GOLD,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(verify.verified, 2);
+        assert_eq!(verify.broken_log_id, None);
+    }
+
+    #[tokio::test]
+    async fn actions_reject_insufficient_balance() {
+        let repository = setup().await;
+        let result =
+            AccountService::actions(&repository, &vec![request(AB_EXP, 1.0, "1")], &operator())
+                .await;
+        assert_eq!(status(result), StatusCode::PAYMENT_REQUIRED);
+        assert_eq!(available_balance(&repository).await, Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn actions_reject_duplicate_order() {
+        let repository = setup().await;
+        let requests = vec![request(AB_INC, 1.0, "1")];
+        AccountService::actions(&repository, &requests, &operator())
+            .await
+            .unwrap();
+        let result = AccountService::actions(&repository, &requests, &operator()).await;
+        assert_eq!(status(result), StatusCode::CONFLICT);
+        assert_eq!(available_balance(&repository).await, Decimal::ONE);
+    }
+
+    // 同一批次内重复的订单号在事务内才能发现，需整体回滚
+    #[tokio::test]
+    async fn actions_roll_back_whole_batch() {
+        let repository = setup().await;
+        let requests = vec![request(AB_INC, 1.0, "1"), request(AB_INC, 1.0, "1")];
+        let result = AccountService::actions(&repository, &requests, &operator()).await;
+        assert_eq!(status(result), StatusCode::CONFLICT);
+        assert_eq!(available_balance(&repository).await, Decimal::ZERO);
+        let account_logs = repository.fetch_account_logs(1, None, 10).await.unwrap();
+        assert!(account_logs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn actions_partial_roll_back_failed_action() {
+        let repository = setup().await;
+        let requests = vec![
+            request(AB_INC, 1.0, "1"),
+            request(AB_INC, 1.0, "1"),
+            request(AB_INC, 2.0, "2"),
+        ];
+        let (transaction_id, results) =
+            AccountService::actions_partial(&repository, &requests, &operator())
+                .await
+                .unwrap();
+        let codes: Vec<_> = results.iter().map(|result| result.code).collect();
+        assert_eq!(codes, [None, Some(409), None]);
+        assert_eq!(results[1].status, AccountActionStatus::Failed);
+        assert_eq!(available_balance(&repository).await, Decimal::from(3));
+        let account_logs = repository.fetch_account_logs(1, None, 10).await.unwrap();
+        assert_eq!(account_logs.len(), 2);
+        assert!(account_logs
+            .iter()
+            .all(|account_log| account_log.transaction_id == Some(transaction_id)));
+    }
+
+    #[tokio::test]
+    async fn actions_reject_inactive_account() {
+        let repository = setup().await;
+        let mut account = repository
+            .find_account(TENANT_ID, USER_ID, GOLD)
+            .await
+            .unwrap();
+        account.is_active = false;
+        repository.set_account(account).await;
+        let result =
+            AccountService::actions(&repository, &vec![request(AB_INC, 1.0, "1")], &operator())
+                .await;
+        assert_eq!(status(result), StatusCode::FORBIDDEN);
+    }
+
+    // 其他租户的资产类型、账户操作类型不可用
+    #[tokio::test]
+    async fn actions_reject_other_tenant() {
+        let repository = setup().await;
+        let operator = Operator {
+            tenant_id: TENANT_ID + 1,
+            ..operator()
+        };
+        let result =
+            AccountService::actions(&repository, &vec![request(AB_INC, 1.0, "1")], &operator).await;
+        assert_eq!(status(result), StatusCode::NOT_FOUND);
+        assert_eq!(available_balance(&repository).await, Decimal::ZERO);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{
+    model::{
+        account_log::AccountLogModel,
+        account_log_partition::{AccountLogPartitionModel, PartitionStatus},
+    },
+    repository::Repository,
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use sqlx::PgPool;
+use std::io;
+
+// 除当前月外提前创建分区的月数
+const PARTITION_MONTHS_AHEAD: u32 = 2;
+// 归档导出时每次读取的日志条数
+const ARCHIVE_CHUNK_SIZE: i64 = 1000;
+
+pub struct AccountLogPartitionService;
+
+impl AccountLogPartitionService {
+    // 创建`today`所在月及之后`PARTITION_MONTHS_AHEAD`个月的分区
+    // 默认分区中有日志的月份同样创建分区并移入这些日志，默认分区中的日志无法归档
+    pub async fn create_partitions(
+        repository: &impl Repository,
+        today: NaiveDate,
+    ) -> AppResult<()> {
+        for month in repository.fetch_account_log_default_months().await? {
+            repository.create_account_log_partition(month).await?;
+        }
+        let month = Self::month_start(today);
+        for offset in 0..=PARTITION_MONTHS_AHEAD {
+            if let Some(month) = month.checked_add_months(Months::new(offset)) {
+                repository.create_account_log_partition(month).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // 服务运行期间定时创建分区，保证写入日志时当月分区已存在
+    pub async fn sweep(repository: impl Repository, interval: std::time::Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let today = Utc::now().date_naive();
+            if let Err(err) = Self::create_partitions(&repository, today).await {
+                tracing::error!("Failed to create account log partitions: {err}");
+            }
+        }
+    }
+
+    // 结束时间不晚于`before`的分区，只能归档当前月之前的分区
+    pub async fn fetch_archivable(
+        pool: &PgPool,
+        before: NaiveDate,
+    ) -> AppResult<Vec<AccountLogPartitionModel>> {
+        if before > Self::month_start(Utc::now().date_naive()) {
+            return Err(Error::Custom(
+                StatusCode::BAD_REQUEST,
+                "只能归档当前月之前的分区".to_string(),
+            ));
+        }
+        AccountLogPartitionModel::fetch_archivable(pool, before.and_time(Default::default())).await
+    }
+
+    // 分离分区后按`id`正序将其中的日志导出为 JSON Lines，返回导出的日志条数
+    // `drop`为真时导出后删除分区表，否则保留分区表，可重新执行归档再次导出
+    pub async fn archive(
+        pool: &PgPool,
+        partition: &AccountLogPartitionModel,
+        mut writer: impl io::Write,
+        drop: bool,
+    ) -> AppResult<u64> {
+        if partition.status == PartitionStatus::Dropped {
+            return Ok(0);
+        }
+        AccountLogPartitionModel::detach(pool, &partition.name).await?;
+        let mut exported = 0;
+        let mut after_id = 0;
+        loop {
+            let account_logs = AccountLogModel::fetch_detached(
+                pool,
+                &partition.name,
+                after_id,
+                ARCHIVE_CHUNK_SIZE,
+            )
+            .await?;
+            let Some(last) = account_logs.last() else {
+                break;
+            };
+            after_id = last.id;
+            for account_log in &account_logs {
+                // 接口返回的日志不包括租户，归档时需保留
+                let mut value = serde_json::to_value(account_log).map_err(anyhow::Error::from)?;
+                value["tenant_id"] = account_log.tenant_id.into();
+                serde_json::to_writer(&mut writer, &value).map_err(anyhow::Error::from)?;
+                writer.write_all(b"\n").map_err(anyhow::Error::from)?;
+                exported += 1;
+            }
+        }
+        writer.flush().map_err(anyhow::Error::from)?;
+        if drop {
+            AccountLogPartitionModel::drop_table(pool, &partition.name).await?;
+        }
+        Ok(exported)
+    }
+
+    fn month_start(date: NaiveDate) -> NaiveDate {
+        date.with_day(1).unwrap_or(date)
+    }
+}
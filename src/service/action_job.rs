@@ -0,0 +1,298 @@
+use super::{account::AccountService, client::Operator, limit_rule::LimitRuleService};
+use crate::{
+    handler::{
+        account::AccountActionRequest,
+        action_job::{ActionJobReportRequest, ActionJobRequest},
+    },
+    model::action_job::{ActionJobItemModel, ActionJobItemStatus, ActionJobModel, ActionJobStatus},
+    repository::{action_job::ActionJobTransaction, Repository, TransactionControl},
+};
+use axum_kit::{error::Error, AppResult};
+use futures::{stream, Stream};
+use std::{io, time::Duration};
+use validator::Validate;
+
+// 提交任务时每次写入的明细条数
+const INSERT_CHUNK_SIZE: usize = 5000;
+// 处理中的任务超过该时间(秒)未更新时视为处理中断，由其他实例继续处理
+const LEASE_SECONDS: f64 = 600.0;
+// 订阅任务进度时查询任务的间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+// 导出任务明细时每次读取的条数
+const REPORT_CHUNK_SIZE: i64 = 1000;
+// 任务明细 CSV 表头，与`ActionJobItemModel`序列化的字段一致
+const REPORT_HEADERS: [&str; 9] = [
+    "seq",
+    "user_id",
+    "asset_type_id",
+    "action_type_id",
+    "amount",
+    "order_number",
+    "description",
+    "status",
+    "message",
+];
+
+pub struct ActionJobService;
+
+impl ActionJobService {
+    // 提交时只检查数量，账户操作在后台处理时逐条校验
+    pub async fn create(
+        repository: &impl Repository,
+        action_job_request: &ActionJobRequest,
+        operator: &Operator,
+    ) -> AppResult<ActionJobModel> {
+        action_job_request.validate()?;
+        let mut tx = repository.begin().await?;
+        let action_job = tx
+            .create_action_job(
+                operator.tenant_id,
+                action_job_request.chunk_size,
+                action_job_request.actions.len() as i32,
+                operator.client_id,
+                &operator.name,
+            )
+            .await?;
+        let items: Vec<ActionJobItemModel> = action_job_request
+            .actions
+            .iter()
+            .enumerate()
+            .map(|(seq, request)| ActionJobItemModel {
+                job_id: action_job.id,
+                seq: seq as i32,
+                user_id: request.user_id.clone(),
+                asset_type_id: request.asset_type_id,
+                action_type_id: request.action_type_id,
+                amount: request.amount,
+                order_number: request.order_number.clone(),
+                description: request.description.clone(),
+                status: ActionJobItemStatus::Pending,
+                message: String::new(),
+            })
+            .collect();
+        for chunk in items.chunks(INSERT_CHUNK_SIZE) {
+            tx.create_action_job_items(chunk).await?;
+        }
+        tx.commit().await?;
+        Ok(action_job)
+    }
+
+    pub async fn info(
+        repository: &impl Repository,
+        tenant_id: i32,
+        id: i64,
+    ) -> AppResult<ActionJobModel> {
+        repository.find_action_job(tenant_id, id).await
+    }
+
+    // 先输出当前进度，之后每次进度变化时输出，任务完成后结束
+    pub async fn progress<R>(
+        repository: R,
+        tenant_id: i32,
+        id: i64,
+    ) -> AppResult<impl Stream<Item = AppResult<ActionJobModel>> + Send + 'static>
+    where
+        R: Repository + 'static,
+    {
+        repository.find_action_job(tenant_id, id).await?;
+        Ok(stream::unfold(
+            Some((repository, None)),
+            move |state| async move {
+                let (repository, last) = state?;
+                loop {
+                    let action_job = match repository.find_action_job(tenant_id, id).await {
+                        Ok(action_job) => action_job,
+                        Err(err) => return Some((Err(err), None)),
+                    };
+                    let progress = Some((
+                        action_job.status,
+                        action_job.succeeded_count,
+                        action_job.failed_count,
+                    ));
+                    if progress != last {
+                        let state = (action_job.status != ActionJobStatus::Completed)
+                            .then_some((repository, progress));
+                        return Some((Ok(action_job), state));
+                    }
+                    tokio::time::sleep(PROGRESS_INTERVAL).await;
+                }
+            },
+        ))
+    }
+
+    // 按`seq`正序输出任务明细及处理结果(CSV)，任务未完成时待处理的明细状态为`PENDING`
+    pub async fn report<R>(
+        repository: R,
+        tenant_id: i32,
+        action_job_report_request: &ActionJobReportRequest,
+    ) -> AppResult<impl Stream<Item = io::Result<Vec<u8>>> + Send + 'static>
+    where
+        R: Repository + 'static,
+    {
+        let action_job = repository
+            .find_action_job(tenant_id, action_job_report_request.id)
+            .await?;
+        let status = action_job_report_request.status;
+        Ok(stream::unfold(
+            Some((repository, -1, true)),
+            move |state| async move {
+                let (repository, after_seq, has_headers) = state?;
+                let result = repository
+                    .fetch_action_job_items(action_job.id, status, after_seq, REPORT_CHUNK_SIZE)
+                    .await
+                    .and_then(|items| {
+                        let last_seq = items.last().map(|item| item.seq);
+                        Ok((Self::write_report(&items, has_headers)?, last_seq))
+                    });
+                match result {
+                    // 没有明细时只输出表头
+                    Ok((chunk, Some(last_seq))) => {
+                        Some((Ok(chunk), Some((repository, last_seq, false))))
+                    }
+                    Ok((chunk, None)) if has_headers => Some((Ok(chunk), None)),
+                    Ok((_, None)) => None,
+                    Err(err) => {
+                        tracing::error!("Failed to export action job report: {err}");
+                        Some((Err(io::Error::other(err.to_string())), None))
+                    }
+                }
+            },
+        ))
+    }
+
+    fn write_report(items: &[ActionJobItemModel], has_headers: bool) -> AppResult<Vec<u8>> {
+        let mut buffer = Vec::new();
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(&mut buffer);
+        if has_headers {
+            writer
+                .write_record(REPORT_HEADERS)
+                .map_err(anyhow::Error::from)?;
+        }
+        for item in items {
+            writer.serialize(item).map_err(anyhow::Error::from)?;
+        }
+        writer.flush().map_err(anyhow::Error::from)?;
+        drop(writer);
+        Ok(buffer)
+    }
+
+    // 定时领取并处理任务，同一实例内任务依次处理
+    pub async fn work(repository: impl Repository, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            loop {
+                match repository.claim_action_job(LEASE_SECONDS).await {
+                    Ok(Some(action_job)) => {
+                        if let Err(err) = Self::process(&repository, &action_job).await {
+                            tracing::error!(
+                                "Failed to process action job {}: {err}",
+                                action_job.id
+                            );
+                            break;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(err) => {
+                        tracing::error!("Failed to claim action job: {err}");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    // 按块处理待处理的明细，每块的处理结果随该块一同提交，处理中断后从未记录结果的明细继续
+    pub async fn process(
+        repository: &impl Repository,
+        action_job: &ActionJobModel,
+    ) -> AppResult<ActionJobModel> {
+        let operator = Operator {
+            client_id: action_job.client_id,
+            tenant_id: action_job.tenant_id,
+            name: action_job.operator.clone(),
+        };
+        loop {
+            let items = repository
+                .fetch_action_job_items(
+                    action_job.id,
+                    Some(ActionJobItemStatus::Pending),
+                    -1,
+                    action_job.chunk_size as i64,
+                )
+                .await?;
+            if items.is_empty() {
+                break;
+            }
+            Self::process_chunk(repository, action_job.id, &items, &operator).await?;
+        }
+        let action_job = repository.complete_action_job(action_job.id).await?;
+        tracing::info!(
+            "Action job {} completed: {} succeeded, {} failed",
+            action_job.id,
+            action_job.succeeded_count,
+            action_job.failed_count
+        );
+        Ok(action_job)
+    }
+
+    // 一块账户操作按部分成功模式在同一事务内处理，每个操作在各自的保存点中执行，只有出错的操作失败
+    // 处理结果与余额变更在同一事务内提交，处理中断后不会重复处理已提交的明细
+    // 数据库临时错误时中止处理且不记录结果，由任务租约过期后重新处理
+    async fn process_chunk(
+        repository: &impl Repository,
+        job_id: i64,
+        items: &[ActionJobItemModel],
+        operator: &Operator,
+    ) -> AppResult<()> {
+        let mut succeeded = Vec::new();
+        let mut failed = Vec::new();
+        // 参数错误的操作直接失败，其余操作在同一事务内处理
+        let mut pending = Vec::with_capacity(items.len());
+        let mut requests = Vec::with_capacity(items.len());
+        for item in items {
+            let request = Self::request(item);
+            match request.validate() {
+                Ok(()) => {
+                    pending.push(item.seq);
+                    requests.push(request);
+                }
+                Err(err) => failed.push((item.seq, err.to_string())),
+            }
+        }
+        let (mut tx, results, limit_usages) =
+            AccountService::begin_actions_partial(repository, &requests, None, operator).await?;
+        let mut succeeded_requests = Vec::new();
+        for ((seq, request), result) in pending.into_iter().zip(requests).zip(results) {
+            match result {
+                Ok(()) => {
+                    succeeded.push(seq);
+                    succeeded_requests.push(request);
+                }
+                Err(Error::Sqlx(err)) if !matches!(err, sqlx::Error::RowNotFound) => {
+                    return Err(err.into())
+                }
+                Err(err) => failed.push((seq, err.to_string())),
+            }
+        }
+        tx.update_action_job_results(job_id, &succeeded, &failed)
+            .await?;
+        LimitRuleService::record(&limit_usages).await;
+        tx.commit().await?;
+        AccountService::record_actions(repository.cache(), &succeeded_requests, operator);
+        Ok(())
+    }
+
+    fn request(item: &ActionJobItemModel) -> AccountActionRequest {
+        AccountActionRequest {
+            user_id: item.user_id.clone(),
+            asset_type_id: item.asset_type_id,
+            action_type_id: item.action_type_id,
+            amount: item.amount,
+            order_number: item.order_number.clone(),
+            description: item.description.clone(),
+        }
+    }
+}
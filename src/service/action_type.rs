@@ -1,35 +1,117 @@
-use crate::model::action_type::ActionTypeModel;
-use axum_kit::{postgres, AppResult};
-use std::sync::OnceLock;
-
-static ACTION_TYPE: OnceLock<Vec<ActionTypeModel>> = OnceLock::new();
+use crate::{
+    model::action_type::ActionTypeModel,
+    repository::{cache::ConfigCache, Repository},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use std::{collections::HashMap, sync::PoisonError};
 
 pub struct ActionTypeService;
 
 impl ActionTypeService {
-    pub async fn init() -> AppResult<()> {
-        let action_types = ActionTypeModel::fetch_all(postgres::conn()).await?;
-        let _ = ACTION_TYPE
-            .set(action_types)
-            .map_err(|_| "Failed to initialize ACTION_TYPE");
+    pub async fn init(repository: &impl Repository) -> AppResult<()> {
+        let mut action_types: HashMap<i32, Vec<ActionTypeModel>> = HashMap::new();
+        for action_type in repository.fetch_action_types().await? {
+            action_types
+                .entry(action_type.tenant_id)
+                .or_default()
+                .push(action_type);
+        }
+        *repository
+            .cache()
+            .action_types
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(action_types);
         Ok(())
     }
 
-    pub fn list() -> &'static Vec<ActionTypeModel> {
-        ACTION_TYPE.get().expect("ACTION_TYPE is not initialized")
+    pub fn is_initialized(cache: &ConfigCache) -> bool {
+        cache
+            .action_types
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    // 在读锁内查找某租户已启用的账户操作类型，租户没有账户操作类型时为空
+    fn with_list<T>(
+        cache: &ConfigCache,
+        tenant_id: i32,
+        f: impl FnOnce(&[ActionTypeModel]) -> T,
+    ) -> AppResult<T> {
+        let action_types = cache
+            .action_types
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        let action_types = action_types.as_ref().ok_or_else(|| {
+            Error::Custom(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "账户操作类型未初始化".to_string(),
+            )
+        })?;
+        Ok(f(action_types.get(&tenant_id).map_or(&[], Vec::as_slice)))
+    }
+
+    pub fn list(cache: &ConfigCache, tenant_id: i32) -> AppResult<Vec<ActionTypeModel>> {
+        Self::with_list(cache, tenant_id, <[ActionTypeModel]>::to_vec)
+    }
+
+    pub fn is_active(cache: &ConfigCache, tenant_id: i32, id: i32) -> bool {
+        Self::by_id(cache, tenant_id, id).is_ok()
+    }
+
+    pub fn by_id(cache: &ConfigCache, tenant_id: i32, id: i32) -> AppResult<ActionTypeModel> {
+        Self::with_list(cache, tenant_id, |action_types| {
+            action_types
+                .iter()
+                .find(|&action_type| action_type.id == id)
+                .cloned()
+        })?
+        .ok_or_else(|| Error::Custom(StatusCode::NOT_FOUND, "账户操作类型不存在".to_string()))
+    }
+
+    pub fn by_name(cache: &ConfigCache, tenant_id: i32, name: &str) -> AppResult<ActionTypeModel> {
+        Self::with_list(cache, tenant_id, |action_types| {
+            action_types
+                .iter()
+                .find(|&action_type| action_type.name == name)
+                .cloned()
+        })?
+        .ok_or_else(|| Error::Custom(StatusCode::NOT_FOUND, "账户操作类型不存在".to_string()))
+    }
+
+    // 账户操作类型名称，不存在时为空字符串
+    pub fn name(cache: &ConfigCache, tenant_id: i32, id: i32) -> String {
+        Self::by_id(cache, tenant_id, id)
+            .map_or_else(|_| String::new(), |action_type| action_type.name)
     }
 
-    #[allow(dead_code)]
-    pub fn is_active(id: i32) -> bool {
-        let action_types = Self::list();
-        action_types.iter().any(|action_type| action_type.id == id)
+    // 某租户所有账户操作类型的名称，包括未启用、不在缓存中的账户操作类型
+    pub async fn names(
+        repository: &impl Repository,
+        tenant_id: i32,
+    ) -> AppResult<HashMap<i32, String>> {
+        let action_types = repository
+            .fetch_action_types_including_inactive(tenant_id)
+            .await?;
+        Ok(action_types
+            .into_iter()
+            .map(|action_type| (action_type.id, action_type.name))
+            .collect())
     }
 
-    #[allow(dead_code)]
-    pub fn by_id(id: i32) -> Option<&'static ActionTypeModel> {
-        let action_types = Self::list();
-        action_types
-            .iter()
-            .find(|&action_type| action_type.id == id)
+    // 按名称从存储读取账户操作类型，包括未启用、不在缓存中的账户操作类型
+    pub async fn find_by_name(
+        repository: &impl Repository,
+        tenant_id: i32,
+        name: &str,
+    ) -> AppResult<ActionTypeModel> {
+        match repository.find_action_type(tenant_id, name).await {
+            Err(Error::Sqlx(sqlx::Error::RowNotFound)) => Err(Error::Custom(
+                StatusCode::NOT_FOUND,
+                "账户操作类型不存在".to_string(),
+            )),
+            result => result,
+        }
     }
 }
@@ -1,33 +1,102 @@
-use crate::model::asset_type::AssetTypeModel;
-use axum_kit::{postgres, AppResult};
-use std::sync::OnceLock;
-
-static ASSET_TYPE: OnceLock<Vec<AssetTypeModel>> = OnceLock::new();
+use crate::{
+    model::{asset_supply::AssetSupplyModel, asset_type::AssetTypeModel},
+    repository::{cache::ConfigCache, Repository},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use std::{collections::HashMap, sync::PoisonError};
 
 pub struct AssetTypeService;
 
 impl AssetTypeService {
-    pub async fn init() -> AppResult<()> {
-        let asset_types = AssetTypeModel::fetch_all(postgres::conn()).await?;
-        let _ = ASSET_TYPE
-            .set(asset_types)
-            .map_err(|_| "Failed to initialize ASSET_TYPE");
+    pub async fn init(repository: &impl Repository) -> AppResult<()> {
+        let mut asset_types: HashMap<i32, Vec<AssetTypeModel>> = HashMap::new();
+        for asset_type in repository.fetch_asset_types().await? {
+            asset_types
+                .entry(asset_type.tenant_id)
+                .or_default()
+                .push(asset_type);
+        }
+        *repository
+            .cache()
+            .asset_types
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(asset_types);
         Ok(())
     }
 
-    pub fn list() -> &'static Vec<AssetTypeModel> {
-        ASSET_TYPE.get().expect("ASSET_TYPE is not initialized")
+    pub fn is_initialized(cache: &ConfigCache) -> bool {
+        cache
+            .asset_types
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    // 在读锁内查找某租户已启用的资产类型，租户没有资产类型时为空
+    fn with_list<T>(
+        cache: &ConfigCache,
+        tenant_id: i32,
+        f: impl FnOnce(&[AssetTypeModel]) -> T,
+    ) -> AppResult<T> {
+        let asset_types = cache
+            .asset_types
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        let asset_types = asset_types.as_ref().ok_or_else(|| {
+            Error::Custom(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "资产类型未初始化".to_string(),
+            )
+        })?;
+        Ok(f(asset_types.get(&tenant_id).map_or(&[], Vec::as_slice)))
+    }
+
+    pub fn list(cache: &ConfigCache, tenant_id: i32) -> AppResult<Vec<AssetTypeModel>> {
+        Self::with_list(cache, tenant_id, <[AssetTypeModel]>::to_vec)
+    }
+
+    pub fn is_active(cache: &ConfigCache, tenant_id: i32, id: i32) -> bool {
+        Self::by_id(cache, tenant_id, id).is_ok()
+    }
+
+    pub fn by_id(cache: &ConfigCache, tenant_id: i32, id: i32) -> AppResult<AssetTypeModel> {
+        Self::with_list(cache, tenant_id, |asset_types| {
+            asset_types
+                .iter()
+                .find(|&asset_type| asset_type.id == id)
+                .cloned()
+        })?
+        .ok_or_else(|| Error::Custom(StatusCode::NOT_FOUND, "资产类型不存在".to_string()))
+    }
+
+    pub fn by_name(cache: &ConfigCache, tenant_id: i32, name: &str) -> AppResult<AssetTypeModel> {
+        Self::with_list(cache, tenant_id, |asset_types| {
+            asset_types
+                .iter()
+                .find(|&asset_type| asset_type.name == name)
+                .cloned()
+        })?
+        .ok_or_else(|| Error::Custom(StatusCode::NOT_FOUND, "资产类型不存在".to_string()))
+    }
+
+    // 资产类型名称，不存在时为空字符串
+    pub fn name(cache: &ConfigCache, tenant_id: i32, id: i32) -> String {
+        Self::by_id(cache, tenant_id, id)
+            .map_or_else(|_| String::new(), |asset_type| asset_type.name)
     }
 
-    #[allow(dead_code)]
-    pub fn is_active(id: i32) -> bool {
-        let asset_types = Self::list();
-        asset_types.iter().any(|asset_type| asset_type.id == id)
+    pub fn ids(cache: &ConfigCache, tenant_id: i32) -> AppResult<Vec<i32>> {
+        Self::with_list(cache, tenant_id, |asset_types| {
+            asset_types.iter().map(|asset_type| asset_type.id).collect()
+        })
     }
 
-    #[allow(dead_code)]
-    pub fn ids() -> Vec<i32> {
-        let asset_types = Self::list();
-        asset_types.iter().map(|asset_type| asset_type.id).collect()
+    // 当前租户已启用资产类型的流通量统计
+    pub async fn supplies(
+        repository: &impl Repository,
+        tenant_id: i32,
+    ) -> AppResult<Vec<AssetSupplyModel>> {
+        repository.fetch_asset_supplies(tenant_id).await
     }
 }
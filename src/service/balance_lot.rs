@@ -0,0 +1,126 @@
+use super::{
+    account::AccountService, action_type::ActionTypeService, client::Operator,
+    system_account::SystemAccountService,
+};
+use crate::{
+    handler::account::AccountActionRequest,
+    model::account::AccountModel,
+    repository::{
+        account::AccountTransaction, balance_lot::BalanceLotTransaction, Repository,
+        TransactionControl,
+    },
+};
+use axum_kit::AppResult;
+use num_traits::ToPrimitive;
+use sqlx::types::Decimal;
+
+// 余额批次过期专用的账户操作类型名称，见`migrations/0014_balance_lot.sql`
+const LOT_EXPIRE: &str = "LOT_EXP";
+
+// 过期任务每页处理的账户数量及操作人
+const EXPIRE_BATCH_SIZE: i64 = 100;
+const EXPIRE_OPERATOR: &str = "lot-expirer";
+
+pub struct BalanceLotService;
+
+impl BalanceLotService {
+    // 按`LOT_EXP`扣减所有租户过期批次的剩余金额，返回处理的账户数量
+    // 单个账户处理失败(如账户已停用)时跳过，不影响其他账户
+    // 按账户 id 分页遍历，每页`limit`个账户，处理失败的账户不会阻塞之后的账户
+    pub async fn expire(repository: &impl Repository, limit: i64) -> AppResult<usize> {
+        let mut expired = 0;
+        let mut after_id = 0;
+        loop {
+            let accounts = repository
+                .fetch_accounts_with_expired_balance_lots(after_id, limit)
+                .await?;
+            for account in &accounts {
+                match Self::expire_account(repository, account).await {
+                    Ok(true) => expired += 1,
+                    Ok(false) => {}
+                    Err(err) => {
+                        tracing::warn!(
+                            "Failed to expire balance lots of account {}: {err}",
+                            account.id
+                        );
+                    }
+                }
+            }
+            match accounts.last() {
+                Some(account) if accounts.len() as i64 == limit => after_id = account.id,
+                _ => return Ok(expired),
+            }
+        }
+    }
+
+    // 定期扣减过期批次，不会返回
+    pub async fn sweep(repository: impl Repository, interval: std::time::Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match Self::expire(&repository, EXPIRE_BATCH_SIZE).await {
+                Ok(0) => {}
+                Ok(expired) => tracing::info!("Expired balance lots of {expired} accounts"),
+                Err(err) => tracing::error!("Failed to expire balance lots: {err}"),
+            }
+        }
+    }
+
+    // 先锁定账户再读取过期批次，保证读取到的剩余金额与扣减时一致
+    // 过期批次最早过期，按先进先出扣减时恰好扣减这些批次
+    // 批次跟踪总余额，过期金额中冻结的部分超出可用余额，待解冻后再扣减
+    async fn expire_account(
+        repository: &impl Repository,
+        account: &AccountModel,
+    ) -> AppResult<bool> {
+        let lot_expire =
+            ActionTypeService::find_by_name(repository, account.tenant_id, LOT_EXPIRE).await?;
+        let system_action_types =
+            SystemAccountService::action_types(repository, account.tenant_id).await?;
+        let mut tx = repository.begin().await?;
+        let account = tx
+            .lock_account(account.tenant_id, &account.user_id, account.asset_type_id)
+            .await?;
+        let lots = tx.fetch_expired_balance_lots(account.id).await?;
+        let expired: Decimal = lots.iter().map(|lot| lot.remaining_amount).sum();
+        let amount = expired.min(account.available_balance);
+        if amount <= Decimal::ZERO {
+            return Ok(false);
+        }
+        // 按先进先出找到本次扣减的最后一个批次及其扣减后的剩余金额
+        // 同一批次每次被扣减后剩余金额都会减少，二者组合在账户内唯一
+        let mut rest = amount;
+        let mut last = (0, Decimal::ZERO);
+        for lot in &lots {
+            let consumed = rest.min(lot.remaining_amount);
+            last = (lot.id, lot.remaining_amount - consumed);
+            rest -= consumed;
+            if rest.is_zero() {
+                break;
+            }
+        }
+        let account_action_request = AccountActionRequest {
+            user_id: account.user_id.clone(),
+            asset_type_id: account.asset_type_id,
+            action_type_id: lot_expire.id,
+            amount: amount.to_f64().unwrap(),
+            order_number: format!("lot-expire-{}-{}", last.0, last.1.normalize()),
+            description: "余额批次过期".to_string(),
+        };
+        let operator = Operator {
+            client_id: 0,
+            tenant_id: account.tenant_id,
+            name: EXPIRE_OPERATOR.to_string(),
+        };
+        AccountService::post_movements(
+            &mut tx,
+            system_action_types.as_ref(),
+            &[(account_action_request, lot_expire)],
+            &operator,
+            None,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,23 @@
+use crate::{handler::change_log::ChangeLogsRequest, model::change_log::ChangeLogModel};
+use axum_kit::{postgres, AppResult};
+use validator::Validate;
+
+pub struct ChangeLogService;
+
+impl ChangeLogService {
+    pub async fn list(
+        tenant_id: i32,
+        change_logs_request: &ChangeLogsRequest,
+    ) -> AppResult<Vec<ChangeLogModel>> {
+        change_logs_request.validate()?;
+        let change_logs = ChangeLogModel::fetch_page(
+            postgres::conn(),
+            tenant_id,
+            change_logs_request.table_name.as_deref(),
+            change_logs_request.before_log_id,
+            change_logs_request.limit,
+        )
+        .await?;
+        Ok(change_logs)
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{
+    model::client::ClientModel,
+    repository::{cache::ConfigCache, Repository},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use sha2::{Digest, Sha256};
+use std::sync::PoisonError;
+
+// 发起请求的客户端、所属租户及操作人，由认证中间件写入请求扩展
+#[derive(Clone, Debug)]
+pub struct Operator {
+    pub client_id: i32,
+    pub tenant_id: i32,
+    pub name: String,
+}
+
+pub struct ClientService;
+
+impl ClientService {
+    pub async fn init(repository: &impl Repository) -> AppResult<()> {
+        let clients = repository.fetch_clients().await?;
+        *repository
+            .cache()
+            .clients
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(clients);
+        Ok(())
+    }
+
+    pub fn is_initialized(cache: &ConfigCache) -> bool {
+        cache
+            .clients
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    pub fn by_api_key(cache: &ConfigCache, api_key: &str) -> AppResult<ClientModel> {
+        let api_key_hash = hex::encode(Sha256::digest(api_key.as_bytes()));
+        let clients = cache.clients.read().unwrap_or_else(PoisonError::into_inner);
+        let clients = clients.as_ref().ok_or_else(|| {
+            Error::Custom(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "客户端未初始化".to_string(),
+            )
+        })?;
+        clients
+            .iter()
+            .find(|&client| client.api_key_hash == api_key_hash)
+            .cloned()
+            .ok_or_else(|| Error::Custom(StatusCode::UNAUTHORIZED, "认证失败".to_string()))
+    }
+}
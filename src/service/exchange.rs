@@ -0,0 +1,132 @@
+use super::{
+    account::AccountService, action_type::ActionTypeService, asset_type::AssetTypeService,
+    client::Operator, limit_rule::LimitRuleService, metric::MetricService,
+};
+use crate::{
+    handler::{account::AccountActionRequest, exchange::ExchangeRequest},
+    model::exchange_log::ExchangeLogModel,
+    repository::{exchange::ExchangeTransaction, Repository, TransactionControl},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use num_traits::{FromPrimitive, ToPrimitive};
+use sqlx::types::Decimal;
+use validator::Validate;
+
+// 兑换专用的账户操作类型名称，见`migrations/0012_exchange.sql`
+const EXCHANGE_OUT: &str = "EXC_OUT";
+const EXCHANGE_IN: &str = "EXC_IN";
+
+pub struct ExchangeService;
+
+impl ExchangeService {
+    // 在同一事务内扣减源资产账户并增加目标资产账户，两条账户操作日志使用同一订单号
+    pub async fn exchange(
+        repository: &impl Repository,
+        exchange_request: &ExchangeRequest,
+        operator: &Operator,
+    ) -> AppResult<ExchangeLogModel> {
+        exchange_request.validate()?;
+        let tenant_id = operator.tenant_id;
+        if exchange_request.from_asset_type_id == exchange_request.to_asset_type_id {
+            return Err(Error::Custom(
+                StatusCode::BAD_REQUEST,
+                "源资产类型与目标资产类型不能相同".to_string(),
+            ));
+        }
+        AssetTypeService::by_id(
+            repository.cache(),
+            tenant_id,
+            exchange_request.from_asset_type_id,
+        )?;
+        AssetTypeService::by_id(
+            repository.cache(),
+            tenant_id,
+            exchange_request.to_asset_type_id,
+        )?;
+        let exchange_rate = repository
+            .find_exchange_rate(
+                tenant_id,
+                exchange_request.from_asset_type_id,
+                exchange_request.to_asset_type_id,
+            )
+            .await
+            .map_err(|err| match err {
+                Error::Sqlx(sqlx::Error::RowNotFound) => {
+                    Error::Custom(StatusCode::NOT_FOUND, "汇率不存在".to_string())
+                }
+                err => err,
+            })?;
+        let from_amount = Decimal::from_f64(exchange_request.amount)
+            .ok_or_else(amount_out_of_range)?
+            .trunc_with_scale(6);
+        let to_amount = from_amount
+            .checked_mul(exchange_rate.applied_rate())
+            .ok_or_else(amount_out_of_range)?
+            .trunc_with_scale(6);
+        if to_amount <= Decimal::ZERO {
+            return Err(Error::Custom(
+                StatusCode::BAD_REQUEST,
+                "兑换金额过小".to_string(),
+            ));
+        }
+        let exchange_out =
+            ActionTypeService::find_by_name(repository, tenant_id, EXCHANGE_OUT).await?;
+        let exchange_in =
+            ActionTypeService::find_by_name(repository, tenant_id, EXCHANGE_IN).await?;
+        // 兑换的两条账户操作与`/accounts/actions`相同，按规则检查限额并收取手续费
+        // 目标资产的手续费可从兑换所得中扣减
+        let mut actions = Vec::with_capacity(2);
+        for (asset_type_id, action_type, amount) in [
+            (
+                exchange_request.from_asset_type_id,
+                exchange_out,
+                from_amount,
+            ),
+            (exchange_request.to_asset_type_id, exchange_in, to_amount),
+        ] {
+            let account_action_request = AccountActionRequest {
+                user_id: exchange_request.user_id.clone(),
+                asset_type_id,
+                action_type_id: action_type.id,
+                amount: amount.to_f64().ok_or_else(amount_out_of_range)?,
+                order_number: exchange_request.order_number.clone(),
+                description: exchange_request.description.clone(),
+            };
+            actions.push((account_action_request, action_type));
+        }
+        let (mut tx, _, limit_usages) =
+            AccountService::begin_actions(repository, &actions, operator).await?;
+        let exchange_log = tx
+            .create_exchange_log(
+                &exchange_request.user_id,
+                &exchange_rate,
+                from_amount,
+                to_amount,
+                &exchange_request.order_number,
+                &exchange_request.description,
+                operator.client_id,
+                &operator.name,
+            )
+            .await?;
+        LimitRuleService::record(&limit_usages).await;
+        tx.commit().await?;
+        // 兑换专用的账户操作类型未启用，不在缓存中，按已读取的名称记录
+        for (account_action_request, action_type) in &actions {
+            MetricService::record_action(
+                &AssetTypeService::name(
+                    repository.cache(),
+                    tenant_id,
+                    account_action_request.asset_type_id,
+                ),
+                &action_type.name,
+                account_action_request.amount,
+            );
+        }
+        Ok(exchange_log)
+    }
+}
+
+fn amount_out_of_range() -> Error {
+    Error::Custom(StatusCode::BAD_REQUEST, "兑换金额超出范围".to_string())
+}
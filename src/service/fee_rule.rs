@@ -0,0 +1,178 @@
+use super::{action_type::ActionTypeService, asset_type::AssetTypeService};
+use crate::{
+    handler::account::AccountActionRequest,
+    model::{action_type::ActionTypeModel, fee_rule::FeeRuleModel},
+    repository::{cache::ConfigCache, Repository},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use num_traits::{FromPrimitive, ToPrimitive};
+use sqlx::types::Decimal;
+use std::{collections::HashMap, sync::PoisonError};
+
+// 用户支付手续费的账户操作类型
+const FEE_EXPENSE: &str = "FEE_EXP";
+// 平台账户收取手续费的账户操作类型
+const FEE_INCOME: &str = "FEE_INC";
+
+// 一次批量操作中需收取的手续费
+pub struct Fees {
+    pub expense: ActionTypeModel,
+    pub income: ActionTypeModel,
+    // 按请求顺序排列，不收取手续费的请求为空
+    pub items: Vec<Option<Fee>>,
+}
+
+pub struct Fee {
+    pub amount: Decimal,
+    // 从用户账户扣减手续费
+    pub debit: AccountActionRequest,
+    // 平台账户收取手续费
+    pub credit: AccountActionRequest,
+}
+
+pub struct FeeRuleService;
+
+impl FeeRuleService {
+    pub async fn init(repository: &impl Repository) -> AppResult<()> {
+        let mut fee_rules: HashMap<i32, Vec<FeeRuleModel>> = HashMap::new();
+        for fee_rule in repository.fetch_fee_rules().await? {
+            fee_rules
+                .entry(fee_rule.tenant_id)
+                .or_default()
+                .push(fee_rule);
+        }
+        *repository
+            .cache()
+            .fee_rules
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(fee_rules);
+        Ok(())
+    }
+
+    pub fn is_initialized(cache: &ConfigCache) -> bool {
+        cache
+            .fee_rules
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    // 资产类型及账户操作类型对应的已启用手续费规则
+    pub fn find(
+        cache: &ConfigCache,
+        tenant_id: i32,
+        asset_type_id: i32,
+        action_type_id: i32,
+    ) -> AppResult<Option<FeeRuleModel>> {
+        let fee_rules = cache
+            .fee_rules
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        let fee_rules = fee_rules.as_ref().ok_or_else(|| {
+            Error::Custom(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "手续费规则未初始化".to_string(),
+            )
+        })?;
+        Ok(fee_rules
+            .get(&tenant_id)
+            .map_or(&[][..], Vec::as_slice)
+            .iter()
+            .find(|fee_rule| {
+                fee_rule.asset_type_id == asset_type_id && fee_rule.action_type_id == action_type_id
+            })
+            .cloned())
+    }
+
+    // 按规则计算本批次各操作的手续费，没有需收取的手续费时返回空
+    // 手续费扣减及平台账户入账的订单号为`fee-<user_id>-<账户操作类型id>-<原订单号>`
+    // 避免不同用户、不同账户操作类型的相同订单号在手续费日志中冲突
+    pub async fn fees(
+        repository: &impl Repository,
+        tenant_id: i32,
+        account_action_requests: &[AccountActionRequest],
+    ) -> AppResult<Option<Fees>> {
+        let mut items = Vec::with_capacity(account_action_requests.len());
+        for account_action_request in account_action_requests {
+            let fee_rule = Self::find(
+                repository.cache(),
+                tenant_id,
+                account_action_request.asset_type_id,
+                account_action_request.action_type_id,
+            )?;
+            let fee = fee_rule.and_then(|fee_rule| {
+                let amount = fee_rule.calculate(
+                    Decimal::from_f64(account_action_request.amount)
+                        .unwrap()
+                        .trunc_with_scale(6),
+                );
+                (amount > Decimal::ZERO).then_some((fee_rule, amount))
+            });
+            items.push(fee);
+        }
+        if items.iter().all(Option::is_none) {
+            return Ok(None);
+        }
+        let expense = ActionTypeService::find_by_name(repository, tenant_id, FEE_EXPENSE).await?;
+        let income = ActionTypeService::find_by_name(repository, tenant_id, FEE_INCOME).await?;
+        let mut fees = Vec::with_capacity(items.len());
+        for (fee, account_action_request) in items.into_iter().zip(account_action_requests) {
+            let Some((fee_rule, amount)) = fee else {
+                fees.push(None);
+                continue;
+            };
+            let house_user_id = Self::house_user_id(repository.cache(), tenant_id, &fee_rule)?;
+            let order_number = format!(
+                "fee-{}-{}-{}",
+                account_action_request.user_id,
+                account_action_request.action_type_id,
+                account_action_request.order_number
+            );
+            fees.push(Some(Fee {
+                amount,
+                debit: AccountActionRequest {
+                    user_id: account_action_request.user_id.clone(),
+                    asset_type_id: account_action_request.asset_type_id,
+                    action_type_id: expense.id,
+                    amount: amount.to_f64().unwrap(),
+                    order_number: order_number.clone(),
+                    description: fee_rule.description.clone(),
+                },
+                credit: AccountActionRequest {
+                    user_id: house_user_id,
+                    asset_type_id: account_action_request.asset_type_id,
+                    action_type_id: income.id,
+                    amount: amount.to_f64().unwrap(),
+                    order_number,
+                    description: fee_rule.description.clone(),
+                },
+            }));
+        }
+        Ok(Some(Fees {
+            expense,
+            income,
+            items: fees,
+        }))
+    }
+
+    // 规则未指定收取账户时使用资产类型的手续费系统账户
+    fn house_user_id(
+        cache: &ConfigCache,
+        tenant_id: i32,
+        fee_rule: &FeeRuleModel,
+    ) -> AppResult<String> {
+        if let Some(house_user_id) = &fee_rule.house_user_id {
+            return Ok(house_user_id.clone());
+        }
+        AssetTypeService::by_id(cache, tenant_id, fee_rule.asset_type_id)?
+            .fee_user_id
+            .clone()
+            .ok_or_else(|| {
+                Error::Custom(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "手续费规则未配置收取账户".to_string(),
+                )
+            })
+    }
+}
@@ -0,0 +1,40 @@
+use super::{
+    action_type::ActionTypeService, asset_type::AssetTypeService, client::ClientService,
+    fee_rule::FeeRuleService, limit_rule::LimitRuleService,
+};
+use crate::{handler::health::ReadinessResponse, repository::cache::ConfigCache};
+use axum_kit::postgres;
+use std::time::Duration;
+use tokio::time::timeout;
+
+// 单项依赖检查超时时间
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub struct HealthService;
+
+impl HealthService {
+    pub async fn check_postgres() -> bool {
+        let ping = sqlx::query("select 1").execute(postgres::conn());
+        matches!(timeout(CHECK_TIMEOUT, ping).await, Ok(Ok(_)))
+    }
+
+    pub async fn check_redis() -> bool {
+        let mut conn = axum_kit::redis::conn().clone();
+        let cmd = redis::cmd("PING");
+        let ping = cmd.query_async::<String>(&mut conn);
+        matches!(timeout(CHECK_TIMEOUT, ping).await, Ok(Ok(_)))
+    }
+
+    pub async fn readiness(cache: &ConfigCache) -> ReadinessResponse {
+        let (postgres, redis) = tokio::join!(Self::check_postgres(), Self::check_redis());
+        ReadinessResponse {
+            postgres,
+            redis,
+            asset_type: AssetTypeService::is_initialized(cache),
+            action_type: ActionTypeService::is_initialized(cache),
+            client: ClientService::is_initialized(cache),
+            limit_rule: LimitRuleService::is_initialized(cache),
+            fee_rule: FeeRuleService::is_initialized(cache),
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use super::{
+    account::AccountService,
+    action_type::ActionTypeService,
+    asset_type::AssetTypeService,
+    client::Operator,
+    metric::{MetricService, RejectReason},
+    system_account::SystemAccountService,
+};
+use crate::{
+    handler::{
+        account::AccountActionRequest,
+        hold::{HoldCaptureRequest, HoldReferenceRequest, HoldReleaseRequest, HoldRequest},
+    },
+    model::{
+        action_type::ActionTypeModel,
+        hold::{HoldModel, HoldStatus},
+    },
+    repository::{hold::HoldTransaction, Repository, RepositoryTransaction, TransactionControl},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use num_traits::{FromPrimitive, ToPrimitive};
+use sqlx::types::Decimal;
+use validator::Validate;
+
+// 预授权使用的账户操作类型名称
+const FREEZE: &str = "FRZ";
+const CAPTURE: &str = "FB_EXP";
+const UNFREEZE: &str = "UFZ";
+
+// 过期释放任务每次处理的预授权数量及操作人
+const SWEEP_BATCH_SIZE: i64 = 100;
+const SWEEP_OPERATOR: &str = "hold-sweeper";
+
+pub struct HoldService;
+
+impl HoldService {
+    // 按`FRZ`冻结金额并创建预授权，冻结操作的订单号为预授权的外部引用
+    pub async fn create(
+        repository: &impl Repository,
+        hold_request: &HoldRequest,
+        operator: &Operator,
+    ) -> AppResult<HoldModel> {
+        hold_request.validate()?;
+        let tenant_id = operator.tenant_id;
+        AssetTypeService::by_id(repository.cache(), tenant_id, hold_request.asset_type_id)?;
+        let freeze = ActionTypeService::find_by_name(repository, tenant_id, FREEZE).await?;
+        let amount = Decimal::from_f64(hold_request.amount)
+            .unwrap()
+            .trunc_with_scale(6);
+        // 开启事务前检查账户状态及余额是否充足
+        let account = repository
+            .find_account(tenant_id, &hold_request.user_id, hold_request.asset_type_id)
+            .await?;
+        if !account.is_active {
+            MetricService::record_reject(RejectReason::InactiveAccount);
+            return Err(Error::Custom(
+                StatusCode::FORBIDDEN,
+                "操作失败，存在未启用账户".to_string(),
+            ));
+        }
+        AccountService::check_balance_before_update(&freeze, &account, amount).await?;
+        // 先创建预授权，并发创建时由唯一约束拒绝，冻结操作订单号重复时同样视为预授权已存在
+        let mut tx = repository.begin().await?;
+        let hold = tx
+            .create_hold(
+                &account,
+                &hold_request.reference,
+                amount,
+                hold_request.expires_in,
+                &hold_request.description,
+                operator.client_id,
+                &operator.name,
+            )
+            .await?;
+        let account_action_request = AccountActionRequest {
+            user_id: hold_request.user_id.clone(),
+            asset_type_id: hold_request.asset_type_id,
+            action_type_id: freeze.id,
+            amount: hold_request.amount,
+            order_number: hold_request.reference.clone(),
+            description: hold_request.description.clone(),
+        };
+        AccountService::update_balance(&mut tx, &account_action_request, &freeze, operator)
+            .await
+            .map_err(|err| match err {
+                Error::Custom(StatusCode::CONFLICT, _) => {
+                    Error::Custom(StatusCode::CONFLICT, "预授权已存在".to_string())
+                }
+                err => err,
+            })?;
+        tx.commit().await?;
+        Ok(hold)
+    }
+
+    pub async fn info(
+        repository: &impl Repository,
+        tenant_id: i32,
+        hold_reference_request: &HoldReferenceRequest,
+    ) -> AppResult<HoldModel> {
+        hold_reference_request.validate()?;
+        repository
+            .find_hold(tenant_id, &hold_reference_request.reference)
+            .await
+            .map_err(hold_not_found)
+    }
+
+    // 按`FB_EXP`从冻结余额扣款，剩余金额全部扣除后预授权结束
+    pub async fn capture(
+        repository: &impl Repository,
+        hold_capture_request: &HoldCaptureRequest,
+        operator: &Operator,
+    ) -> AppResult<HoldModel> {
+        hold_capture_request.validate()?;
+        let capture =
+            ActionTypeService::find_by_name(repository, operator.tenant_id, CAPTURE).await?;
+        let system_action_types =
+            SystemAccountService::action_types(repository, operator.tenant_id).await?;
+        let mut tx = repository.begin().await?;
+        let hold =
+            Self::lock_held(&mut tx, operator.tenant_id, &hold_capture_request.reference).await?;
+        if tx.is_hold_expired(&hold).await? {
+            return Err(Error::Custom(
+                StatusCode::CONFLICT,
+                "预授权已过期".to_string(),
+            ));
+        }
+        let remaining_amount = hold.remaining_amount();
+        let amount = match hold_capture_request.amount {
+            Some(amount) => Decimal::from_f64(amount).unwrap().trunc_with_scale(6),
+            None => remaining_amount,
+        };
+        if amount > remaining_amount {
+            return Err(Error::Custom(
+                StatusCode::BAD_REQUEST,
+                "扣款金额超出预授权剩余金额".to_string(),
+            ));
+        }
+        // 扣款减少总余额，复式记账资产类型计入销毁系统账户
+        let account_action_request = Self::request(
+            &hold,
+            &capture,
+            amount,
+            &hold_capture_request.order_number,
+            &hold_capture_request.description,
+        );
+        AccountService::post_movements(
+            &mut tx,
+            system_action_types.as_ref(),
+            &[(account_action_request, capture)],
+            operator,
+            None,
+        )
+        .await?;
+        let status = if amount == remaining_amount {
+            HoldStatus::Captured
+        } else {
+            HoldStatus::Held
+        };
+        let hold = tx
+            .update_hold(
+                hold.id,
+                hold.captured_amount + amount,
+                hold.released_amount,
+                status,
+            )
+            .await?;
+        tx.commit().await?;
+        Ok(hold)
+    }
+
+    // 按`UFZ`释放剩余金额，预授权结束
+    pub async fn release(
+        repository: &impl Repository,
+        hold_release_request: &HoldReleaseRequest,
+        operator: &Operator,
+    ) -> AppResult<HoldModel> {
+        hold_release_request.validate()?;
+        let unfreeze =
+            ActionTypeService::find_by_name(repository, operator.tenant_id, UNFREEZE).await?;
+        let mut tx = repository.begin().await?;
+        let hold =
+            Self::lock_held(&mut tx, operator.tenant_id, &hold_release_request.reference).await?;
+        let hold = Self::release_remaining(
+            &mut tx,
+            &hold,
+            &unfreeze,
+            HoldStatus::Released,
+            &hold_release_request.description,
+            operator,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(hold)
+    }
+
+    // 释放所有租户已过期的预授权，返回释放的数量
+    // 单个预授权释放失败(如账户已停用)时跳过，不影响其他预授权
+    pub async fn release_expired(repository: &impl Repository, limit: i64) -> AppResult<usize> {
+        let mut released = 0;
+        for hold in repository.fetch_expired_holds(limit).await? {
+            let operator = Operator {
+                client_id: 0,
+                tenant_id: hold.tenant_id,
+                name: SWEEP_OPERATOR.to_string(),
+            };
+            match Self::release_expired_hold(repository, &hold, &operator).await {
+                Ok(true) => released += 1,
+                Ok(false) => {}
+                Err(err) => {
+                    tracing::warn!("Failed to release expired hold {}: {err}", hold.id);
+                }
+            }
+        }
+        Ok(released)
+    }
+
+    // 定期释放过期的预授权，不会返回
+    pub async fn sweep(repository: impl Repository, interval: std::time::Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match Self::release_expired(&repository, SWEEP_BATCH_SIZE).await {
+                Ok(0) => {}
+                Ok(released) => tracing::info!("Released {released} expired holds"),
+                Err(err) => tracing::error!("Failed to release expired holds: {err}"),
+            }
+        }
+    }
+
+    // 加锁后预授权可能已被扣款或释放，此时跳过
+    async fn release_expired_hold(
+        repository: &impl Repository,
+        hold: &HoldModel,
+        operator: &Operator,
+    ) -> AppResult<bool> {
+        let unfreeze =
+            ActionTypeService::find_by_name(repository, hold.tenant_id, UNFREEZE).await?;
+        let mut tx = repository.begin().await?;
+        let hold = tx
+            .find_hold_for_update(hold.tenant_id, &hold.reference)
+            .await?;
+        if hold.status != HoldStatus::Held {
+            return Ok(false);
+        }
+        Self::release_remaining(
+            &mut tx,
+            &hold,
+            &unfreeze,
+            HoldStatus::Expired,
+            "预授权过期自动释放",
+            operator,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    // 锁定仍处于冻结中的预授权
+    async fn lock_held(
+        tx: &mut impl RepositoryTransaction,
+        tenant_id: i32,
+        reference: &str,
+    ) -> AppResult<HoldModel> {
+        let hold = tx
+            .find_hold_for_update(tenant_id, reference)
+            .await
+            .map_err(hold_not_found)?;
+        if hold.status != HoldStatus::Held {
+            return Err(Error::Custom(
+                StatusCode::CONFLICT,
+                "预授权已结束".to_string(),
+            ));
+        }
+        Ok(hold)
+    }
+
+    // 剩余金额只释放一次，释放操作的订单号为预授权的外部引用
+    async fn release_remaining(
+        tx: &mut impl RepositoryTransaction,
+        hold: &HoldModel,
+        unfreeze: &ActionTypeModel,
+        status: HoldStatus,
+        description: &str,
+        operator: &Operator,
+    ) -> AppResult<HoldModel> {
+        let remaining_amount = hold.remaining_amount();
+        Self::update_balance(
+            tx,
+            hold,
+            unfreeze,
+            remaining_amount,
+            &hold.reference,
+            description,
+            operator,
+        )
+        .await?;
+        tx.update_hold(
+            hold.id,
+            hold.captured_amount,
+            hold.released_amount + remaining_amount,
+            status,
+        )
+        .await
+    }
+
+    // 冻结、释放不改变总余额，不生成系统账户的对应分录
+    async fn update_balance(
+        tx: &mut impl RepositoryTransaction,
+        hold: &HoldModel,
+        action_type: &ActionTypeModel,
+        amount: Decimal,
+        order_number: &str,
+        description: &str,
+        operator: &Operator,
+    ) -> AppResult<()> {
+        let account_action_request =
+            Self::request(hold, action_type, amount, order_number, description);
+        AccountService::update_balance(tx, &account_action_request, action_type, operator).await?;
+        Ok(())
+    }
+
+    fn request(
+        hold: &HoldModel,
+        action_type: &ActionTypeModel,
+        amount: Decimal,
+        order_number: &str,
+        description: &str,
+    ) -> AccountActionRequest {
+        AccountActionRequest {
+            user_id: hold.user_id.clone(),
+            asset_type_id: hold.asset_type_id,
+            action_type_id: action_type.id,
+            amount: amount.to_f64().unwrap(),
+            order_number: order_number.to_string(),
+            description: description.to_string(),
+        }
+    }
+}
+
+fn hold_not_found(err: Error) -> Error {
+    match err {
+        Error::Sqlx(sqlx::Error::RowNotFound) => {
+            Error::Custom(StatusCode::NOT_FOUND, "预授权不存在".to_string())
+        }
+        err => err,
+    }
+}
@@ -0,0 +1,289 @@
+use super::{
+    account::AccountService, action_type::ActionTypeService, asset_type::AssetTypeService,
+    client::Operator, limit_rule::LimitRuleService,
+};
+use crate::{
+    handler::account::{validate_user_id, AccountActionRequest},
+    model::action_type::ActionTypeModel,
+    repository::{cache::ConfigCache, Repository, TransactionControl},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use num_traits::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use sqlx::types::{chrono::NaiveDateTime, Decimal};
+use std::{collections::HashMap, io};
+
+// 未提供说明时记录到账户操作日志的说明
+const DEFAULT_DESCRIPTION: &str = "历史数据导入";
+
+// 导入文件中的一行，`created_at`为原始时间(UTC)，格式`YYYY-MM-DDTHH:MM:SS`
+#[derive(Deserialize)]
+pub struct ImportRow {
+    pub user_id: String,
+    pub asset_type: String,
+    pub action_type: String,
+    pub amount: Decimal,
+    pub order_number: String,
+    pub created_at: NaiveDateTime,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStatus {
+    Imported,
+    // 订单已导入或与文件中前面的行重复
+    Skipped,
+    Failed,
+}
+
+// 导入报告中的一行，`line`为导入文件中的行号(表头为第 1 行)
+#[derive(Serialize, Clone, Debug)]
+pub struct ImportReport {
+    pub line: u64,
+    pub order_number: String,
+    pub status: ImportStatus,
+    pub message: String,
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct ImportSummary {
+    pub imported: u64,
+    pub skipped: u64,
+    pub failed: u64,
+}
+
+// 已通过校验、待导入的行
+struct ImportEntry {
+    line: u64,
+    request: AccountActionRequest,
+    action_type: ActionTypeModel,
+    created_at: NaiveDateTime,
+}
+
+pub struct ImportService;
+
+impl ImportService {
+    // 逐行校验导入文件，每`chunk_size`行在一个事务内导入，报告按行号顺序写入`report`
+    // 与部分成功模式的账户操作相同，收取手续费、检查限额及发行上限，每行在各自的保存点中导入，只有出错的行导入失败
+    // 已导入的订单跳过，导入中断后可使用同一文件重新导入
+    pub async fn import(
+        repository: &impl Repository,
+        reader: impl io::Read,
+        report: impl io::Write,
+        chunk_size: usize,
+        operator: &Operator,
+    ) -> AppResult<ImportSummary> {
+        let mut csv_reader = csv::Reader::from_reader(reader);
+        let mut report_writer = csv::Writer::from_writer(report);
+        let mut summary = ImportSummary::default();
+        let mut chunk = Vec::with_capacity(chunk_size);
+        for (index, row) in csv_reader.deserialize::<ImportRow>().enumerate() {
+            let line = index as u64 + 2;
+            let entry = row
+                .map_err(|err| Error::Custom(StatusCode::BAD_REQUEST, err.to_string()))
+                .and_then(|row| Self::validate(repository.cache(), operator.tenant_id, line, row));
+            chunk.push(entry.map_err(|err| ImportReport {
+                line,
+                order_number: String::new(),
+                status: ImportStatus::Failed,
+                message: err.to_string(),
+            }));
+            if chunk.len() >= chunk_size {
+                for report in Self::import_chunk(repository, &chunk, operator).await {
+                    Self::write_report(&mut report_writer, &mut summary, report)?;
+                }
+                chunk.clear();
+            }
+        }
+        for report in Self::import_chunk(repository, &chunk, operator).await {
+            Self::write_report(&mut report_writer, &mut summary, report)?;
+        }
+        report_writer.flush().map_err(anyhow::Error::from)?;
+        Ok(summary)
+    }
+
+    fn validate(
+        cache: &ConfigCache,
+        tenant_id: i32,
+        line: u64,
+        row: ImportRow,
+    ) -> AppResult<ImportEntry> {
+        let invalid = |message: &str| Error::Custom(StatusCode::BAD_REQUEST, message.to_string());
+        if row.user_id.is_empty()
+            || row.user_id.len() > 64
+            || validate_user_id(&row.user_id).is_err()
+        {
+            return Err(invalid("无效的用户id"));
+        }
+        if row.order_number.is_empty() {
+            return Err(invalid("订单号不能为空"));
+        }
+        let asset_type = AssetTypeService::by_name(cache, tenant_id, &row.asset_type)?;
+        let action_type = ActionTypeService::by_name(cache, tenant_id, &row.action_type)?;
+        if row.amount <= Decimal::ZERO || row.amount.scale() > 6 {
+            return Err(invalid("无效的金额(需大于0，最多6位小数)"));
+        }
+        // 余额变更经过`f64`，超出其精度的金额不能原样导入
+        let amount = row.amount.to_f64().unwrap_or_default();
+        if Decimal::from_f64(amount).map(|decimal| decimal.trunc_with_scale(6)) != Some(row.amount)
+        {
+            return Err(invalid("金额超出精度范围"));
+        }
+        Ok(ImportEntry {
+            line,
+            request: AccountActionRequest {
+                user_id: row.user_id,
+                asset_type_id: asset_type.id,
+                action_type_id: action_type.id,
+                amount,
+                order_number: row.order_number,
+                description: row
+                    .description
+                    .filter(|description| !description.is_empty())
+                    .unwrap_or_else(|| DEFAULT_DESCRIPTION.to_string()),
+            },
+            action_type,
+            created_at: row.created_at,
+        })
+    }
+
+    async fn import_chunk(
+        repository: &impl Repository,
+        chunk: &[Result<ImportEntry, ImportReport>],
+        operator: &Operator,
+    ) -> Vec<ImportReport> {
+        let mut reports = Vec::with_capacity(chunk.len());
+        let mut pending: Vec<&ImportEntry> = Vec::with_capacity(chunk.len());
+        // 同一块内的订单：账户、账户操作类型及订单号相同的行只导入第一行
+        let mut orders = HashMap::new();
+        for entry in chunk {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(report) => {
+                    reports.push(report.clone());
+                    continue;
+                }
+            };
+            let request = &entry.request;
+            let order = (
+                request.user_id.as_str(),
+                request.asset_type_id,
+                request.action_type_id,
+                request.order_number.as_str(),
+            );
+            if let Some(line) = orders.get(&order) {
+                let message = format!("与第 {line} 行订单重复");
+                reports.push(Self::report(entry, ImportStatus::Skipped, message));
+                continue;
+            }
+            orders.insert(order, entry.line);
+            match Self::prepare(repository, operator.tenant_id, entry).await {
+                Ok(true) => pending.push(entry),
+                Ok(false) => reports.push(Self::report(entry, ImportStatus::Skipped, "")),
+                Err(err) => {
+                    reports.push(Self::report(entry, ImportStatus::Failed, err.to_string()))
+                }
+            }
+        }
+        if !pending.is_empty() {
+            let requests: Vec<AccountActionRequest> =
+                pending.iter().map(|entry| entry.request.clone()).collect();
+            let created_at: Vec<NaiveDateTime> =
+                pending.iter().map(|entry| entry.created_at).collect();
+            match Self::apply(repository, &requests, &created_at, operator).await {
+                Ok(results) => {
+                    for (entry, result) in pending.iter().zip(results) {
+                        reports.push(match result {
+                            Ok(()) => Self::report(entry, ImportStatus::Imported, ""),
+                            Err(err) => Self::report(entry, ImportStatus::Failed, err.to_string()),
+                        });
+                    }
+                }
+                Err(err) => reports.extend(
+                    pending
+                        .iter()
+                        .map(|entry| Self::report(entry, ImportStatus::Failed, err.to_string())),
+                ),
+            }
+        }
+        reports.sort_by_key(|report| report.line);
+        reports
+    }
+
+    // 账户不存在时创建账户，订单已导入时返回假
+    async fn prepare(
+        repository: &impl Repository,
+        tenant_id: i32,
+        entry: &ImportEntry,
+    ) -> AppResult<bool> {
+        let request = &entry.request;
+        let account = match repository
+            .find_account(tenant_id, &request.user_id, request.asset_type_id)
+            .await
+        {
+            Err(Error::Sqlx(sqlx::Error::RowNotFound)) => {
+                repository
+                    .create_account(tenant_id, &request.user_id, request.asset_type_id)
+                    .await?
+            }
+            result => result?,
+        };
+        Ok(!repository
+            .is_account_log_exists(account.id, entry.action_type.id, &request.order_number)
+            .await)
+    }
+
+    // 返回每行的导入结果，日志时间为各行的原始时间
+    async fn apply(
+        repository: &impl Repository,
+        requests: &[AccountActionRequest],
+        created_at: &[NaiveDateTime],
+        operator: &Operator,
+    ) -> AppResult<Vec<AppResult<()>>> {
+        let (tx, results, limit_usages) =
+            AccountService::begin_actions_partial(repository, requests, Some(created_at), operator)
+                .await?;
+        LimitRuleService::record(&limit_usages).await;
+        tx.commit().await?;
+        let succeeded: Vec<AccountActionRequest> = requests
+            .iter()
+            .zip(&results)
+            .filter(|(_, result)| result.is_ok())
+            .map(|(request, _)| request.clone())
+            .collect();
+        AccountService::record_actions(repository.cache(), &succeeded, operator);
+        Ok(results)
+    }
+
+    fn report(
+        entry: &ImportEntry,
+        status: ImportStatus,
+        message: impl Into<String>,
+    ) -> ImportReport {
+        ImportReport {
+            line: entry.line,
+            order_number: entry.request.order_number.clone(),
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn write_report(
+        report_writer: &mut csv::Writer<impl io::Write>,
+        summary: &mut ImportSummary,
+        report: ImportReport,
+    ) -> AppResult<()> {
+        match report.status {
+            ImportStatus::Imported => summary.imported += 1,
+            ImportStatus::Skipped => summary.skipped += 1,
+            ImportStatus::Failed => summary.failed += 1,
+        }
+        report_writer
+            .serialize(&report)
+            .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+}
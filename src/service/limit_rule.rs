@@ -0,0 +1,351 @@
+use super::{
+    action_type::ActionTypeService,
+    metric::{MetricService, RejectReason},
+};
+use crate::{
+    handler::account::AccountActionRequest,
+    model::limit_rule::{LimitRuleModel, LimitUsage},
+    repository::{
+        cache::{ConfigCache, LimitRules},
+        Repository, RepositoryTransaction,
+    },
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use chrono::{DateTime, NaiveDateTime};
+use num_traits::{FromPrimitive, ToPrimitive};
+use redis::RedisResult;
+use sqlx::types::Decimal;
+use std::{collections::HashMap, sync::PoisonError};
+
+// Redis 中金额按最小精度(6位小数)的整数保存，保证累加结果精确
+const AMOUNT_SCALE: i64 = 1_000_000;
+
+// 仅在计数已存在时累加，计数不存在时由下一次检查从数据库重新统计
+const INCREMENT_SCRIPT: &str = r#"
+if redis.call('EXISTS', KEYS[1]) == 1 then
+    redis.call('HINCRBY', KEYS[1], 'count', ARGV[1])
+    redis.call('HINCRBY', KEYS[1], 'amount', ARGV[2])
+end
+return 1
+"#;
+
+// 事务内已使用的限额，按规则及用户记录窗口内已提交的用量及本事务累加的用量
+pub struct LimitUsages {
+    tenant_id: i32,
+    now: NaiveDateTime,
+    use_redis: bool,
+    rules: Vec<LimitRuleModel>,
+    usages: HashMap<(i32, String), (LimitUsage, LimitUsage)>,
+}
+
+pub struct LimitRuleService;
+
+impl LimitRuleService {
+    // `use_redis`为假时每次检查均从数据库统计，用于未连接 Redis 的测试及工具
+    pub async fn init(repository: &impl Repository, use_redis: bool) -> AppResult<()> {
+        let mut rules: HashMap<i32, Vec<LimitRuleModel>> = HashMap::new();
+        for limit_rule in repository.fetch_limit_rules().await? {
+            rules
+                .entry(limit_rule.tenant_id)
+                .or_default()
+                .push(limit_rule);
+        }
+        *repository
+            .cache()
+            .limit_rules
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(LimitRules { rules, use_redis });
+        Ok(())
+    }
+
+    pub fn is_initialized(cache: &ConfigCache) -> bool {
+        cache
+            .limit_rules
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    fn get(cache: &ConfigCache) -> AppResult<LimitRules> {
+        cache
+            .limit_rules
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .ok_or_else(|| {
+                Error::Custom(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "限额规则未初始化".to_string(),
+                )
+            })
+    }
+
+    // 某租户已启用的限额规则
+    pub fn list(cache: &ConfigCache, tenant_id: i32) -> AppResult<Vec<LimitRuleModel>> {
+        let mut limit_rules = Self::get(cache)?;
+        Ok(limit_rules.rules.remove(&tenant_id).unwrap_or_default())
+    }
+
+    // 在事务内锁定并读取本批次涉及用户在当前窗口内已使用的限额，需在锁定任何账户之前调用
+    // 按用户排序加锁并持有至事务结束，同一用户的限额检查与累加串行执行，窗口按事务时间计算
+    pub async fn lock(
+        tx: &mut impl RepositoryTransaction,
+        tenant_id: i32,
+        account_action_requests: &[AccountActionRequest],
+    ) -> AppResult<LimitUsages> {
+        let use_redis = Self::get(tx.cache())?.use_redis;
+        let rules = Self::list(tx.cache(), tenant_id)?;
+        let mut user_ids: Vec<&str> = rules
+            .iter()
+            .flat_map(|limit_rule| {
+                Self::batch_usage(tx.cache(), limit_rule, tenant_id, account_action_requests)
+                    .into_keys()
+            })
+            .collect();
+        user_ids.sort_unstable();
+        user_ids.dedup();
+        // 没有适用的规则时不加锁，也不会用到事务时间
+        let mut now = NaiveDateTime::default();
+        for user_id in &user_ids {
+            now = tx.lock_limit_usage(tenant_id, user_id).await?;
+        }
+        let mut usages = HashMap::new();
+        for limit_rule in &rules {
+            for user_id in
+                Self::batch_usage(tx.cache(), limit_rule, tenant_id, account_action_requests)
+                    .into_keys()
+            {
+                let usage = Self::usage(tx, use_redis, limit_rule, tenant_id, user_id, now).await?;
+                usages.insert(
+                    (limit_rule.id, user_id.to_string()),
+                    (usage, LimitUsage::default()),
+                );
+            }
+        }
+        Ok(LimitUsages {
+            tenant_id,
+            now,
+            use_redis,
+            rules,
+            usages,
+        })
+    }
+
+    // 检查已使用的限额加上本事务已累加及`account_action_requests`的用量是否超出规则
+    // `account_action_requests`需已包含在`lock`的操作中
+    pub fn check(
+        cache: &ConfigCache,
+        limit_usages: &LimitUsages,
+        account_action_requests: &[AccountActionRequest],
+    ) -> AppResult<()> {
+        for limit_rule in &limit_usages.rules {
+            for (user_id, batch_usage) in Self::batch_usage(
+                cache,
+                limit_rule,
+                limit_usages.tenant_id,
+                account_action_requests,
+            ) {
+                let (usage, added) = limit_usages
+                    .usages
+                    .get(&(limit_rule.id, user_id.to_string()))
+                    .copied()
+                    .unwrap_or_default();
+                let total_usage = LimitUsage {
+                    count: usage.count + added.count + batch_usage.count,
+                    amount: usage.amount + added.amount + batch_usage.amount,
+                };
+                if limit_rule.is_exceeded(total_usage) {
+                    MetricService::record_reject(RejectReason::LimitExceeded);
+                    return Err(Error::Custom(
+                        StatusCode::TOO_MANY_REQUESTS,
+                        format!("操作失败，超出限额: {}", limit_rule.description),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // 累加本事务已入账的操作，之后的检查将其计入
+    pub fn add(
+        cache: &ConfigCache,
+        limit_usages: &mut LimitUsages,
+        account_action_requests: &[AccountActionRequest],
+    ) {
+        for limit_rule in &limit_usages.rules {
+            for (user_id, batch_usage) in Self::batch_usage(
+                cache,
+                limit_rule,
+                limit_usages.tenant_id,
+                account_action_requests,
+            ) {
+                let (_, added) = limit_usages
+                    .usages
+                    .entry((limit_rule.id, user_id.to_string()))
+                    .or_default();
+                added.count += batch_usage.count;
+                added.amount += batch_usage.amount;
+            }
+        }
+    }
+
+    // 在提交事务前累加 Redis 中已使用的限额，此时仍持有锁，其他事务读取到的缓存已包含本事务
+    // 提交失败时缓存多计本事务的用量，直至窗口结束后过期
+    pub async fn record(limit_usages: &LimitUsages) {
+        if !limit_usages.use_redis {
+            return;
+        }
+        for limit_rule in &limit_usages.rules {
+            for ((rule_id, user_id), (_, added)) in &limit_usages.usages {
+                if *rule_id != limit_rule.id || added.count == 0 {
+                    continue;
+                }
+                let key = Self::key(
+                    limit_rule,
+                    limit_usages.tenant_id,
+                    user_id,
+                    limit_usages.now,
+                );
+                if let Err(err) = Self::increment_cached_usage(&key, *added).await {
+                    tracing::warn!("Failed to record limit usage {key}: {err}");
+                }
+            }
+        }
+    }
+
+    // 本批次中适用该规则的操作，按用户汇总
+    // 与`LimitRuleModel::fetch_usage`一致，未指定账户操作类型的规则不统计兑换等未启用的类型
+    fn batch_usage<'a>(
+        cache: &ConfigCache,
+        limit_rule: &LimitRuleModel,
+        tenant_id: i32,
+        account_action_requests: &'a [AccountActionRequest],
+    ) -> HashMap<&'a str, LimitUsage> {
+        let mut batch_usage: HashMap<&str, LimitUsage> = HashMap::new();
+        for account_action_request in account_action_requests.iter().filter(|request| {
+            limit_rule.matches(request.asset_type_id, request.action_type_id)
+                && (limit_rule.action_type_id.is_some()
+                    || ActionTypeService::is_active(cache, tenant_id, request.action_type_id))
+        }) {
+            let usage = batch_usage
+                .entry(account_action_request.user_id.as_str())
+                .or_default();
+            usage.count += 1;
+            usage.amount += Decimal::from_f64(account_action_request.amount.abs())
+                .unwrap()
+                .trunc_with_scale(6);
+        }
+        batch_usage
+    }
+
+    // 固定窗口，按 UTC 时间对齐，如`86400`为自然日，窗口开始时已使用的限额清零
+    fn window_start(limit_rule: &LimitRuleModel, now: NaiveDateTime) -> NaiveDateTime {
+        let timestamp = now.and_utc().timestamp();
+        let start = timestamp - timestamp.rem_euclid(i64::from(limit_rule.window_seconds));
+        DateTime::from_timestamp(start, 0)
+            .unwrap_or_default()
+            .naive_utc()
+    }
+
+    fn key(
+        limit_rule: &LimitRuleModel,
+        tenant_id: i32,
+        user_id: &str,
+        now: NaiveDateTime,
+    ) -> String {
+        format!(
+            "amazing:limit:{tenant_id}:{}:{}:{user_id}",
+            limit_rule.id,
+            Self::window_start(limit_rule, now).and_utc().timestamp()
+        )
+    }
+
+    // 优先读取 Redis 缓存，缓存不存在或 Redis 不可用时从数据库统计
+    async fn usage(
+        tx: &mut impl RepositoryTransaction,
+        use_redis: bool,
+        limit_rule: &LimitRuleModel,
+        tenant_id: i32,
+        user_id: &str,
+        now: NaiveDateTime,
+    ) -> AppResult<LimitUsage> {
+        let key = Self::key(limit_rule, tenant_id, user_id, now);
+        if use_redis {
+            match Self::cached_usage(&key).await {
+                Ok(Some(usage)) => return Ok(usage),
+                Ok(None) => {}
+                Err(err) => tracing::warn!("Failed to read limit usage {key}: {err}"),
+            }
+        }
+        let usage = tx
+            .fetch_limit_usage(
+                tenant_id,
+                user_id,
+                limit_rule.asset_type_id,
+                limit_rule.action_type_id,
+                Self::window_start(limit_rule, now),
+            )
+            .await?;
+        if use_redis {
+            if let Err(err) = Self::cache_usage(&key, usage, limit_rule.window_seconds).await {
+                tracing::warn!("Failed to cache limit usage {key}: {err}");
+            }
+        }
+        Ok(usage)
+    }
+
+    async fn cached_usage(key: &str) -> RedisResult<Option<LimitUsage>> {
+        let mut conn = axum_kit::redis::conn().clone();
+        let (count, amount) = redis::cmd("HMGET")
+            .arg(key)
+            .arg("count")
+            .arg("amount")
+            .query_async::<(Option<i64>, Option<i64>)>(&mut conn)
+            .await?;
+        Ok(match (count, amount) {
+            (Some(count), Some(amount)) => Some(LimitUsage {
+                count,
+                amount: Decimal::new(amount, 6),
+            }),
+            _ => None,
+        })
+    }
+
+    // 缓存在窗口结束后过期
+    async fn cache_usage(key: &str, usage: LimitUsage, window_seconds: i32) -> RedisResult<()> {
+        let mut conn = axum_kit::redis::conn().clone();
+        redis::pipe()
+            .atomic()
+            .cmd("HSET")
+            .arg(key)
+            .arg("count")
+            .arg(usage.count)
+            .arg("amount")
+            .arg(Self::scaled_amount(usage.amount))
+            .ignore()
+            .cmd("EXPIRE")
+            .arg(key)
+            .arg(window_seconds)
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+    }
+
+    async fn increment_cached_usage(key: &str, usage: LimitUsage) -> RedisResult<()> {
+        let mut conn = axum_kit::redis::conn().clone();
+        redis::Script::new(INCREMENT_SCRIPT)
+            .key(key)
+            .arg(usage.count)
+            .arg(Self::scaled_amount(usage.amount))
+            .invoke_async::<()>(&mut conn)
+            .await
+    }
+
+    fn scaled_amount(amount: Decimal) -> i64 {
+        (amount * Decimal::from(AMOUNT_SCALE))
+            .trunc()
+            .to_i64()
+            .unwrap_or(i64::MAX)
+    }
+}
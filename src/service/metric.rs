@@ -0,0 +1,102 @@
+use axum::http::StatusCode;
+use axum_kit::{error::Error, postgres, AppResult};
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::{future::Future, sync::OnceLock, time::Duration};
+
+static PROMETHEUS: OnceLock<PrometheusHandle> = OnceLock::new();
+
+tokio::task_local! {
+    // 为真时不记录账户操作被拒绝的次数，如预览
+    static SKIP_REJECTS: bool;
+}
+
+const ACTIONS_DURATION_SECONDS: &str = "amazing_actions_duration_seconds";
+// 计数器只支持整数，金额按最小精度(6位小数)的整数累计
+const AMOUNT_SCALE: f64 = 1_000_000.0;
+
+// 账户操作被拒绝的原因
+pub enum RejectReason {
+    InactiveAccount,
+    InsufficientBalance,
+    DuplicateOrder,
+    LimitExceeded,
+    MaxSupplyExceeded,
+}
+
+impl RejectReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RejectReason::InactiveAccount => "inactive_account",
+            RejectReason::InsufficientBalance => "insufficient_balance",
+            RejectReason::DuplicateOrder => "duplicate_order",
+            RejectReason::LimitExceeded => "limit_exceeded",
+            RejectReason::MaxSupplyExceeded => "max_supply_exceeded",
+        }
+    }
+}
+
+pub struct MetricService;
+
+impl MetricService {
+    // 需在记录任何指标前调用
+    pub fn init() -> anyhow::Result<()> {
+        let handle = PrometheusBuilder::new()
+            .set_buckets_for_metric(
+                Matcher::Full(ACTIONS_DURATION_SECONDS.to_string()),
+                &[
+                    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+                ],
+            )?
+            .install_recorder()?;
+        PROMETHEUS
+            .set(handle)
+            .map_err(|_| anyhow::anyhow!("Failed to initialize PROMETHEUS"))?;
+        Ok(())
+    }
+
+    // Prometheus 文本格式指标
+    pub fn render() -> AppResult<String> {
+        let handle = PROMETHEUS.get().ok_or_else(|| {
+            Error::Custom(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "监控指标未初始化".to_string(),
+            )
+        })?;
+        // 连接池指标只在采集时读取
+        let pool = postgres::conn();
+        gauge!("amazing_db_pool_connections").set(pool.size() as f64);
+        gauge!("amazing_db_pool_idle_connections").set(pool.num_idle() as f64);
+        gauge!("amazing_db_pool_max_connections").set(pool.options().get_max_connections() as f64);
+        handle.run_upkeep();
+        Ok(handle.render())
+    }
+
+    // 账户操作成功后记录操作次数及变动金额
+    pub fn record_action(asset_type: &str, action_type: &str, amount: f64) {
+        let labels = [
+            ("asset_type", asset_type.to_string()),
+            ("action_type", action_type.to_string()),
+        ];
+        counter!("amazing_actions_total", &labels).increment(1);
+        counter!("amazing_action_amount_micros_total", &labels)
+            .increment((amount.abs() * AMOUNT_SCALE).round() as u64);
+    }
+
+    pub fn record_reject(reason: RejectReason) {
+        if SKIP_REJECTS.try_with(|skip| *skip).unwrap_or(false) {
+            return;
+        }
+        counter!("amazing_actions_rejected_total", "reason" => reason.as_str()).increment(1);
+    }
+
+    // 执行`future`期间不记录账户操作被拒绝的次数
+    pub async fn skip_rejects<F: Future>(future: F) -> F::Output {
+        SKIP_REJECTS.scope(true, future).await
+    }
+
+    pub fn record_actions_duration(duration: Duration, is_ok: bool) {
+        let result = if is_ok { "ok" } else { "error" };
+        histogram!(ACTIONS_DURATION_SECONDS, "result" => result).record(duration.as_secs_f64());
+    }
+}
@@ -1,3 +1,18 @@
 pub mod account;
+pub mod account_log_partition;
+pub mod action_job;
 pub mod action_type;
 pub mod asset_type;
+pub mod balance_lot;
+pub mod change_log;
+pub mod client;
+pub mod exchange;
+pub mod fee_rule;
+pub mod health;
+pub mod hold;
+pub mod import;
+pub mod limit_rule;
+pub mod metric;
+pub mod reconciliation;
+pub mod statement;
+pub mod system_account;
@@ -0,0 +1,141 @@
+use super::{account::AccountService, client::Operator};
+use crate::{
+    handler::account::AccountActionRequest,
+    model::{
+        action_type::ActionTypeModel, change_log::ChangeLogModel,
+        reconciliation::ReconciliationModel,
+    },
+    repository::{postgres::PgRepository, TransactionControl, TransactionalRepository},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    types::{chrono::NaiveDateTime, Decimal},
+    PgExecutor,
+};
+
+// 修复类账户操作类型名称前缀，仅允许通过管理工具使用
+const FIX_ACTION_TYPE_PREFIX: &str = "FIX_";
+
+// 待审批的账户修复操作
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Correction {
+    pub tenant_id: i32,
+    pub user_id: String,
+    pub asset_type_id: i32,
+    pub action_type: String,
+    pub amount: Decimal,
+    pub order_number: String,
+    pub description: String,
+}
+
+pub struct ReconciliationService;
+
+impl ReconciliationService {
+    pub async fn reconcile(executor: impl PgExecutor<'_>) -> AppResult<Vec<ReconciliationModel>> {
+        ReconciliationModel::fetch_mismatched(executor).await
+    }
+
+    // 以最新一条账户操作日志为准生成修复操作，使账户余额恢复为日志记录的操作后余额
+    // 修复操作本身也会写入日志，执行后账户余额与最新日志再次一致
+    pub fn propose_corrections(
+        reconciliation: &ReconciliationModel,
+        proposed_at: NaiveDateTime,
+    ) -> Vec<Correction> {
+        let fields = [
+            (
+                "AB",
+                "可用余额",
+                reconciliation.available_balance_after - reconciliation.available_balance,
+            ),
+            (
+                "FB",
+                "冻结余额",
+                reconciliation.frozen_balance_after - reconciliation.frozen_balance,
+            ),
+            (
+                "TI",
+                "累计收入",
+                reconciliation.total_income_after - reconciliation.total_income,
+            ),
+            (
+                "TE",
+                "累计支出",
+                reconciliation.total_expense_after - reconciliation.total_expense,
+            ),
+        ];
+        fields
+            .into_iter()
+            .filter(|(_, _, diff)| !diff.is_zero())
+            .map(|(field, field_name, diff)| {
+                let direction = if diff.is_sign_positive() {
+                    "INC"
+                } else {
+                    "DEC"
+                };
+                Correction {
+                    tenant_id: reconciliation.tenant_id,
+                    user_id: reconciliation.user_id.clone(),
+                    asset_type_id: reconciliation.asset_type_id,
+                    action_type: format!("{FIX_ACTION_TYPE_PREFIX}{field}_{direction}"),
+                    amount: diff.abs(),
+                    order_number: format!(
+                        "reconcile-{}-{:010}-{field}",
+                        proposed_at.format("%Y%m%d%H%M%S"),
+                        reconciliation.account_id
+                    ),
+                    description: format!("对账修复{field_name}"),
+                }
+            })
+            .collect()
+    }
+
+    // 在同一事务内执行已审批的修复操作，任一操作失败则全部回滚
+    // 需已加载`repository`的资产类型缓存
+    pub async fn apply_corrections(
+        repository: &PgRepository,
+        corrections: &[Correction],
+        operator: &Operator,
+    ) -> AppResult<()> {
+        let mut tx = repository.begin().await?;
+        ChangeLogModel::set_operator(tx.conn(), operator.client_id, &operator.name).await?;
+        for correction in corrections {
+            if !correction.action_type.starts_with(FIX_ACTION_TYPE_PREFIX) {
+                return Err(Error::Custom(
+                    StatusCode::BAD_REQUEST,
+                    format!("只允许使用修复类账户操作类型: {}", correction.action_type),
+                ));
+            }
+            let action_type = ActionTypeModel::find_by_name(
+                tx.conn(),
+                correction.tenant_id,
+                &correction.action_type,
+            )
+            .await?;
+            let amount = correction.amount.to_f64().ok_or_else(|| {
+                Error::Custom(
+                    StatusCode::BAD_REQUEST,
+                    format!("无效的修复金额: {}", correction.amount),
+                )
+            })?;
+            let account_action_request = AccountActionRequest {
+                user_id: correction.user_id.clone(),
+                asset_type_id: correction.asset_type_id,
+                action_type_id: action_type.id,
+                amount,
+                order_number: correction.order_number.clone(),
+                description: correction.description.clone(),
+            };
+            AccountService::update_balance(
+                &mut tx,
+                &account_action_request,
+                &action_type,
+                operator,
+            )
+            .await?;
+        }
+        tx.commit().await
+    }
+}
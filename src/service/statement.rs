@@ -0,0 +1,272 @@
+use super::{action_type::ActionTypeService, asset_type::AssetTypeService};
+use crate::{
+    handler::account::{AccountStatementRequest, StatementFormat},
+    model::{account::AccountModel, account_log::AccountLogModel},
+    repository::Repository,
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use futures::{stream, Stream};
+use serde::Serialize;
+use sqlx::types::{chrono::NaiveDateTime, Decimal};
+use std::{collections::HashMap, io};
+use validator::Validate;
+
+// 导出账单时每次读取的日志条数，内存占用与导出范围无关
+const STATEMENT_CHUNK_SIZE: i64 = 1000;
+
+// 账单中的一行：期初余额、账户操作日志或期末余额
+// 期初、期末余额行只有资产类型及余额，CSV 中其余列为空，JSON Lines 中为`null`
+#[derive(Serialize)]
+struct StatementRow<'a> {
+    record: &'static str,
+    asset_type: &'a str,
+    id: Option<i64>,
+    created_at: Option<NaiveDateTime>,
+    action_type: Option<&'a str>,
+    amount_available_balance: Option<Decimal>,
+    amount_frozen_balance: Option<Decimal>,
+    amount_total_income: Option<Decimal>,
+    amount_total_expense: Option<Decimal>,
+    available_balance: Decimal,
+    frozen_balance: Decimal,
+    total_income: Decimal,
+    total_expense: Decimal,
+    order_number: Option<&'a str>,
+    description: Option<&'a str>,
+    operator: Option<&'a str>,
+}
+
+impl<'a> StatementRow<'a> {
+    // 期初、期末余额取自该时间之前的最后一条日志，没有日志时为零
+    fn balance(
+        record: &'static str,
+        asset_type: &'a str,
+        account_log: Option<&AccountLogModel>,
+    ) -> Self {
+        Self {
+            record,
+            asset_type,
+            id: None,
+            created_at: None,
+            action_type: None,
+            amount_available_balance: None,
+            amount_frozen_balance: None,
+            amount_total_income: None,
+            amount_total_expense: None,
+            available_balance: account_log.map_or(Decimal::ZERO, |account_log| {
+                account_log.available_balance_after
+            }),
+            frozen_balance: account_log.map_or(Decimal::ZERO, |account_log| {
+                account_log.frozen_balance_after
+            }),
+            total_income: account_log
+                .map_or(Decimal::ZERO, |account_log| account_log.total_income_after),
+            total_expense: account_log
+                .map_or(Decimal::ZERO, |account_log| account_log.total_expense_after),
+            order_number: None,
+            description: None,
+            operator: None,
+        }
+    }
+
+    fn entry(asset_type: &'a str, action_type: &'a str, account_log: &'a AccountLogModel) -> Self {
+        Self {
+            record: "entry",
+            asset_type,
+            id: Some(account_log.id),
+            created_at: Some(account_log.created_at),
+            action_type: Some(action_type),
+            amount_available_balance: Some(account_log.amount_available_balance),
+            amount_frozen_balance: Some(account_log.amount_frozen_balance),
+            amount_total_income: Some(account_log.amount_total_income),
+            amount_total_expense: Some(account_log.amount_total_expense),
+            available_balance: account_log.available_balance_after,
+            frozen_balance: account_log.frozen_balance_after,
+            total_income: account_log.total_income_after,
+            total_expense: account_log.total_expense_after,
+            order_number: Some(&account_log.order_number),
+            description: Some(&account_log.description),
+            operator: Some(&account_log.operator),
+        }
+    }
+}
+
+// 每个账户依次输出期初余额、范围内的日志及期末余额
+enum Phase {
+    Opening,
+    Entries { after_id: i64 },
+    Closing,
+}
+
+struct Statement<R> {
+    repository: R,
+    tenant_id: i32,
+    format: StatementFormat,
+    start_at: NaiveDateTime,
+    end_at: NaiveDateTime,
+    accounts: Vec<AccountModel>,
+    action_types: HashMap<i32, String>,
+    index: usize,
+    phase: Phase,
+    // CSV 表头只在第一块输出
+    has_headers: bool,
+}
+
+impl<R: Repository> Statement<R> {
+    // 输出下一块内容，全部账户输出完毕时返回空
+    async fn next_chunk(&mut self) -> AppResult<Option<Vec<u8>>> {
+        loop {
+            let Some(account) = self.accounts.get(self.index) else {
+                return Ok(None);
+            };
+            let asset_type = AssetTypeService::name(
+                self.repository.cache(),
+                self.tenant_id,
+                account.asset_type_id,
+            );
+            match self.phase {
+                Phase::Opening => {
+                    let account_log = self
+                        .repository
+                        .find_last_account_log_before(account.id, self.start_at)
+                        .await?;
+                    self.phase = Phase::Entries { after_id: 0 };
+                    let row = StatementRow::balance("opening", &asset_type, account_log.as_ref());
+                    return self.write(&[row]).map(Some);
+                }
+                Phase::Entries { after_id } => {
+                    let account_logs = self
+                        .repository
+                        .fetch_account_log_range(
+                            account.id,
+                            self.start_at,
+                            self.end_at,
+                            after_id,
+                            STATEMENT_CHUNK_SIZE,
+                        )
+                        .await?;
+                    let Some(last) = account_logs.last() else {
+                        self.phase = Phase::Closing;
+                        continue;
+                    };
+                    self.phase = Phase::Entries { after_id: last.id };
+                    let rows: Vec<StatementRow> = account_logs
+                        .iter()
+                        .map(|account_log| {
+                            let action_type = self
+                                .action_types
+                                .get(&account_log.action_type_id)
+                                .map_or("", String::as_str);
+                            StatementRow::entry(&asset_type, action_type, account_log)
+                        })
+                        .collect();
+                    return self.write(&rows).map(Some);
+                }
+                Phase::Closing => {
+                    let account_log = self
+                        .repository
+                        .find_last_account_log_before(account.id, self.end_at)
+                        .await?;
+                    self.index += 1;
+                    self.phase = Phase::Opening;
+                    let row = StatementRow::balance("closing", &asset_type, account_log.as_ref());
+                    return self.write(&[row]).map(Some);
+                }
+            }
+        }
+    }
+
+    fn write(&self, rows: &[StatementRow]) -> AppResult<Vec<u8>> {
+        let mut buffer = Vec::new();
+        match self.format {
+            StatementFormat::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(self.has_headers)
+                    .from_writer(&mut buffer);
+                for row in rows {
+                    writer.serialize(row).map_err(anyhow::Error::from)?;
+                }
+                writer.flush().map_err(anyhow::Error::from)?;
+            }
+            StatementFormat::Ndjson => {
+                for row in rows {
+                    serde_json::to_writer(&mut buffer, row).map_err(anyhow::Error::from)?;
+                    buffer.push(b'\n');
+                }
+            }
+        }
+        Ok(buffer)
+    }
+}
+
+pub struct StatementService;
+
+impl StatementService {
+    // 校验请求后返回按块生成的账单，日志按账户分页读取
+    // 开始输出后出错时中断响应，客户端需以连接是否正常结束判断账单是否完整
+    pub async fn export<R>(
+        repository: R,
+        tenant_id: i32,
+        statement_request: &AccountStatementRequest,
+    ) -> AppResult<impl Stream<Item = io::Result<Vec<u8>>> + Send + 'static>
+    where
+        R: Repository + 'static,
+    {
+        statement_request.validate()?;
+        if statement_request.start_date >= statement_request.end_date {
+            return Err(Error::Custom(
+                StatusCode::BAD_REQUEST,
+                "结束日期需晚于开始日期".to_string(),
+            ));
+        }
+        let accounts = match statement_request.asset_type_id {
+            Some(asset_type_id) => {
+                AssetTypeService::by_id(repository.cache(), tenant_id, asset_type_id)?;
+                vec![
+                    repository
+                        .find_account(tenant_id, &statement_request.user_id, asset_type_id)
+                        .await?,
+                ]
+            }
+            None => {
+                let mut accounts = repository
+                    .find_accounts(
+                        tenant_id,
+                        &statement_request.user_id,
+                        AssetTypeService::ids(repository.cache(), tenant_id)?,
+                    )
+                    .await?;
+                accounts.sort_by_key(|account| account.asset_type_id);
+                accounts
+            }
+        };
+        let action_types = ActionTypeService::names(&repository, tenant_id).await?;
+        let statement = Statement {
+            repository,
+            tenant_id,
+            format: statement_request.format,
+            start_at: statement_request.start_date.and_time(Default::default()),
+            end_at: statement_request.end_date.and_time(Default::default()),
+            accounts,
+            action_types,
+            index: 0,
+            phase: Phase::Opening,
+            has_headers: true,
+        };
+        Ok(stream::unfold(Some(statement), |statement| async move {
+            let mut statement = statement?;
+            match statement.next_chunk().await {
+                Ok(Some(chunk)) => {
+                    statement.has_headers = false;
+                    Some((Ok(chunk), Some(statement)))
+                }
+                Ok(None) => None,
+                Err(err) => {
+                    tracing::error!("Failed to export statement: {err}");
+                    Some((Err(io::Error::other(err.to_string())), None))
+                }
+            }
+        }))
+    }
+}
@@ -0,0 +1,144 @@
+use super::{action_type::ActionTypeService, asset_type::AssetTypeService};
+use crate::{
+    handler::account::AccountActionRequest,
+    model::action_type::ActionTypeModel,
+    repository::{cache::ConfigCache, Repository},
+};
+use axum::http::StatusCode;
+use axum_kit::{error::Error, AppResult};
+use sqlx::types::Decimal;
+use std::collections::BTreeMap;
+
+// 发行系统账户的对应分录账户操作类型
+const ISSUANCE: &str = "SYS_ISS";
+// 销毁系统账户的对应分录账户操作类型
+const BURN: &str = "SYS_BRN";
+
+// 系统账户对应分录的账户操作类型，默认未启用，需在开启事务前读取
+pub struct SystemActionTypes {
+    issuance: ActionTypeModel,
+    burn: ActionTypeModel,
+}
+
+// 系统账户的对应分录，金额为单边操作净额的绝对值
+pub struct CounterEntry {
+    pub user_id: String,
+    pub asset_type_id: i32,
+    pub action_type: ActionTypeModel,
+    pub amount: Decimal,
+    pub order_number: String,
+    pub description: String,
+}
+
+// 资产类型的单边操作净额及其中第一个单边操作
+struct Imbalance<'a> {
+    amount: Decimal,
+    first: &'a AccountActionRequest,
+}
+
+pub struct SystemAccountService;
+
+impl SystemAccountService {
+    // 租户存在复式记账资产类型时读取对应分录的账户操作类型，否则返回空
+    pub async fn action_types(
+        repository: &impl Repository,
+        tenant_id: i32,
+    ) -> AppResult<Option<SystemActionTypes>> {
+        if !AssetTypeService::list(repository.cache(), tenant_id)?
+            .iter()
+            .any(|asset_type| asset_type.is_double_entry)
+        {
+            return Ok(None);
+        }
+        Ok(Some(SystemActionTypes {
+            issuance: ActionTypeService::find_by_name(repository, tenant_id, ISSUANCE).await?,
+            burn: ActionTypeService::find_by_name(repository, tenant_id, BURN).await?,
+        }))
+    }
+
+    // 为复式记账资产类型生成系统账户的对应分录，同一批次内每个资产类型至多一条
+    // 单边操作改变总余额(可用余额 + 冻结余额)的净额增加时由发行系统账户扣减，减少时计入销毁系统账户
+    // 双边操作需合计为零，否则返回 422；对应分录的订单号为该资产类型第一个单边操作的`<user_id>-<账户操作类型id>-<原订单号>`
+    pub fn counter_entries(
+        cache: &ConfigCache,
+        system_action_types: Option<&SystemActionTypes>,
+        tenant_id: i32,
+        movements: &[(AccountActionRequest, ActionTypeModel)],
+    ) -> AppResult<Vec<CounterEntry>> {
+        let mut counter_entries = Vec::new();
+        for (asset_type_id, imbalance) in Self::imbalances(cache, tenant_id, movements)? {
+            let asset_type = AssetTypeService::by_id(cache, tenant_id, asset_type_id)?;
+            let system_account_missing = || {
+                Error::Custom(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "资产类型未配置系统账户".to_string(),
+                )
+            };
+            let system_action_types = system_action_types.ok_or_else(system_account_missing)?;
+            let (user_id, action_type) = if imbalance.amount > Decimal::ZERO {
+                (&asset_type.issuance_user_id, &system_action_types.issuance)
+            } else {
+                (&asset_type.burn_user_id, &system_action_types.burn)
+            };
+            let first = imbalance.first;
+            counter_entries.push(CounterEntry {
+                user_id: user_id.clone().ok_or_else(system_account_missing)?,
+                asset_type_id,
+                action_type: action_type.clone(),
+                amount: imbalance.amount.abs(),
+                order_number: format!(
+                    "{}-{}-{}",
+                    first.user_id, first.action_type_id, first.order_number
+                ),
+                description: first.description.clone(),
+            });
+        }
+        Ok(counter_entries)
+    }
+
+    // 检查双边操作是否合计为零，用于部分成功模式中逐个检查账户操作
+    pub fn check_balanced(
+        cache: &ConfigCache,
+        tenant_id: i32,
+        movements: &[(AccountActionRequest, ActionTypeModel)],
+    ) -> AppResult<()> {
+        Self::imbalances(cache, tenant_id, movements)?;
+        Ok(())
+    }
+
+    // 按资产类型 id 排序，所有事务按相同顺序锁定系统账户
+    fn imbalances<'a>(
+        cache: &ConfigCache,
+        tenant_id: i32,
+        movements: &'a [(AccountActionRequest, ActionTypeModel)],
+    ) -> AppResult<BTreeMap<i32, Imbalance<'a>>> {
+        let mut imbalances: BTreeMap<i32, Imbalance> = BTreeMap::new();
+        let mut two_sided: BTreeMap<i32, Decimal> = BTreeMap::new();
+        for (account_action_request, action_type) in movements {
+            let asset_type_id = account_action_request.asset_type_id;
+            if !AssetTypeService::by_id(cache, tenant_id, asset_type_id)?.is_double_entry {
+                continue;
+            }
+            let amount = action_type.balance_change(account_action_request.amount);
+            if !action_type.is_single_sided {
+                *two_sided.entry(asset_type_id).or_default() += amount;
+            } else if !amount.is_zero() {
+                imbalances
+                    .entry(asset_type_id)
+                    .or_insert(Imbalance {
+                        amount: Decimal::ZERO,
+                        first: account_action_request,
+                    })
+                    .amount += amount;
+            }
+        }
+        if two_sided.values().any(|amount| !amount.is_zero()) {
+            return Err(Error::Custom(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "操作失败，双边操作的分录合计不为零".to_string(),
+            ));
+        }
+        imbalances.retain(|_, imbalance| !imbalance.amount.is_zero());
+        Ok(imbalances)
+    }
+}
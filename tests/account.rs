@@ -0,0 +1,390 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::*;
+use serde_json::json;
+use sqlx::{types::Decimal, PgPool};
+use tokio::task::JoinSet;
+
+#[sqlx::test(migrations = "./migrations")]
+async fn create_and_info(pool: PgPool) {
+    let router = setup(pool, &[]).await;
+    create_account(&router, USER_ID).await;
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": GOLD });
+    let (status, account) = post(&router, "/accounts/info", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(account["user_id"], USER_ID);
+    assert_eq!(account["is_active"], true);
+    let (status, accounts) = post(&router, "/accounts/infos", json!({ "user_id": USER_ID })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(accounts.as_array().unwrap().len(), 1);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn reject_unauthenticated(pool: PgPool) {
+    let router = setup(pool, &[]).await;
+    let request = axum::http::Request::builder()
+        .method("POST")
+        .uri("/accounts/infos")
+        .header("content-type", "application/json")
+        .body(axum::body::Body::from(
+            json!({ "user_id": USER_ID }).to_string(),
+        ))
+        .unwrap();
+    let response = tower::ServiceExt::oneshot(router, request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn actions_update_balance(pool: PgPool) {
+    let router = setup(pool, &[]).await;
+    create_account(&router, USER_ID).await;
+    let (status, _) = post(
+        &router,
+        "/accounts/actions",
+        json!([action(AB_INC, 100.5, "1")]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = post(
+        &router,
+        "/accounts/actions",
+        json!([action(AB_EXP, 0.5, "2")]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(available_balance(&router).await, Decimal::from(100));
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": GOLD });
+    let (status, verify) = post(&router, "/accounts/logs/verify", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(verify["verified"], 2);
+    assert_eq!(verify["broken_log_id"], serde_json::Value::Null);
+}
+
+// 修改日志时间或清空整条哈希链均导致校验失败
+#[sqlx::test(migrations = "./migrations")]
+async fn verify_logs_detect_tampering(pool: PgPool) {
+    let router = setup(pool.clone(), &[]).await;
+    create_account(&router, USER_ID).await;
+    for payload in [action(AB_INC, 100.0, "1"), action(AB_EXP, 10.0, "2")] {
+        let (status, _) = post(&router, "/accounts/actions", json!([payload])).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let first_id: i64 = sqlx::query_scalar("select min(id) from account_log")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": GOLD });
+
+    sqlx::query("update account_log set created_at = created_at - interval '1 day' where id = $1")
+        .bind(first_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (_, verify) = post(&router, "/accounts/logs/verify", payload.clone()).await;
+    assert_eq!(verify["broken_log_id"], first_id);
+
+    sqlx::query("update account_log set hash = ''")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (_, verify) = post(&router, "/accounts/logs/verify", payload).await;
+    assert_eq!(verify["unhashed"], 0);
+    assert_eq!(verify["broken_log_id"], first_id);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn actions_reject_overdraft(pool: PgPool) {
+    let router = setup(pool, &[]).await;
+    create_account(&router, USER_ID).await;
+    let (status, _) = post(
+        &router,
+        "/accounts/actions",
+        json!([action(AB_EXP, 1.0, "1")]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::PAYMENT_REQUIRED);
+    assert_eq!(available_balance(&router).await, Decimal::ZERO);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn actions_reject_duplicate_order(pool: PgPool) {
+    let router = setup(pool, &[]).await;
+    create_account(&router, USER_ID).await;
+    let payload = json!([action(AB_INC, 1.0, "1")]);
+    let (status, _) = post(&router, "/accounts/actions", payload.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = post(&router, "/accounts/actions", payload).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(available_balance(&router).await, Decimal::ONE);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn actions_reject_inactive_account(pool: PgPool) {
+    let router = setup(pool.clone(), &[]).await;
+    create_account(&router, USER_ID).await;
+    sqlx::query("update account set is_active = false where user_id = $1")
+        .bind(USER_ID.to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, _) = post(
+        &router,
+        "/accounts/actions",
+        json!([action(AB_INC, 1.0, "1")]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+// 同一批次内任一操作失败，整批回滚
+#[sqlx::test(migrations = "./migrations")]
+async fn actions_roll_back_whole_batch(pool: PgPool) {
+    let router = setup(pool, &[]).await;
+    create_account(&router, USER_ID).await;
+    let payload = json!([action(AB_INC, 1.0, "1"), action(AB_INC, 1.0, "1")]);
+    let (status, _) = post(&router, "/accounts/actions", payload).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(available_balance(&router).await, Decimal::ZERO);
+}
+
+// 部分成功模式下每个操作单独回滚，按提交顺序返回处理结果
+#[sqlx::test(migrations = "./migrations")]
+async fn actions_partial_success(pool: PgPool) {
+    let router = setup(pool, &[]).await;
+    create_account(&router, USER_ID).await;
+    let payload = json!([
+        action(AB_INC, 100.0, "1"),
+        action(AB_EXP, 1000.0, "2"),
+        // 与同一批次内已成功的操作订单号重复，在事务内失败
+        action(AB_INC, 1.0, "1"),
+        action(AB_INC, 5.0, "3"),
+    ]);
+    let (status, results) = post(&router, "/accounts/actions?mode=partial", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    let statuses: Vec<_> = results
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| (result["status"].as_str().unwrap(), result["code"].as_u64()))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("SUCCEEDED", None),
+            ("FAILED", Some(402)),
+            ("FAILED", Some(409)),
+            ("SUCCEEDED", None),
+        ]
+    );
+    assert_eq!(available_balance(&router).await, Decimal::from(105));
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": GOLD });
+    let (status, verify) = post(&router, "/accounts/logs/verify", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(verify["verified"], 2);
+    assert_eq!(verify["broken_log_id"], serde_json::Value::Null);
+
+    // 参数错误时整批失败
+    let payload = json!([action(AB_INC, 1.0, "4"), action(AB_INC, -1.0, "5")]);
+    let (status, _) = post(&router, "/accounts/actions?mode=partial", payload).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(available_balance(&router).await, Decimal::from(105));
+}
+
+fn balance(account: &serde_json::Value) -> Decimal {
+    account["available_balance"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+// 预览返回操作后的账户，不改变余额也不记录日志
+#[sqlx::test(migrations = "./migrations")]
+async fn actions_dry_run(pool: PgPool) {
+    let router = setup(pool, &[]).await;
+    create_account(&router, USER_ID).await;
+    let payload = json!([action(AB_INC, 100.0, "1")]);
+    let (status, _) = post(&router, "/accounts/actions", payload).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let payload = json!([action(AB_EXP, 30.0, "2"), action(AB_EXP, 20.0, "3")]);
+    let (status, preview) = post(&router, "/accounts/actions?dry_run=true", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(preview["accounts"].as_array().unwrap().len(), 1);
+    assert_eq!(balance(&preview["accounts"][0]), Decimal::from(50));
+    assert_eq!(preview["results"][1]["status"], "SUCCEEDED");
+    // 整批处理时任一操作失败返回错误
+    let payload = json!([action(AB_EXP, 30.0, "2"), action(AB_EXP, 1000.0, "3")]);
+    let (status, _) = post(&router, "/accounts/actions?dry_run=true", payload.clone()).await;
+    assert_eq!(status, StatusCode::PAYMENT_REQUIRED);
+    let (status, preview) = post(
+        &router,
+        "/accounts/actions?mode=partial&dry_run=true",
+        payload,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(balance(&preview["accounts"][0]), Decimal::from(70));
+    assert_eq!(preview["results"][0]["status"], "SUCCEEDED");
+    assert_eq!(preview["results"][1]["code"], 402);
+
+    assert_eq!(available_balance(&router).await, Decimal::from(100));
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": GOLD });
+    let (status, verify) = post(&router, "/accounts/logs/verify", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(verify["verified"], 1);
+    // 预览过的订单仍可实际处理
+    let payload = json!([action(AB_EXP, 30.0, "2")]);
+    let (status, _) = post(&router, "/accounts/actions", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(available_balance(&router).await, Decimal::from(70));
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn actions_share_transaction_id(pool: PgPool) {
+    let router = setup(pool.clone(), &[]).await;
+    create_account(&router, USER_ID).await;
+    let payload = json!([action(AB_INC, 100.0, "1"), action(AB_INC, 30.0, "2")]);
+    let (status, transaction_id) = post_header(
+        &router,
+        API_KEY,
+        "/accounts/actions",
+        payload,
+        "x-transaction-id",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let transaction_id: i64 = transaction_id.unwrap().parse().unwrap();
+    let payload = json!({ "transaction_id": transaction_id });
+    let (status, legs) = post(&router, "/accounts/transactions/info", payload.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    let legs = legs.as_array().unwrap();
+    assert_eq!(legs.len(), 2);
+    assert!(legs
+        .iter()
+        .all(|leg| leg["transaction_id"] == transaction_id && leg["user_id"] == USER_ID));
+    assert_eq!(legs[1]["order_number"], format!("{:0>32}", "2"));
+    // 每次请求使用新的交易id
+    let payload = json!([action(AB_EXP, 10.0, "3")]);
+    let (status, next) = post_header(
+        &router,
+        API_KEY,
+        "/accounts/actions?mode=partial",
+        payload,
+        "x-transaction-id",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(next.unwrap().parse::<i64>().unwrap() > transaction_id);
+    // 预览不分配交易id
+    let payload = json!([action(AB_EXP, 10.0, "4")]);
+    let (status, preview) = post_header(
+        &router,
+        API_KEY,
+        "/accounts/actions?dry_run=true",
+        payload,
+        "x-transaction-id",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(preview, None);
+    let payload = json!({ "transaction_id": 0 });
+    let (status, _) = post(&router, "/accounts/transactions/info", payload).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    // 交易id计入日志哈希，事后修改交易归属导致校验失败
+    let last_id: i64 = sqlx::query_scalar("select max(id) from account_log")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    sqlx::query("update account_log set transaction_id = $1 where id = $2")
+        .bind(transaction_id)
+        .bind(last_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": GOLD });
+    let (_, verify) = post(&router, "/accounts/logs/verify", payload).await;
+    assert_eq!(verify["broken_log_id"], last_id);
+}
+
+// 并发扣减时，开启事务前的余额检查均可通过，由事务内的检查保证余额不为负数
+#[sqlx::test(migrations = "./migrations")]
+async fn concurrent_expenses_never_overdraw(pool: PgPool) {
+    let router = setup(pool, &[]).await;
+    create_account(&router, USER_ID).await;
+    let (status, _) = post(
+        &router,
+        "/accounts/actions",
+        json!([action(AB_INC, 10.0, "0")]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let mut tasks = JoinSet::new();
+    for index in 1..=20 {
+        let router = router.clone();
+        tasks.spawn(async move {
+            let payload = json!([action(AB_EXP, 1.0, &index.to_string())]);
+            post(&router, "/accounts/actions", payload).await.0
+        });
+    }
+    let responses = tasks.join_all().await;
+    let succeeded = responses
+        .iter()
+        .filter(|&&status| status == StatusCode::OK)
+        .count();
+    assert_eq!(succeeded, 10);
+    assert!(responses
+        .iter()
+        .all(|&status| status == StatusCode::OK || status == StatusCode::PAYMENT_REQUIRED));
+    assert_eq!(available_balance(&router).await, Decimal::ZERO);
+}
+
+// 并发提交相同订单号时只处理一次
+#[sqlx::test(migrations = "./migrations")]
+async fn concurrent_duplicate_orders_apply_once(pool: PgPool) {
+    let router = setup(pool, &[]).await;
+    create_account(&router, USER_ID).await;
+    let mut tasks = JoinSet::new();
+    for _ in 0..10 {
+        let router = router.clone();
+        tasks.spawn(async move {
+            let payload = json!([action(AB_INC, 1.0, "1")]);
+            post(&router, "/accounts/actions", payload).await.0
+        });
+    }
+    let responses = tasks.join_all().await;
+    let succeeded = responses
+        .iter()
+        .filter(|&&status| status == StatusCode::OK)
+        .count();
+    assert_eq!(succeeded, 1);
+    assert!(responses
+        .iter()
+        .all(|&status| status == StatusCode::OK || status == StatusCode::CONFLICT));
+    assert_eq!(available_balance(&router).await, Decimal::ONE);
+}
+
+// 整数与字符串形式的`user_id`为同一用户
+#[sqlx::test(migrations = "./migrations")]
+async fn user_id_accepts_integer_and_string(pool: PgPool) {
+    let router = setup(pool, &[]).await;
+    let snowflake = 1_234_567_890_123_456_789_u64;
+    let payload = json!({ "user_id": snowflake, "asset_type_id": GOLD });
+    let (status, account) = post(&router, "/accounts/new", payload).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(account["user_id"], snowflake);
+    let payload = json!({ "user_id": snowflake.to_string(), "asset_type_id": GOLD });
+    let (status, _) = post(&router, "/accounts/new", payload).await;
+    assert!(!status.is_success());
+    let uuid = "6f1c2a4e-3b5d-4c7e-9f80-1a2b3c4d5e6f";
+    let payload = json!({ "user_id": uuid, "asset_type_id": GOLD });
+    let (status, _) = post(&router, "/accounts/new", payload).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, accounts) = post(&router, "/accounts/infos", json!({ "user_id": uuid })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(accounts[0]["user_id"], uuid);
+    let payload = json!({ "user_id": " padded ", "asset_type_id": GOLD });
+    let (status, _) = post(&router, "/accounts/new", payload).await;
+    assert!(!status.is_success());
+}
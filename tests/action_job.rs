@@ -0,0 +1,111 @@
+mod common;
+
+use amazing::{repository::action_job::ActionJobRepository, service::action_job::ActionJobService};
+use axum::http::StatusCode;
+use common::*;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+// 领取并处理已提交的任务，等同后台任务处理一次
+async fn process(pool: &PgPool) {
+    let repository = repository(pool.clone()).await;
+    let action_job = repository.claim_action_job(600.0).await.unwrap().unwrap();
+    ActionJobService::process(&repository, &action_job)
+        .await
+        .unwrap();
+    assert!(repository.claim_action_job(600.0).await.unwrap().is_none());
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn process_action_job(pool: PgPool) {
+    let router = setup(pool.clone(), &[OTHER_TENANT]).await;
+    create_account(&router, USER_ID).await;
+    let payload = json!({
+        "chunk_size": 2,
+        "actions": [
+            action(AB_INC, 100.0, "job-1"),
+            action(AB_EXP, 30.0, "job-2"),
+            // 余额不足，只有该条失败，同一块内的其他操作不受影响
+            action(AB_EXP, 1000.0, "job-3"),
+            action(AB_INC, 5.0, "job-4"),
+            // 订单号重复
+            action(AB_INC, 5.0, "job-1"),
+        ],
+    });
+    let (status, action_job) = post(&router, "/jobs/actions/new", payload).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(action_job["status"], "PENDING");
+    assert_eq!(action_job["total_count"], 5);
+
+    process(&pool).await;
+    let payload = json!({ "id": action_job["id"] });
+    let (status, action_job) = post(&router, "/jobs/actions/info", payload.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(action_job["status"], "COMPLETED");
+    assert_eq!(action_job["succeeded_count"], 3);
+    assert_eq!(action_job["failed_count"], 2);
+    assert_ne!(action_job["finished_at"], Value::Null);
+    assert_eq!(available_balance(&router).await, "75".parse().unwrap());
+
+    let mut payload = payload;
+    payload["status"] = json!("FAILED");
+    let (status, report) = post_text(&router, "/jobs/actions/report", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("seq,user_id,"));
+    assert!(lines[1].starts_with("2,1,"));
+    assert!(lines[2].starts_with("4,1,"));
+    assert!(lines[1].contains(",FAILED,"));
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn action_job_events(pool: PgPool) {
+    let router = setup(pool.clone(), &[OTHER_TENANT]).await;
+    create_account(&router, USER_ID).await;
+    let payload = json!({ "actions": [action(AB_INC, 10.0, "job-1")] });
+    let (status, action_job) = post(&router, "/jobs/actions/new", payload).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(action_job["chunk_size"], 500);
+    process(&pool).await;
+
+    // 任务已完成时推送一次进度后结束
+    let payload = json!({ "id": action_job["id"] });
+    let (status, events) = post_text(&router, "/jobs/actions/events", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(events.starts_with("event: progress\n"));
+    assert!(events.contains(r#""status":"COMPLETED""#));
+    assert_eq!(events.matches("event: progress").count(), 1);
+
+    // 其他租户不能查看任务
+    let payload = json!({ "id": action_job["id"] });
+    let (status, _) = post_as(&router, OTHER_API_KEY, "/jobs/actions/info", payload).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let payload = json!({ "actions": [] });
+    let (status, _) = post(&router, "/jobs/actions/new", payload).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+// 账户不存在、参数错误的操作只有该条失败，不中止任务
+#[sqlx::test(migrations = "./migrations")]
+async fn fail_invalid_items_only(pool: PgPool) {
+    let router = setup(pool.clone(), &[OTHER_TENANT]).await;
+    create_account(&router, USER_ID).await;
+    let mut missing_account = action(AB_INC, 5.0, "job-2");
+    missing_account["user_id"] = json!("2");
+    let mut invalid = action(AB_INC, 5.0, "job-3");
+    invalid["order_number"] = json!("job-3");
+    let payload = json!({
+        "actions": [action(AB_INC, 10.0, "job-1"), missing_account, invalid],
+    });
+    let (status, action_job) = post(&router, "/jobs/actions/new", payload).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    process(&pool).await;
+
+    let payload = json!({ "id": action_job["id"] });
+    let (_, action_job) = post(&router, "/jobs/actions/info", payload).await;
+    assert_eq!(action_job["status"], "COMPLETED");
+    assert_eq!(action_job["succeeded_count"], 1);
+    assert_eq!(action_job["failed_count"], 2);
+    assert_eq!(available_balance(&router).await, "10".parse().unwrap());
+}
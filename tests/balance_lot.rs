@@ -0,0 +1,183 @@
+mod common;
+
+use amazing::service::{balance_lot::BalanceLotService, client::Operator, import::ImportService};
+use axum::{http::StatusCode, Router};
+use common::*;
+use serde_json::json;
+use sqlx::{
+    types::{chrono::NaiveDateTime, Decimal},
+    PgPool,
+};
+
+// `GOLD`按批次跟踪余额，有效期 365 天
+const LOT_TTL: &str = "update asset_type set lot_ttl_days = 365 where id = 1";
+
+async fn setup_lot(pool: PgPool) -> Router {
+    let router = setup(pool, &[LOT_TTL]).await;
+    create_account(&router, USER_ID).await;
+    router
+}
+
+async fn act(router: &Router, action_type_id: i32, amount: f64, order_number: &str) {
+    let payload = json!([action(action_type_id, amount, order_number)]);
+    let (status, _) = post(router, "/accounts/actions", payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+async fn remaining_amounts(pool: &PgPool) -> Vec<Decimal> {
+    sqlx::query_scalar("select remaining_amount from balance_lot order by id")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+async fn expire_lot(pool: &PgPool, order_number: &str) {
+    sqlx::query(
+        "update balance_lot set expires_at = localtimestamp - interval '1 second' where order_number = $1",
+    )
+    .bind(format!("{order_number:0>32}"))
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+async fn freeze(router: &Router, amount: f64) {
+    let payload = json!({
+        "user_id": USER_ID,
+        "asset_type_id": GOLD,
+        "reference": "auth-1",
+        "amount": amount,
+        "expires_in": 3600,
+        "description": "integration test",
+    });
+    let (status, _) = post(router, "/holds/new", payload).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+async fn release(router: &Router) {
+    let payload = json!({ "reference": "auth-1", "description": "integration test" });
+    let (status, _) = post(router, "/holds/release", payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+// 扣减按先进先出消耗批次
+#[sqlx::test(migrations = "./migrations")]
+async fn debit_consumes_lots_fifo(pool: PgPool) {
+    let router = setup_lot(pool.clone()).await;
+    act(&router, AB_INC, 10.0, "1").await;
+    act(&router, AB_INC, 20.0, "2").await;
+    act(&router, AB_EXP, 15.0, "3").await;
+    assert_eq!(
+        remaining_amounts(&pool).await,
+        [Decimal::ZERO, Decimal::from(15)]
+    );
+}
+
+// 过期批次的剩余金额按`LOT_EXP`扣减，未过期批次不受影响
+#[sqlx::test(migrations = "./migrations")]
+async fn expire_remaining_lot_amounts(pool: PgPool) {
+    let router = setup_lot(pool.clone()).await;
+    act(&router, AB_INC, 10.0, "1").await;
+    act(&router, AB_EXP, 4.0, "2").await;
+    act(&router, AB_INC, 5.0, "3").await;
+    expire_lot(&pool, "1").await;
+    let repository = repository(pool.clone()).await;
+    assert_eq!(BalanceLotService::expire(&repository, 10).await.unwrap(), 1);
+    assert_eq!(BalanceLotService::expire(&repository, 10).await.unwrap(), 0);
+    assert_eq!(available_balance(&router).await, Decimal::from(5));
+    assert_eq!(
+        remaining_amounts(&pool).await,
+        [Decimal::ZERO, Decimal::from(5)]
+    );
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": GOLD });
+    let (status, verify) = post(&router, "/accounts/logs/verify", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(verify["verified"], 4);
+    assert_eq!(verify["broken_log_id"], json!(null));
+}
+
+// 处理失败的账户不阻塞之后的账户
+#[sqlx::test(migrations = "./migrations")]
+async fn expire_skips_failed_accounts(pool: PgPool) {
+    let router = setup_lot(pool.clone()).await;
+    create_account(&router, 2).await;
+    act(&router, AB_INC, 10.0, "1").await;
+    let mut payload = action(AB_INC, 10.0, "2");
+    payload["user_id"] = json!(2);
+    let (status, _) = post(&router, "/accounts/actions", json!([payload])).await;
+    assert_eq!(status, StatusCode::OK);
+    expire_lot(&pool, "1").await;
+    expire_lot(&pool, "2").await;
+    sqlx::query("update account set is_active = false where user_id = $1")
+        .bind(USER_ID.to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
+    let repository = repository(pool.clone()).await;
+    assert_eq!(BalanceLotService::expire(&repository, 1).await.unwrap(), 1);
+    assert_eq!(
+        remaining_amounts(&pool).await,
+        [Decimal::from(10), Decimal::ZERO]
+    );
+}
+
+// 冻结、解冻不产生新批次，解冻后的余额保持原批次的过期时间
+#[sqlx::test(migrations = "./migrations")]
+async fn freeze_and_release_keep_lot(pool: PgPool) {
+    let router = setup_lot(pool.clone()).await;
+    act(&router, AB_INC, 10.0, "1").await;
+    let expires_at: NaiveDateTime = sqlx::query_scalar("select expires_at from balance_lot")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    freeze(&router, 4.0).await;
+    release(&router).await;
+    assert_eq!(remaining_amounts(&pool).await, [Decimal::from(10)]);
+    let released: NaiveDateTime = sqlx::query_scalar("select expires_at from balance_lot")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(released, expires_at);
+}
+
+// 过期金额中冻结的部分待解冻后再扣减
+#[sqlx::test(migrations = "./migrations")]
+async fn expire_frozen_amount_after_release(pool: PgPool) {
+    let router = setup_lot(pool.clone()).await;
+    act(&router, AB_INC, 10.0, "1").await;
+    freeze(&router, 4.0).await;
+    expire_lot(&pool, "1").await;
+    let repository = repository(pool.clone()).await;
+    assert_eq!(BalanceLotService::expire(&repository, 10).await.unwrap(), 1);
+    assert_eq!(BalanceLotService::expire(&repository, 10).await.unwrap(), 0);
+    assert_eq!(available_balance(&router).await, Decimal::ZERO);
+    assert_eq!(remaining_amounts(&pool).await, [Decimal::from(4)]);
+    release(&router).await;
+    assert_eq!(BalanceLotService::expire(&repository, 10).await.unwrap(), 1);
+    assert_eq!(available_balance(&router).await, Decimal::ZERO);
+    assert_eq!(remaining_amounts(&pool).await, [Decimal::ZERO]);
+}
+
+// 导入的历史数据从原始时间起算有效期
+#[sqlx::test(migrations = "./migrations")]
+async fn import_lot_expires_from_created_at(pool: PgPool) {
+    let _router = setup_lot(pool.clone()).await;
+    let file = "\
+user_id,asset_type,action_type,amount,order_number,created_at,description
+1,GOLD,AB_INC,100,legacy-1,2020-01-01T00:00:00,
+";
+    let operator = Operator {
+        client_id: 0,
+        tenant_id: 1,
+        name: "importer".to_string(),
+    };
+    let repository = repository(pool.clone()).await;
+    ImportService::import(&repository, file.as_bytes(), &mut Vec::new(), 10, &operator)
+        .await
+        .unwrap();
+    let expires_at: NaiveDateTime = sqlx::query_scalar("select expires_at from balance_lot")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(expires_at.to_string(), "2020-12-31 00:00:00");
+}
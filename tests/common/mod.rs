@@ -0,0 +1,215 @@
+// 集成测试公共方法
+// 测试通过`#[sqlx::test]`为每个用例创建一次性数据库并执行`migrations/`，需设置`DATABASE_URL`
+#![allow(dead_code)]
+
+use amazing::{
+    repository::postgres::PgRepository,
+    route,
+    service::{
+        action_type::ActionTypeService, asset_type::AssetTypeService, client::ClientService,
+        fee_rule::FeeRuleService, limit_rule::LimitRuleService,
+    },
+};
+use axum::{
+    body::{self, Body},
+    http::{header, Request, StatusCode},
+    Router,
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use sqlx::{types::Decimal, PgPool};
+use tower::ServiceExt;
+
+pub const API_KEY: &str = "integration-test-api-key";
+// 第二个租户的客户端，数据见`OTHER_TENANT`
+pub const OTHER_API_KEY: &str = "integration-test-other-api-key";
+pub const OTHER_GOLD: i32 = 1000;
+pub const OTHER_SILVER: i32 = 1001;
+pub const OTHER_AB_INC: i32 = 1000;
+pub const OTHER_AB_EXP: i32 = 1001;
+// 第二个租户收取手续费的平台账户
+pub const OTHER_HOUSE_USER_ID: &str = "house";
+// 第二个租户`SILVER`的发行、销毁系统账户
+pub const OTHER_ISSUANCE_USER_ID: &str = "issuance";
+pub const OTHER_BURN_USER_ID: &str = "burn";
+pub const USER_ID: i32 = 1;
+pub const GOLD: i32 = 1;
+pub const AB_INC: i32 = 1;
+pub const AB_EXP: i32 = 3;
+
+// 第二个租户及其`GOLD`、`AB_INC`、`AB_EXP`
+// 使用固定`id`，不受`migrations`中初始数据数量影响
+pub const OTHER_TENANT: &str = r#"insert into tenant (name, is_active) values ('integration-test-other', true);
+    insert into asset_type (id, tenant_id, name, is_active) values (1000, 2, 'GOLD', true);
+    insert into action_type (
+        id,
+        tenant_id,
+        name,
+        available_balance_change,
+        frozen_balance_change,
+        total_income_change,
+        total_expense_change,
+        is_active
+    )
+    values (1000, 2, 'AB_INC', 'INC', 'NONE', 'INC', 'NONE', true),
+        (1001, 2, 'AB_EXP', 'DEC', 'NONE', 'NONE', 'INC', true);"#;
+
+// 第二个租户启用复式记账的`SILVER`及其发行、销毁账户操作类型，需在`OTHER_TENANT`之后写入
+pub const OTHER_SILVER_DOUBLE_ENTRY: &str = r#"insert into asset_type (id, tenant_id, name, issuance_user_id, burn_user_id, is_double_entry, is_active)
+    values (1001, 2, 'SILVER', 'issuance', 'burn', true, true);
+    insert into action_type (
+        id,
+        tenant_id,
+        name,
+        available_balance_change,
+        frozen_balance_change,
+        total_income_change,
+        total_expense_change,
+        is_active
+    )
+    values (1004, 2, 'SYS_ISS', 'DEC', 'NONE', 'NONE', 'INC', false),
+        (1005, 2, 'SYS_BRN', 'INC', 'NONE', 'INC', 'NONE', false);"#;
+
+// 依次写入用例所需的数据`fixtures`及已存在租户的客户端，返回连接到一次性数据库的路由
+pub async fn setup(pool: PgPool, fixtures: &[&str]) -> Router {
+    for fixture in fixtures {
+        sqlx::raw_sql(fixture).execute(&pool).await.unwrap();
+    }
+    for (tenant_id, name, api_key) in [
+        (1, "integration-test", API_KEY),
+        (2, "integration-test-other", OTHER_API_KEY),
+    ] {
+        sqlx::query(
+            r#"insert into client (tenant_id, name, api_key_hash, is_active)
+            select $1, $2, $3, true where exists (select 1 from tenant where id = $1)"#,
+        )
+        .bind(tenant_id)
+        .bind(name)
+        .bind(hex::encode(Sha256::digest(api_key.as_bytes())))
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    route::api::router(repository(pool).await)
+}
+
+// 连接到一次性数据库并加载其配置缓存的存储，缓存随存储创建，用例之间互不影响
+pub async fn repository(pool: PgPool) -> PgRepository {
+    let repository = PgRepository::new(pool);
+    AssetTypeService::init(&repository).await.unwrap();
+    ActionTypeService::init(&repository).await.unwrap();
+    ClientService::init(&repository).await.unwrap();
+    LimitRuleService::init(&repository, false).await.unwrap();
+    FeeRuleService::init(&repository).await.unwrap();
+    repository
+}
+
+pub async fn post(router: &Router, path: &str, payload: Value) -> (StatusCode, Value) {
+    post_as(router, API_KEY, path, payload).await
+}
+
+pub async fn post_as(
+    router: &Router,
+    api_key: &str,
+    path: &str,
+    payload: Value,
+) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(path)
+        .header(header::AUTHORIZATION, format!("Bearer {api_key}"))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, value)
+}
+
+// 返回响应头`name`的值，用于通过响应头返回结果的接口
+pub async fn post_header(
+    router: &Router,
+    api_key: &str,
+    path: &str,
+    payload: Value,
+    name: &str,
+) -> (StatusCode, Option<String>) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(path)
+        .header(header::AUTHORIZATION, format!("Bearer {api_key}"))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let value = response
+        .headers()
+        .get(name)
+        .map(|value| value.to_str().unwrap().to_string());
+    (response.status(), value)
+}
+
+// 以文本形式返回响应，用于非 JSON 响应
+pub async fn post_text(router: &Router, path: &str, payload: Value) -> (StatusCode, String) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(path)
+        .header(header::AUTHORIZATION, format!("Bearer {API_KEY}"))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+pub async fn get_as(router: &Router, api_key: &str, path: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("GET")
+        .uri(path)
+        .header(header::AUTHORIZATION, format!("Bearer {api_key}"))
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, value)
+}
+
+pub async fn create_account(router: &Router, user_id: i32) {
+    let payload = json!({ "user_id": user_id, "asset_type_id": GOLD });
+    let (status, _) = post(router, "/accounts/new", payload).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+pub fn action(action_type_id: i32, amount: f64, order_number: &str) -> Value {
+    json!({
+        "user_id": USER_ID,
+        "asset_type_id": GOLD,
+        "action_type_id": action_type_id,
+        "amount": amount,
+        "order_number": format!("{order_number:0>32}"),
+        "description": "integration test",
+    })
+}
+
+pub async fn available_balance(router: &Router) -> Decimal {
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": GOLD });
+    let (status, account) = post(router, "/accounts/info", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    account["available_balance"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
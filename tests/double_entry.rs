@@ -0,0 +1,167 @@
+mod common;
+
+use axum::{http::StatusCode, Router};
+use common::*;
+use serde_json::{json, Value};
+use sqlx::{types::Decimal, PgPool};
+
+// 第二个租户的双边账户操作类型：转出、转入
+const TRANSFER: &str = r#"insert into action_type (
+        id,
+        tenant_id,
+        name,
+        available_balance_change,
+        frozen_balance_change,
+        total_income_change,
+        total_expense_change,
+        is_single_sided,
+        is_active
+    )
+    values (1006, 2, 'TRF_OUT', 'DEC', 'NONE', 'NONE', 'INC', false, true),
+        (1007, 2, 'TRF_IN', 'INC', 'NONE', 'INC', 'NONE', false, true);"#;
+const OTHER_TRF_OUT: i32 = 1006;
+const OTHER_TRF_IN: i32 = 1007;
+const OTHER_USER_ID: i32 = 2;
+
+// 第二个租户的`SILVER`启用复式记账，发行、销毁系统账户见`common::OTHER_SILVER_DOUBLE_ENTRY`
+fn silver_action(action_type_id: i32, amount: f64, order_number: &str) -> Value {
+    user_silver_action(USER_ID, action_type_id, amount, order_number)
+}
+
+fn user_silver_action(user_id: i32, action_type_id: i32, amount: f64, order_number: &str) -> Value {
+    json!({
+        "user_id": user_id,
+        "asset_type_id": OTHER_SILVER,
+        "action_type_id": action_type_id,
+        "amount": amount,
+        "order_number": format!("{order_number:0>32}"),
+        "description": "integration test",
+    })
+}
+
+async fn other_actions(router: &Router, payload: Value) -> StatusCode {
+    post_as(router, OTHER_API_KEY, "/accounts/actions", payload)
+        .await
+        .0
+}
+
+async fn create_silver_account(router: &Router, user_id: Value) {
+    let payload = json!({ "user_id": user_id, "asset_type_id": OTHER_SILVER });
+    let (status, _) = post_as(router, OTHER_API_KEY, "/accounts/new", payload).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+async fn silver_balance(router: &Router, user_id: Value) -> Decimal {
+    let payload = json!({ "user_id": user_id, "asset_type_id": OTHER_SILVER });
+    let (status, account) = post_as(router, OTHER_API_KEY, "/accounts/info", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    account["available_balance"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn generate_counter_entries(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT, OTHER_SILVER_DOUBLE_ENTRY, TRANSFER]).await;
+    create_silver_account(&router, json!(USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_ISSUANCE_USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_BURN_USER_ID)).await;
+    let payload = json!([silver_action(OTHER_AB_INC, 100.0, "1")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    let payload = json!([silver_action(OTHER_AB_EXP, 30.0, "2")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    let user = silver_balance(&router, json!(USER_ID)).await;
+    let issuance = silver_balance(&router, json!(OTHER_ISSUANCE_USER_ID)).await;
+    let burn = silver_balance(&router, json!(OTHER_BURN_USER_ID)).await;
+    assert_eq!(user, Decimal::new(70, 0));
+    assert_eq!(issuance, Decimal::new(-100, 0));
+    assert_eq!(burn, Decimal::new(30, 0));
+    assert!((user + issuance + burn).is_zero());
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn reject_missing_system_account(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT, OTHER_SILVER_DOUBLE_ENTRY, TRANSFER]).await;
+    create_silver_account(&router, json!(USER_ID)).await;
+    let payload = json!([silver_action(OTHER_AB_INC, 100.0, "1")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::FORBIDDEN);
+    assert_eq!(silver_balance(&router, json!(USER_ID)).await, Decimal::ZERO);
+}
+
+// 单边操作按批次内的净额生成一条对应分录
+#[sqlx::test(migrations = "./migrations")]
+async fn counter_entry_for_net_imbalance(pool: PgPool) {
+    let router = setup(
+        pool.clone(),
+        &[OTHER_TENANT, OTHER_SILVER_DOUBLE_ENTRY, TRANSFER],
+    )
+    .await;
+    create_silver_account(&router, json!(USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_ISSUANCE_USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_BURN_USER_ID)).await;
+    let payload = json!([
+        silver_action(OTHER_AB_INC, 100.0, "1"),
+        user_silver_action(OTHER_USER_ID, OTHER_AB_INC, 20.0, "1"),
+    ]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    let payload = json!([
+        user_silver_action(OTHER_USER_ID, OTHER_AB_INC, 20.0, "2"),
+        silver_action(OTHER_AB_EXP, 50.0, "2"),
+    ]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    assert_eq!(
+        silver_balance(&router, json!(OTHER_ISSUANCE_USER_ID)).await,
+        Decimal::new(-120, 0)
+    );
+    assert_eq!(
+        silver_balance(&router, json!(OTHER_BURN_USER_ID)).await,
+        Decimal::new(30, 0)
+    );
+    let count: i64 = sqlx::query_scalar(
+        "select count(*) from account_log join action_type on action_type.id = account_log.action_type_id where action_type.name like 'SYS_%'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(count, 2);
+}
+
+// 双边操作不生成对应分录，批次内合计不为零时整批失败
+#[sqlx::test(migrations = "./migrations")]
+async fn reject_unbalanced_two_sided_actions(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT, OTHER_SILVER_DOUBLE_ENTRY, TRANSFER]).await;
+    create_silver_account(&router, json!(USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_ISSUANCE_USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_BURN_USER_ID)).await;
+    let payload = json!([silver_action(OTHER_AB_INC, 100.0, "1")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    let payload = json!([
+        silver_action(OTHER_TRF_OUT, 30.0, "2"),
+        user_silver_action(OTHER_USER_ID, OTHER_TRF_IN, 20.0, "2"),
+    ]);
+    assert_eq!(
+        other_actions(&router, payload).await,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let payload = json!([
+        silver_action(OTHER_TRF_OUT, 30.0, "3"),
+        user_silver_action(OTHER_USER_ID, OTHER_TRF_IN, 30.0, "3"),
+    ]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    assert_eq!(
+        silver_balance(&router, json!(OTHER_USER_ID)).await,
+        Decimal::new(30, 0)
+    );
+    assert_eq!(
+        silver_balance(&router, json!(OTHER_ISSUANCE_USER_ID)).await,
+        Decimal::new(-100, 0)
+    );
+    assert_eq!(
+        silver_balance(&router, json!(OTHER_BURN_USER_ID)).await,
+        Decimal::ZERO
+    );
+}
@@ -0,0 +1,171 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::*;
+use serde_json::{json, Value};
+use sqlx::{types::Decimal, PgPool};
+
+const SILVER: i32 = 2;
+const BRONZE: i32 = 1002;
+const HOUSE_USER_ID: &str = "house";
+
+// `BRONZE`按 1:1 兑换`GOLD`，兑换手续费 1，每日兑换限额 20
+const EXCHANGE_RULES: &str = r#"insert into asset_type (id, tenant_id, name, is_active) values (1002, 1, 'BRONZE', true);
+    insert into exchange_rate (from_asset_type_id, to_asset_type_id, rate, spread, effective_at, is_active)
+    values (1002, 1, 1, 0, now() - interval '1 minute', true);
+    insert into fee_rule (tenant_id, asset_type_id, action_type_id, fixed_amount, rate, house_user_id, description, is_active)
+    select 1, 1002, id, 1, 0, 'house', 'BRONZE 兑换手续费', true from action_type where tenant_id = 1 and name = 'EXC_OUT';
+    insert into limit_rule (tenant_id, asset_type_id, action_type_id, window_seconds, max_amount, description, is_active)
+    select 1, 1002, id, 86400, 20, 'BRONZE 每日兑换 20', true from action_type where tenant_id = 1 and name = 'EXC_OUT';"#;
+
+// 用户持有 100 SILVER，汇率 1 SILVER = 0.1 GOLD，点差 1%
+async fn setup_exchange(pool: PgPool) -> axum::Router {
+    sqlx::query(
+        r#"insert into exchange_rate (from_asset_type_id, to_asset_type_id, rate, spread, effective_at, is_active)
+        values ($1, $2, 0.1, 0.01, now() - interval '1 minute', true)"#,
+    )
+    .bind(SILVER)
+    .bind(GOLD)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let router = setup(pool, &[EXCHANGE_RULES]).await;
+    create_account(&router, USER_ID).await;
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": SILVER });
+    let (status, _) = post(&router, "/accounts/new", payload).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let payload = json!([{
+        "user_id": USER_ID,
+        "asset_type_id": SILVER,
+        "action_type_id": AB_INC,
+        "amount": 100.0,
+        "order_number": format!("{:0>32}", 1),
+        "description": "integration test",
+    }]);
+    let (status, _) = post(&router, "/accounts/actions", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    router
+}
+
+fn exchange(amount: f64, order_number: &str) -> Value {
+    json!({
+        "user_id": USER_ID,
+        "from_asset_type_id": SILVER,
+        "to_asset_type_id": GOLD,
+        "amount": amount,
+        "order_number": format!("{order_number:0>32}"),
+        "description": "integration test",
+    })
+}
+
+async fn create_bronze_account(router: &axum::Router, user_id: Value) {
+    let payload = json!({ "user_id": user_id, "asset_type_id": BRONZE });
+    let (status, _) = post(router, "/accounts/new", payload).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+async fn bronze_balance(router: &axum::Router, user_id: Value) -> Decimal {
+    let payload = json!({ "user_id": user_id, "asset_type_id": BRONZE });
+    let (status, account) = post(router, "/accounts/info", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    account["available_balance"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+async fn logs(router: &axum::Router, asset_type_id: i32) -> Vec<Value> {
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": asset_type_id });
+    let (status, account_logs) = post(router, "/accounts/logs", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    account_logs.as_array().unwrap().clone()
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn exchange_updates_both_accounts(pool: PgPool) {
+    let router = setup_exchange(pool).await;
+    let (status, exchange_log) = post(&router, "/accounts/exchange", exchange(50.0, "2")).await;
+    assert_eq!(status, StatusCode::OK);
+    let to_amount: Decimal = exchange_log["to_amount"].as_str().unwrap().parse().unwrap();
+    assert_eq!(to_amount, "4.95".parse().unwrap());
+    assert_eq!(available_balance(&router).await, "4.95".parse().unwrap());
+    let silver_logs = logs(&router, SILVER).await;
+    let gold_logs = logs(&router, GOLD).await;
+    assert_eq!(silver_logs[0]["order_number"], format!("{:0>32}", 2));
+    assert_eq!(silver_logs[0]["order_number"], gold_logs[0]["order_number"]);
+    let available_balance_after: Decimal = silver_logs[0]["available_balance_after"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(available_balance_after, Decimal::from(50));
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn exchange_rejects_insufficient_balance(pool: PgPool) {
+    let router = setup_exchange(pool).await;
+    let (status, _) = post(&router, "/accounts/exchange", exchange(100.5, "2")).await;
+    assert_eq!(status, StatusCode::PAYMENT_REQUIRED);
+    assert_eq!(available_balance(&router).await, Decimal::ZERO);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn exchange_rejects_duplicate_order(pool: PgPool) {
+    let router = setup_exchange(pool).await;
+    let (status, _) = post(&router, "/accounts/exchange", exchange(10.0, "2")).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = post(&router, "/accounts/exchange", exchange(10.0, "2")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(available_balance(&router).await, "0.99".parse().unwrap());
+}
+
+// 没有反向汇率
+#[sqlx::test(migrations = "./migrations")]
+async fn exchange_requires_rate(pool: PgPool) {
+    let router = setup_exchange(pool).await;
+    let mut payload = exchange(1.0, "2");
+    payload["from_asset_type_id"] = json!(GOLD);
+    payload["to_asset_type_id"] = json!(SILVER);
+    let (status, _) = post(&router, "/accounts/exchange", payload).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+// 兑换与账户操作相同，收取手续费并检查限额
+#[sqlx::test(migrations = "./migrations")]
+async fn exchange_charges_fee_and_checks_limit(pool: PgPool) {
+    let router = setup_exchange(pool).await;
+    create_bronze_account(&router, json!(USER_ID)).await;
+    create_bronze_account(&router, json!(HOUSE_USER_ID)).await;
+    let payload = json!([{
+        "user_id": USER_ID,
+        "asset_type_id": BRONZE,
+        "action_type_id": AB_INC,
+        "amount": 30.0,
+        "order_number": format!("{:0>32}", 1),
+        "description": "integration test",
+    }]);
+    let (status, _) = post(&router, "/accounts/actions", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    let mut payload = exchange(10.0, "2");
+    payload["from_asset_type_id"] = json!(BRONZE);
+    let (status, _) = post(&router, "/accounts/exchange", payload.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        bronze_balance(&router, json!(USER_ID)).await,
+        Decimal::from(19)
+    );
+    assert_eq!(
+        bronze_balance(&router, json!(HOUSE_USER_ID)).await,
+        Decimal::ONE
+    );
+    assert_eq!(available_balance(&router).await, Decimal::from(10));
+    payload["amount"] = json!(11.0);
+    payload["order_number"] = json!(format!("{:0>32}", 3));
+    let (status, _) = post(&router, "/accounts/exchange", payload).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(
+        bronze_balance(&router, json!(USER_ID)).await,
+        Decimal::from(19)
+    );
+}
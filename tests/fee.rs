@@ -0,0 +1,263 @@
+mod common;
+
+use axum::{http::StatusCode, Router};
+use common::*;
+use serde_json::{json, Value};
+use sqlx::{types::Decimal, PgPool};
+
+// 同样收取手续费的另一支出账户操作类型
+const OTHER_AB_WDR: i32 = 1006;
+
+// 第二个租户的手续费账户操作类型及`GOLD AB_EXP`、`GOLD AB_WDR`手续费规则：1 + 1%，最低 1.5，最高 5
+const FEE_RULE: &str = r#"insert into action_type (
+        id,
+        tenant_id,
+        name,
+        available_balance_change,
+        frozen_balance_change,
+        total_income_change,
+        total_expense_change,
+        is_active
+    )
+    values (1002, 2, 'FEE_EXP', 'DEC', 'NONE', 'NONE', 'INC', false),
+        (1003, 2, 'FEE_INC', 'INC', 'NONE', 'INC', 'NONE', false),
+        (1006, 2, 'AB_WDR', 'DEC', 'NONE', 'NONE', 'INC', true);
+    insert into fee_rule (tenant_id, asset_type_id, action_type_id, fixed_amount, rate, min_amount, max_amount, house_user_id, description, is_active)
+    values (2, 1000, 1001, 1, 0.01, 1.5, 5, 'house', 'GOLD 支出手续费', true),
+        (2, 1000, 1006, 1, 0.01, 1.5, 5, 'house', 'GOLD 提现手续费', true);"#;
+
+fn other_action(action_type_id: i32, amount: f64, order_number: &str) -> Value {
+    json!({
+        "user_id": USER_ID,
+        "asset_type_id": OTHER_GOLD,
+        "action_type_id": action_type_id,
+        "amount": amount,
+        "order_number": format!("{order_number:0>32}"),
+        "description": "integration test",
+    })
+}
+
+async fn other_actions(router: &Router, payload: Value) -> StatusCode {
+    post_as(router, OTHER_API_KEY, "/accounts/actions", payload)
+        .await
+        .0
+}
+
+async fn create_other_account(router: &Router, user_id: Value) {
+    let payload = json!({ "user_id": user_id, "asset_type_id": OTHER_GOLD });
+    let (status, _) = post_as(router, OTHER_API_KEY, "/accounts/new", payload).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+async fn other_available_balance(router: &Router, user_id: Value) -> Decimal {
+    let payload = json!({ "user_id": user_id, "asset_type_id": OTHER_GOLD });
+    let (status, account) = post_as(router, OTHER_API_KEY, "/accounts/info", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    account["available_balance"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn charge_fee_to_house_account(pool: PgPool) {
+    let router = setup(pool.clone(), &[OTHER_TENANT, FEE_RULE]).await;
+    create_other_account(&router, json!(USER_ID)).await;
+    create_other_account(&router, json!(OTHER_HOUSE_USER_ID)).await;
+    let payload = json!([other_action(OTHER_AB_INC, 100.0, "1")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    // 1 + 50 × 1% = 1.5
+    let payload = json!([other_action(OTHER_AB_EXP, 50.0, "2")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    assert_eq!(
+        other_available_balance(&router, json!(USER_ID)).await,
+        Decimal::new(485, 1)
+    );
+    assert_eq!(
+        other_available_balance(&router, json!(OTHER_HOUSE_USER_ID)).await,
+        Decimal::new(15, 1)
+    );
+    // 手续费扣减及平台账户入账使用同一手续费订单号
+    let order_number = format!("{:0>32}", "2");
+    let count: i64 = sqlx::query_scalar("select count(*) from account_log where order_number = $1")
+        .bind(&order_number)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    let count: i64 = sqlx::query_scalar("select count(*) from account_log where order_number = $1")
+        .bind(format!("fee-{USER_ID}-{OTHER_AB_EXP}-{order_number}"))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+}
+
+// 同一用户不同账户操作类型使用相同订单号时，手续费日志不冲突
+#[sqlx::test(migrations = "./migrations")]
+async fn same_order_number_for_different_action_types(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT, FEE_RULE]).await;
+    create_other_account(&router, json!(USER_ID)).await;
+    create_other_account(&router, json!(OTHER_HOUSE_USER_ID)).await;
+    let payload = json!([other_action(OTHER_AB_INC, 100.0, "1")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    let payload = json!([other_action(OTHER_AB_EXP, 50.0, "2")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    let payload = json!([other_action(OTHER_AB_WDR, 10.0, "2")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    // 100 - 50 - 1.5 - 10 - 1.5
+    assert_eq!(
+        other_available_balance(&router, json!(USER_ID)).await,
+        Decimal::new(370, 1)
+    );
+    assert_eq!(
+        other_available_balance(&router, json!(OTHER_HOUSE_USER_ID)).await,
+        Decimal::new(30, 1)
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn reject_insufficient_balance_for_fee(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT, FEE_RULE]).await;
+    create_other_account(&router, json!(USER_ID)).await;
+    create_other_account(&router, json!(OTHER_HOUSE_USER_ID)).await;
+    let payload = json!([other_action(OTHER_AB_INC, 50.0, "1")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    // 余额足够支付本金但不足以支付手续费
+    let payload = json!([other_action(OTHER_AB_EXP, 50.0, "2")]);
+    assert_eq!(
+        other_actions(&router, payload).await,
+        StatusCode::PAYMENT_REQUIRED
+    );
+    assert_eq!(
+        other_available_balance(&router, json!(USER_ID)).await,
+        Decimal::new(50, 0)
+    );
+    assert_eq!(
+        other_available_balance(&router, json!(OTHER_HOUSE_USER_ID)).await,
+        Decimal::ZERO
+    );
+}
+
+// 部分成功模式下失败的操作不收取手续费
+#[sqlx::test(migrations = "./migrations")]
+async fn partial_mode_skips_fee_of_failed_action(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT, FEE_RULE]).await;
+    create_other_account(&router, json!(USER_ID)).await;
+    create_other_account(&router, json!(OTHER_HOUSE_USER_ID)).await;
+    let payload = json!([other_action(OTHER_AB_INC, 60.0, "1")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    // 事务开始前两个操作的余额检查均可通过，第二个操作在事务内余额不足
+    let payload = json!([
+        other_action(OTHER_AB_EXP, 50.0, "2"),
+        other_action(OTHER_AB_EXP, 50.0, "3"),
+    ]);
+    let (status, results) = post_as(
+        &router,
+        OTHER_API_KEY,
+        "/accounts/actions?mode=partial",
+        payload,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(results[0]["status"], "SUCCEEDED");
+    assert_eq!(results[1]["status"], "FAILED");
+    assert_eq!(results[1]["code"], 402);
+    assert_eq!(
+        other_available_balance(&router, json!(USER_ID)).await,
+        Decimal::new(85, 1)
+    );
+    assert_eq!(
+        other_available_balance(&router, json!(OTHER_HOUSE_USER_ID)).await,
+        Decimal::new(15, 1)
+    );
+}
+
+// 预览结果包括收取手续费的平台账户
+#[sqlx::test(migrations = "./migrations")]
+async fn dry_run_includes_house_account(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT, FEE_RULE]).await;
+    create_other_account(&router, json!(USER_ID)).await;
+    create_other_account(&router, json!(OTHER_HOUSE_USER_ID)).await;
+    let payload = json!([other_action(OTHER_AB_INC, 60.0, "1")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    let payload = json!([other_action(OTHER_AB_EXP, 50.0, "2")]);
+    let (status, preview) = post_as(
+        &router,
+        OTHER_API_KEY,
+        "/accounts/actions?dry_run=true",
+        payload,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let balances: Vec<_> = preview["accounts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|account| {
+            (
+                account["user_id"].clone(),
+                account["available_balance"]
+                    .as_str()
+                    .unwrap()
+                    .parse::<Decimal>()
+                    .unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        balances,
+        [
+            (json!(USER_ID), Decimal::new(85, 1)),
+            (json!(OTHER_HOUSE_USER_ID), Decimal::new(15, 1))
+        ]
+    );
+    assert_eq!(
+        other_available_balance(&router, json!(OTHER_HOUSE_USER_ID)).await,
+        Decimal::ZERO
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn fee_legs_share_transaction_id(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT, FEE_RULE]).await;
+    create_other_account(&router, json!(USER_ID)).await;
+    create_other_account(&router, json!(OTHER_HOUSE_USER_ID)).await;
+    let payload = json!([other_action(OTHER_AB_INC, 100.0, "1")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    let payload = json!([other_action(OTHER_AB_EXP, 50.0, "2")]);
+    let (status, transaction_id) = post_header(
+        &router,
+        OTHER_API_KEY,
+        "/accounts/actions",
+        payload,
+        "x-transaction-id",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let transaction_id: i64 = transaction_id.unwrap().parse().unwrap();
+    // 本金、手续费扣减及平台账户入账属于同一交易
+    let payload = json!({ "transaction_id": transaction_id });
+    let (status, legs) = post_as(
+        &router,
+        OTHER_API_KEY,
+        "/accounts/transactions/info",
+        payload.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let legs = legs.as_array().unwrap();
+    assert_eq!(legs.len(), 3);
+    assert!(legs
+        .iter()
+        .all(|leg| leg["transaction_id"] == transaction_id));
+    let house_legs = legs
+        .iter()
+        .filter(|leg| leg["user_id"] == OTHER_HOUSE_USER_ID)
+        .count();
+    assert_eq!(house_legs, 1);
+    // 其他租户不能查询
+    let (status, _) = post(&router, "/accounts/transactions/info", payload).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
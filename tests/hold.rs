@@ -0,0 +1,151 @@
+mod common;
+
+use amazing::service::hold::HoldService;
+use axum::{http::StatusCode, Router};
+use common::*;
+use serde_json::{json, Value};
+use sqlx::{types::Decimal, PgPool};
+use tokio::task::JoinSet;
+
+const REFERENCE: &str = "auth-1";
+
+// 可用余额 100
+async fn setup_hold(pool: PgPool) -> Router {
+    let router = setup(pool, &[]).await;
+    create_account(&router, USER_ID).await;
+    let (status, _) = post(
+        &router,
+        "/accounts/actions",
+        json!([action(AB_INC, 100.0, "1")]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    router
+}
+
+fn hold(amount: f64) -> Value {
+    json!({
+        "user_id": USER_ID,
+        "asset_type_id": GOLD,
+        "reference": REFERENCE,
+        "amount": amount,
+        "expires_in": 3600,
+        "description": "integration test",
+    })
+}
+
+fn capture(amount: Option<f64>, order_number: &str) -> Value {
+    json!({
+        "reference": REFERENCE,
+        "amount": amount,
+        "order_number": format!("{order_number:0>32}"),
+        "description": "integration test",
+    })
+}
+
+fn decimal(value: &Value) -> Decimal {
+    value.as_str().unwrap().parse().unwrap()
+}
+
+async fn frozen_balance(router: &Router) -> Decimal {
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": GOLD });
+    let (status, account) = post(router, "/accounts/info", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    decimal(&account["frozen_balance"])
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn partial_capture_then_release(pool: PgPool) {
+    let router = setup_hold(pool).await;
+    let (status, _) = post(&router, "/holds/new", hold(30.0)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(available_balance(&router).await, Decimal::from(70));
+    assert_eq!(frozen_balance(&router).await, Decimal::from(30));
+    let (status, captured) = post(&router, "/holds/capture", capture(Some(10.0), "2")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(captured["status"], "HELD");
+    assert_eq!(decimal(&captured["captured_amount"]), Decimal::from(10));
+    let payload = json!({ "reference": REFERENCE, "description": "integration test" });
+    let (status, released) = post(&router, "/holds/release", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(released["status"], "RELEASED");
+    assert_eq!(decimal(&released["released_amount"]), Decimal::from(20));
+    assert_eq!(available_balance(&router).await, Decimal::from(90));
+    assert_eq!(frozen_balance(&router).await, Decimal::ZERO);
+    let (status, _) = post(&router, "/holds/capture", capture(Some(1.0), "3")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn capture_remaining_amount(pool: PgPool) {
+    let router = setup_hold(pool).await;
+    let (status, _) = post(&router, "/holds/new", hold(30.0)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = post(&router, "/holds/capture", capture(Some(30.5), "2")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, captured) = post(&router, "/holds/capture", capture(None, "2")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(captured["status"], "CAPTURED");
+    assert_eq!(available_balance(&router).await, Decimal::from(70));
+    assert_eq!(frozen_balance(&router).await, Decimal::ZERO);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn create_rejects_insufficient_balance_and_duplicate(pool: PgPool) {
+    let router = setup_hold(pool).await;
+    let (status, _) = post(&router, "/holds/new", hold(100.5)).await;
+    assert_eq!(status, StatusCode::PAYMENT_REQUIRED);
+    let (status, _) = post(&router, "/holds/new", hold(10.0)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = post(&router, "/holds/new", hold(10.0)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(frozen_balance(&router).await, Decimal::from(10));
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn release_expired_holds(pool: PgPool) {
+    let router = setup_hold(pool.clone()).await;
+    let (status, _) = post(&router, "/holds/new", hold(30.0)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    sqlx::query("update hold set expires_at = localtimestamp - interval '1 second'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, _) = post(&router, "/holds/capture", capture(None, "2")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let repository = repository(pool).await;
+    assert_eq!(
+        HoldService::release_expired(&repository, 10).await.unwrap(),
+        1
+    );
+    assert_eq!(
+        HoldService::release_expired(&repository, 10).await.unwrap(),
+        0
+    );
+    let (status, expired) = post(&router, "/holds/info", json!({ "reference": REFERENCE })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(expired["status"], "EXPIRED");
+    assert_eq!(available_balance(&router).await, Decimal::from(100));
+    assert_eq!(frozen_balance(&router).await, Decimal::ZERO);
+}
+
+// 并发创建相同外部引用的预授权时只创建一次，其余返回冲突
+#[sqlx::test(migrations = "./migrations")]
+async fn concurrent_duplicate_holds_create_once(pool: PgPool) {
+    let router = setup_hold(pool).await;
+    let mut tasks = JoinSet::new();
+    for _ in 0..10 {
+        let router = router.clone();
+        tasks.spawn(async move { post(&router, "/holds/new", hold(10.0)).await.0 });
+    }
+    let responses = tasks.join_all().await;
+    let created = responses
+        .iter()
+        .filter(|&&status| status == StatusCode::CREATED)
+        .count();
+    assert_eq!(created, 1);
+    assert!(responses
+        .iter()
+        .all(|&status| status == StatusCode::CREATED || status == StatusCode::CONFLICT));
+    assert_eq!(frozen_balance(&router).await, Decimal::from(10));
+}
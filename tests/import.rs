@@ -0,0 +1,87 @@
+mod common;
+
+use amazing::service::{client::Operator, import::ImportService};
+use common::*;
+use sqlx::{types::chrono::NaiveDateTime, PgPool};
+
+const IMPORT_FILE: &str = "\
+user_id,asset_type,action_type,amount,order_number,created_at,description
+1,GOLD,AB_INC,100,legacy-1,2020-01-01T00:00:00,
+1,GOLD,AB_EXP,30,legacy-2,2020-01-02T00:00:00,兑换礼品
+1,BRONZE,AB_INC,1,legacy-3,2020-01-03T00:00:00,
+1,GOLD,AB_EXP,1000,legacy-4,2020-01-04T00:00:00,
+2,GOLD,AB_INC,5.5,legacy-5,2020-01-05T00:00:00,
+";
+
+fn operator() -> Operator {
+    Operator {
+        client_id: 0,
+        tenant_id: 1,
+        name: "importer".to_string(),
+    }
+}
+
+async fn import(pool: &PgPool) -> (String, (u64, u64, u64)) {
+    import_file(pool, IMPORT_FILE).await
+}
+
+async fn import_file(pool: &PgPool, file: &str) -> (String, (u64, u64, u64)) {
+    let repository = repository(pool.clone()).await;
+    let mut report = Vec::new();
+    let summary = ImportService::import(&repository, file.as_bytes(), &mut report, 10, &operator())
+        .await
+        .unwrap();
+    (
+        String::from_utf8(report).unwrap(),
+        (summary.imported, summary.skipped, summary.failed),
+    )
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn import_with_report(pool: PgPool) {
+    let router = setup(pool.clone(), &[]).await;
+    let (report, summary) = import(&pool).await;
+    // 第 5 行余额不足只回滚该行，其余行仍导入成功
+    assert_eq!(summary, (3, 0, 2));
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines[0], "line,order_number,status,message");
+    assert_eq!(lines[1], "2,legacy-1,imported,");
+    assert_eq!(lines[2], "3,legacy-2,imported,");
+    assert!(lines[3].starts_with("4,,failed,"));
+    assert!(lines[4].starts_with("5,legacy-4,failed,"));
+    assert_eq!(lines[5], "6,legacy-5,imported,");
+    assert_eq!(available_balance(&router).await, "70".parse().unwrap());
+    // 日志时间为原始时间
+    let created_at: NaiveDateTime =
+        sqlx::query_scalar("select created_at from account_log where order_number = 'legacy-1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(created_at.to_string(), "2020-01-01 00:00:00");
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn skip_imported_rows(pool: PgPool) {
+    let router = setup(pool.clone(), &[]).await;
+    import(&pool).await;
+    let (_, summary) = import(&pool).await;
+    assert_eq!(summary, (0, 3, 2));
+    assert_eq!(available_balance(&router).await, "70".parse().unwrap());
+}
+
+// 文件中订单重复的行跳过，不影响同一块内其他行
+#[sqlx::test(migrations = "./migrations")]
+async fn skip_duplicate_rows_in_file(pool: PgPool) {
+    let router = setup(pool.clone(), &[]).await;
+    let file = "\
+user_id,asset_type,action_type,amount,order_number,created_at,description
+1,GOLD,AB_INC,100,legacy-1,2020-01-01T00:00:00,
+1,GOLD,AB_INC,100,legacy-1,2020-01-01T00:00:00,
+1,GOLD,AB_EXP,30,legacy-1,2020-01-02T00:00:00,
+";
+    let (report, summary) = import_file(&pool, file).await;
+    assert_eq!(summary, (2, 1, 0));
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines[2], "3,legacy-1,skipped,与第 2 行订单重复");
+    assert_eq!(available_balance(&router).await, "70".parse().unwrap());
+}
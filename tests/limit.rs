@@ -0,0 +1,98 @@
+mod common;
+
+use axum::{http::StatusCode, Router};
+use common::*;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use tokio::task::JoinSet;
+
+// 第二个租户的`GOLD AB_INC`每日 100 及每日 3 次两条限额规则
+const LIMIT_RULES: &str = r#"insert into limit_rule (tenant_id, asset_type_id, action_type_id, window_seconds, max_amount, description, is_active)
+    values (2, 1000, 1000, 86400, 100, 'GOLD AB_INC 每日 100', true);
+    insert into limit_rule (tenant_id, window_seconds, max_count, description, is_active)
+    values (2, 86400, 3, '每日 3 次', true);"#;
+
+fn other_action(user_id: i32, amount: f64, order_number: &str) -> Value {
+    json!({
+        "user_id": user_id,
+        "asset_type_id": OTHER_GOLD,
+        "action_type_id": OTHER_AB_INC,
+        "amount": amount,
+        "order_number": format!("{order_number:0>32}"),
+        "description": "integration test",
+    })
+}
+
+async fn other_actions(router: &Router, payload: Value) -> StatusCode {
+    post_as(router, OTHER_API_KEY, "/accounts/actions", payload)
+        .await
+        .0
+}
+
+async fn create_other_account(router: &Router, user_id: i32) {
+    let payload = json!({ "user_id": user_id, "asset_type_id": OTHER_GOLD });
+    let (status, _) = post_as(router, OTHER_API_KEY, "/accounts/new", payload).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn reject_amount_exceeded(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT, LIMIT_RULES]).await;
+    create_other_account(&router, USER_ID).await;
+    create_other_account(&router, USER_ID + 1).await;
+    let status = other_actions(&router, json!([other_action(USER_ID, 60.0, "1")])).await;
+    assert_eq!(status, StatusCode::OK);
+    // 同一批次的操作合并计算
+    let payload = json!([
+        other_action(USER_ID, 20.0, "2"),
+        other_action(USER_ID, 20.5, "3"),
+    ]);
+    assert_eq!(
+        other_actions(&router, payload).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    let payload = json!([other_action(USER_ID, 40.0, "2")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    // 不同用户的限额相互独立
+    let payload = json!([other_action(USER_ID + 1, 100.0, "1")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn reject_count_exceeded(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT, LIMIT_RULES]).await;
+    create_other_account(&router, USER_ID).await;
+    for order_number in ["1", "2", "3"] {
+        let payload = json!([other_action(USER_ID, 1.0, order_number)]);
+        assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    }
+    let payload = json!([other_action(USER_ID, 1.0, "4")]);
+    assert_eq!(
+        other_actions(&router, payload).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+}
+
+// 同一用户的并发请求在事务内依次检查，不会同时通过检查
+#[sqlx::test(migrations = "./migrations")]
+async fn concurrent_actions_respect_count(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT, LIMIT_RULES]).await;
+    create_other_account(&router, USER_ID).await;
+    let mut tasks = JoinSet::new();
+    for index in 1..=10 {
+        let router = router.clone();
+        tasks.spawn(async move {
+            let payload = json!([other_action(USER_ID, 1.0, &index.to_string())]);
+            other_actions(&router, payload).await
+        });
+    }
+    let responses = tasks.join_all().await;
+    let succeeded = responses
+        .iter()
+        .filter(|&&status| status == StatusCode::OK)
+        .count();
+    assert_eq!(succeeded, 3);
+    assert!(responses
+        .iter()
+        .all(|&status| status == StatusCode::OK || status == StatusCode::TOO_MANY_REQUESTS));
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use amazing::{
+    model::account_log_partition::{AccountLogPartitionModel, PartitionStatus},
+    service::{
+        account_log_partition::AccountLogPartitionService, client::Operator, import::ImportService,
+        reconciliation::ReconciliationService,
+    },
+};
+use axum::http::StatusCode;
+use common::*;
+use serde_json::{json, Value};
+use sqlx::{types::chrono::NaiveDate, PgPool};
+
+fn operator() -> Operator {
+    Operator {
+        client_id: 0,
+        tenant_id: 1,
+        name: "importer".to_string(),
+    }
+}
+
+async fn import(pool: &PgPool, file: &str) -> (u64, u64, u64) {
+    let repository = repository(pool.clone()).await;
+    let summary = ImportService::import(&repository, file.as_bytes(), Vec::new(), 10, &operator())
+        .await
+        .unwrap();
+    (summary.imported, summary.skipped, summary.failed)
+}
+
+async fn partition_of(pool: &PgPool, order_number: &str) -> String {
+    sqlx::query_scalar("select tableoid::regclass::text from account_log where order_number = $1")
+        .bind(order_number)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn create_future_partitions(pool: PgPool) {
+    let _ = setup(pool.clone(), &[]).await;
+    let repository = repository(pool.clone()).await;
+    let today = NaiveDate::from_ymd_opt(2031, 5, 15).unwrap();
+    AccountLogPartitionService::create_partitions(&repository, today)
+        .await
+        .unwrap();
+    // 重复创建不做任何操作
+    AccountLogPartitionService::create_partitions(&repository, today)
+        .await
+        .unwrap();
+    let names: Vec<String> = AccountLogPartitionModel::fetch_all(&pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|partition| partition.name)
+        .filter(|name| name.starts_with("account_log_2031"))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "account_log_203105",
+            "account_log_203106",
+            "account_log_203107"
+        ]
+    );
+    let file = "\
+user_id,asset_type,action_type,amount,order_number,created_at
+1,GOLD,AB_INC,10,future-1,2031-06-01T00:00:00
+1,GOLD,AB_INC,10,legacy-1,2001-01-01T00:00:00
+";
+    assert_eq!(import(&pool, file).await, (2, 0, 0));
+    assert_eq!(partition_of(&pool, "future-1").await, "account_log_203106");
+    // 未创建分区的月份写入默认分区
+    assert_eq!(partition_of(&pool, "legacy-1").await, "account_log_default");
+    // 之后创建分区时为该月创建分区并移入默认分区中的日志，订单号仍不能重复导入
+    AccountLogPartitionService::create_partitions(&repository, today)
+        .await
+        .unwrap();
+    assert_eq!(partition_of(&pool, "legacy-1").await, "account_log_200101");
+    assert_eq!(import(&pool, file).await, (0, 2, 0));
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn archive_partition(pool: PgPool) {
+    let router = setup(pool.clone(), &[]).await;
+    let repository = repository(pool.clone()).await;
+    AccountLogPartitionService::create_partitions(
+        &repository,
+        NaiveDate::from_ymd_opt(2001, 1, 1).unwrap(),
+    )
+    .await
+    .unwrap();
+    let file = "\
+user_id,asset_type,action_type,amount,order_number,created_at
+1,GOLD,AB_INC,100,archived-1,2001-01-05T00:00:00
+1,GOLD,AB_EXP,30,archived-2,2001-01-06T00:00:00
+";
+    assert_eq!(import(&pool, file).await, (2, 0, 0));
+    let (status, _) = post(
+        &router,
+        "/accounts/actions",
+        json!([action(AB_INC, 10.0, "1")]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let before = NaiveDate::from_ymd_opt(2001, 2, 1).unwrap();
+    let partitions = AccountLogPartitionService::fetch_archivable(&pool, before)
+        .await
+        .unwrap();
+    assert_eq!(partitions.len(), 1);
+    let mut archive = Vec::new();
+    let exported = AccountLogPartitionService::archive(&pool, &partitions[0], &mut archive, true)
+        .await
+        .unwrap();
+    assert_eq!(exported, 2);
+    let lines: Vec<Value> = String::from_utf8(archive)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines[0]["order_number"], "archived-1");
+    assert_eq!(lines[1]["tenant_id"], 1);
+    let partitions = AccountLogPartitionModel::fetch_all(&pool).await.unwrap();
+    assert_eq!(partitions[0].name, "account_log_200101");
+    assert_eq!(partitions[0].status, PartitionStatus::Dropped);
+
+    // 哈希链从归档检查点继续，对账以检查点为准
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": GOLD });
+    let (status, verify) = post(&router, "/accounts/logs/verify", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(verify["verified"], 1);
+    assert_eq!(verify["broken_log_id"], Value::Null);
+    assert!(ReconciliationService::reconcile(&pool)
+        .await
+        .unwrap()
+        .is_empty());
+    // 已归档的订单仍不能重复处理
+    assert_eq!(import(&pool, file).await, (0, 2, 0));
+    assert_eq!(available_balance(&router).await, "80".parse().unwrap());
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use axum::{http::StatusCode, Router};
+use chrono::{Duration, Utc};
+use common::*;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+async fn setup_logs(pool: PgPool) -> Router {
+    let router = setup(pool, &[]).await;
+    create_account(&router, USER_ID).await;
+    let payload = json!([action(AB_INC, 100.0, "1")]);
+    assert_eq!(
+        post(&router, "/accounts/actions", payload).await.0,
+        StatusCode::OK
+    );
+    let payload = json!([action(AB_EXP, 30.0, "2")]);
+    assert_eq!(
+        post(&router, "/accounts/actions", payload).await.0,
+        StatusCode::OK
+    );
+    router
+}
+
+fn statement_request(format: &str) -> Value {
+    // 前后各留一天，避免用例跨越零点
+    let today = Utc::now().date_naive();
+    json!({
+        "user_id": USER_ID,
+        "start_date": today - Duration::days(1),
+        "end_date": today + Duration::days(2),
+        "format": format,
+    })
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn export_csv(pool: PgPool) {
+    let router = setup_logs(pool).await;
+    let mut payload = statement_request("csv");
+    payload["asset_type_id"] = json!(GOLD);
+    let (status, body) = post_text(&router, "/accounts/statement", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("record,asset_type,id,created_at,action_type,"));
+    assert!(lines[1].starts_with("opening,GOLD,,,,,,,,0,0,0,0"));
+    assert!(lines[2].starts_with("entry,GOLD,"));
+    assert!(lines[2].contains(",AB_INC,"));
+    assert!(lines[3].contains(",AB_EXP,"));
+    assert!(lines[4].starts_with("closing,GOLD,,,,,,,,70.000000,0,100.000000,30.000000"));
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn export_ndjson(pool: PgPool) {
+    let router = setup_logs(pool).await;
+    let (status, body) =
+        post_text(&router, "/accounts/statement", statement_request("ndjson")).await;
+    assert_eq!(status, StatusCode::OK);
+    let records: Vec<Value> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    // 用户只有`GOLD`账户
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[0]["record"], "opening");
+    assert_eq!(records[0]["available_balance"], "0");
+    assert_eq!(records[1]["action_type"], "AB_INC");
+    assert_eq!(records[1]["amount_available_balance"], "100.000000");
+    assert_eq!(records[3]["record"], "closing");
+    assert_eq!(records[3]["available_balance"], "70.000000");
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn reject_invalid_date_range(pool: PgPool) {
+    let router = setup_logs(pool).await;
+    let mut payload = statement_request("csv");
+    payload["end_date"] = payload["start_date"].clone();
+    let (status, _) = post_text(&router, "/accounts/statement", payload).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,195 @@
+mod common;
+
+use amazing::service::{client::Operator, import::ImportService};
+use axum::{http::StatusCode, Router};
+use common::*;
+use serde_json::{json, Value};
+use sqlx::{types::Decimal, PgPool};
+use tokio::task::JoinSet;
+
+// 第二个租户的`SILVER`启用复式记账，发行上限 150
+const MAX_SUPPLY: &str = "update asset_type set max_supply = 150 where id = 1001";
+
+fn silver_action(amount: f64, order_number: &str) -> Value {
+    json!({
+        "user_id": USER_ID,
+        "asset_type_id": OTHER_SILVER,
+        "action_type_id": OTHER_AB_INC,
+        "amount": amount,
+        "order_number": format!("{order_number:0>32}"),
+        "description": "integration test",
+    })
+}
+
+async fn other_actions(router: &Router, payload: Value) -> StatusCode {
+    post_as(router, OTHER_API_KEY, "/accounts/actions", payload)
+        .await
+        .0
+}
+
+// 随余额变更累计的流通量
+async fn supply(pool: &PgPool) -> Decimal {
+    sqlx::query_scalar("select supply from asset_type where id = $1")
+        .bind(OTHER_SILVER)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+async fn create_silver_account(router: &Router, user_id: Value) {
+    let payload = json!({ "user_id": user_id, "asset_type_id": OTHER_SILVER });
+    let (status, _) = post_as(router, OTHER_API_KEY, "/accounts/new", payload).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn reject_exceeding_max_supply(pool: PgPool) {
+    let router = setup(
+        pool.clone(),
+        &[OTHER_TENANT, OTHER_SILVER_DOUBLE_ENTRY, MAX_SUPPLY],
+    )
+    .await;
+    create_silver_account(&router, json!(USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_ISSUANCE_USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_BURN_USER_ID)).await;
+    let payload = json!([silver_action(100.0, "1")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    let payload = json!([silver_action(60.0, "2")]);
+    assert_eq!(
+        other_actions(&router, payload).await,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    // 达到上限但未超出
+    let payload = json!([silver_action(50.0, "3")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    assert_eq!(supply(&pool).await, Decimal::from(150));
+}
+
+// 部分成功模式中超出发行上限只回滚该操作及其对应分录
+#[sqlx::test(migrations = "./migrations")]
+async fn partial_rejects_exceeding_action_only(pool: PgPool) {
+    let router = setup(
+        pool.clone(),
+        &[OTHER_TENANT, OTHER_SILVER_DOUBLE_ENTRY, MAX_SUPPLY],
+    )
+    .await;
+    create_silver_account(&router, json!(USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_ISSUANCE_USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_BURN_USER_ID)).await;
+    let payload = json!([
+        silver_action(100.0, "1"),
+        silver_action(60.0, "2"),
+        silver_action(50.0, "3"),
+    ]);
+    let (status, results) = post_as(
+        &router,
+        OTHER_API_KEY,
+        "/accounts/actions?mode=partial",
+        payload,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let codes: Vec<_> = results
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| result["code"].as_u64())
+        .collect();
+    assert_eq!(codes, [None, Some(422), None]);
+    assert_eq!(supply(&pool).await, Decimal::from(150));
+    let issuance: Decimal = sqlx::query_scalar(
+        "select available_balance from account where user_id = $1 and asset_type_id = $2",
+    )
+    .bind(OTHER_ISSUANCE_USER_ID)
+    .bind(OTHER_SILVER)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(issuance, Decimal::from(-150));
+}
+
+// 不同用户的并发请求同样在各自的保存点中检查发行上限，超出时只有该操作失败
+#[sqlx::test(migrations = "./migrations")]
+async fn concurrent_partial_respects_max_supply(pool: PgPool) {
+    let router = setup(
+        pool.clone(),
+        &[OTHER_TENANT, OTHER_SILVER_DOUBLE_ENTRY, MAX_SUPPLY],
+    )
+    .await;
+    create_silver_account(&router, json!(OTHER_ISSUANCE_USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_BURN_USER_ID)).await;
+    let mut tasks = JoinSet::new();
+    for user_id in 1..=5 {
+        create_silver_account(&router, json!(user_id)).await;
+        let router = router.clone();
+        tasks.spawn(async move {
+            let mut action = silver_action(50.0, "1");
+            action["user_id"] = json!(user_id);
+            let (status, results) = post_as(
+                &router,
+                OTHER_API_KEY,
+                "/accounts/actions?mode=partial",
+                json!([action]),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            results[0]["code"].as_u64()
+        });
+    }
+    let codes = tasks.join_all().await;
+    assert_eq!(codes.iter().filter(|code| code.is_none()).count(), 3);
+    assert!(codes.iter().all(|code| matches!(code, None | Some(422))));
+    assert_eq!(supply(&pool).await, Decimal::from(150));
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn sum_balances_per_asset_type(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT, OTHER_SILVER_DOUBLE_ENTRY, MAX_SUPPLY]).await;
+    create_silver_account(&router, json!(USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_ISSUANCE_USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_BURN_USER_ID)).await;
+    let payload = json!([silver_action(100.0, "1")]);
+    assert_eq!(other_actions(&router, payload).await, StatusCode::OK);
+    let (status, supplies) = get_as(&router, OTHER_API_KEY, "/assets/supply").await;
+    assert_eq!(status, StatusCode::OK);
+    let supplies = supplies.as_array().unwrap();
+    assert_eq!(supplies.len(), 2);
+    assert_eq!(supplies[0]["asset_type_id"], OTHER_GOLD);
+    assert_eq!(supplies[0]["available_balance"], "0");
+    assert!(supplies[0]["max_supply"].is_null());
+    // 不包括发行系统账户
+    assert_eq!(supplies[1]["asset_type_id"], OTHER_SILVER);
+    assert_eq!(supplies[1]["available_balance"], "100.000000");
+    assert_eq!(supplies[1]["total_income"], "100.000000");
+    assert_eq!(supplies[1]["max_supply"], "150.000000");
+}
+
+// 导入历史数据同样检查发行上限
+#[sqlx::test(migrations = "./migrations")]
+async fn import_respects_max_supply(pool: PgPool) {
+    let router = setup(
+        pool.clone(),
+        &[OTHER_TENANT, OTHER_SILVER_DOUBLE_ENTRY, MAX_SUPPLY],
+    )
+    .await;
+    create_silver_account(&router, json!(USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_ISSUANCE_USER_ID)).await;
+    create_silver_account(&router, json!(OTHER_BURN_USER_ID)).await;
+    let file = "\
+user_id,asset_type,action_type,amount,order_number,created_at,description
+1,SILVER,AB_INC,100,legacy-1,2020-01-01T00:00:00,
+1,SILVER,AB_INC,60,legacy-2,2020-01-02T00:00:00,
+";
+    let operator = Operator {
+        client_id: 0,
+        tenant_id: 2,
+        name: "importer".to_string(),
+    };
+    let repository = repository(pool.clone()).await;
+    let summary =
+        ImportService::import(&repository, file.as_bytes(), &mut Vec::new(), 10, &operator)
+            .await
+            .unwrap();
+    assert_eq!((summary.imported, summary.failed), (1, 1));
+    assert_eq!(supply(&pool).await, Decimal::from(100));
+}
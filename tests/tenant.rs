@@ -0,0 +1,48 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::*;
+use serde_json::json;
+use sqlx::{types::Decimal, PgPool};
+
+// 不同租户的相同`user_id`相互独立
+#[sqlx::test(migrations = "./migrations")]
+async fn same_user_id_in_different_tenants(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT]).await;
+    create_account(&router, USER_ID).await;
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": OTHER_GOLD });
+    let (status, _) = post_as(&router, OTHER_API_KEY, "/accounts/new", payload).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let payload = json!([{
+        "user_id": USER_ID,
+        "asset_type_id": OTHER_GOLD,
+        "action_type_id": OTHER_AB_INC,
+        "amount": 5.0,
+        "order_number": format!("{:0>32}", 1),
+        "description": "integration test",
+    }]);
+    let (status, _) = post_as(&router, OTHER_API_KEY, "/accounts/actions", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(available_balance(&router).await, Decimal::ZERO);
+    let payload = json!({ "user_id": USER_ID });
+    let (status, accounts) = post_as(&router, OTHER_API_KEY, "/accounts/infos", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    let accounts = accounts.as_array().unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0]["asset_type_id"], OTHER_GOLD);
+    assert_eq!(accounts[0]["available_balance"], "5.000000");
+}
+
+// 不能使用其他租户的资产类型、账户操作类型
+#[sqlx::test(migrations = "./migrations")]
+async fn reject_other_tenant_types(pool: PgPool) {
+    let router = setup(pool, &[OTHER_TENANT]).await;
+    create_account(&router, USER_ID).await;
+    let payload = json!({ "user_id": USER_ID, "asset_type_id": GOLD });
+    let (status, _) = post_as(&router, OTHER_API_KEY, "/accounts/info", payload).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let payload = json!([action(OTHER_AB_INC, 1.0, "1")]);
+    let (status, _) = post(&router, "/accounts/actions", payload).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(available_balance(&router).await, Decimal::ZERO);
+}